@@ -0,0 +1,746 @@
+//! AIS ship types & navigational statuses - based on: <https://www.itu.int/rec/R-REC-M.1371>.
+//!
+//! Both are (de)serialized as their numeric code. Deserialization is lenient: besides the codes the
+//! common textual labels (e.g. as used by the DMA or MarineCadastre dumps) are accepted, unknown
+//! values end up as "not available" instead of failing the whole document.
+
+use std::{error, fmt};
+
+/// Errors while parsing ship types & navigational statuses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AisError {
+    UnknownShipType(String),
+    UnknownNavStatus(String),
+}
+
+impl fmt::Display for AisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AisError::UnknownShipType(value) => write!(f, "unknown ship type: {}", value),
+            AisError::UnknownNavStatus(value) => {
+                write!(f, "unknown navigational status: {}", value)
+            }
+        }
+    }
+}
+
+impl error::Error for AisError {}
+
+/// Lower cases the label & replaces everything but letters & digits by single spaces.
+fn normalize(label: &str) -> String {
+    label
+        .split(|item: char| !item.is_ascii_alphanumeric())
+        .filter(|item| !item.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Hazardous category of the cargo - the second digit of the grouped ship types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Hazard {
+    /// All ships of this type.
+    #[default]
+    Unspecified,
+    A,
+    B,
+    C,
+    D,
+    /// Reserved for future use - digits 5 to 8.
+    Reserved(u8),
+    NoAdditionalInformation,
+}
+
+impl Hazard {
+    fn from_digit(digit: u8) -> Self {
+        match digit {
+            0 => Hazard::Unspecified,
+            1 => Hazard::A,
+            2 => Hazard::B,
+            3 => Hazard::C,
+            4 => Hazard::D,
+            9 => Hazard::NoAdditionalInformation,
+            _ => Hazard::Reserved(digit),
+        }
+    }
+
+    fn digit(&self) -> u8 {
+        match self {
+            Hazard::Unspecified => 0,
+            Hazard::A => 1,
+            Hazard::B => 2,
+            Hazard::C => 3,
+            Hazard::D => 4,
+            Hazard::Reserved(digit) => *digit,
+            Hazard::NoAdditionalInformation => 9,
+        }
+    }
+
+    /// Parses the normalized words following the ship type - e.g. "hazardous category a".
+    fn parse(words: &[&str]) -> Option<Self> {
+        match words {
+            [] | ["all", ..] => Some(Hazard::Unspecified),
+            ["hazardous" | "hazard", rest @ ..] => {
+                let rest = match rest {
+                    ["category" | "cat", rest @ ..] => rest,
+                    _ => rest,
+                };
+                match *rest.first()? {
+                    "a" => Some(Hazard::A),
+                    "b" => Some(Hazard::B),
+                    "c" => Some(Hazard::C),
+                    "d" => Some(Hazard::D),
+                    _ => None,
+                }
+            }
+            ["reserved", digit] => match digit.parse() {
+                Ok(digit @ 5..=8) => Some(Hazard::Reserved(digit)),
+                _ => None,
+            },
+            ["no", "additional", "information"] => Some(Hazard::NoAdditionalInformation),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hazard::Unspecified => Ok(()),
+            Hazard::A => write!(f, ", hazardous category A"),
+            Hazard::B => write!(f, ", hazardous category B"),
+            Hazard::C => write!(f, ", hazardous category C"),
+            Hazard::D => write!(f, ", hazardous category D"),
+            Hazard::Reserved(digit) => write!(f, ", reserved {}", digit),
+            Hazard::NoAdditionalInformation => write!(f, ", no additional information"),
+        }
+    }
+}
+
+/// Broad category of ship types - for grouping & styling.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ShipCategory {
+    Unknown,
+    Cargo,
+    Tanker,
+    Passenger,
+    Tug,
+    Fishing,
+    Pleasure,
+    HighSpeed,
+    Special,
+    Other,
+}
+
+impl ShipCategory {
+    /// All categories - the common ones first.
+    pub const ALL: [ShipCategory; 10] = [
+        ShipCategory::Cargo,
+        ShipCategory::Tanker,
+        ShipCategory::Passenger,
+        ShipCategory::Tug,
+        ShipCategory::Fishing,
+        ShipCategory::Pleasure,
+        ShipCategory::HighSpeed,
+        ShipCategory::Special,
+        ShipCategory::Other,
+        ShipCategory::Unknown,
+    ];
+}
+
+impl fmt::Display for ShipCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ShipCategory::Unknown => "Unknown",
+            ShipCategory::Cargo => "Cargo",
+            ShipCategory::Tanker => "Tanker",
+            ShipCategory::Passenger => "Passenger",
+            ShipCategory::Tug => "Tug",
+            ShipCategory::Fishing => "Fishing",
+            ShipCategory::Pleasure => "Pleasure",
+            ShipCategory::HighSpeed => "High speed",
+            ShipCategory::Special => "Special",
+            ShipCategory::Other => "Other",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Ship & cargo type as reported in AIS static data - codes 0 to 99.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ShipType {
+    #[default]
+    NotAvailable,
+    /// Reserved for future use - codes 1 to 19, 38 & 39.
+    Reserved(u8),
+    WingInGround(Hazard),
+    Fishing,
+    Towing,
+    /// Towing with a length exceeding 200 m or a breadth exceeding 25 m.
+    TowingLarge,
+    Dredging,
+    Diving,
+    Military,
+    Sailing,
+    PleasureCraft,
+    HighSpeedCraft(Hazard),
+    Pilot,
+    SearchAndRescue,
+    Tug,
+    PortTender,
+    AntiPollution,
+    LawEnforcement,
+    /// Spare codes 56 & 57 - for assignments to local vessels.
+    LocalVessel(u8),
+    MedicalTransport,
+    /// Noncombatant ship according to RR Resolution No. 18.
+    NonCombatant,
+    Passenger(Hazard),
+    Cargo(Hazard),
+    Tanker(Hazard),
+    Other(Hazard),
+}
+
+/// Labels of the ship types without hazardous category - the first one is used for display.
+const SHIP_TYPE_LABELS: [(ShipType, &[&str]); 17] = [
+    (
+        ShipType::NotAvailable,
+        &["Not available", "undefined", "unknown", "none", ""],
+    ),
+    (ShipType::Fishing, &["Fishing"]),
+    (ShipType::Towing, &["Towing"]),
+    (ShipType::TowingLarge, &["Towing long/wide", "towing large"]),
+    (
+        ShipType::Dredging,
+        &["Dredging", "dredging or underwater ops", "dredger"],
+    ),
+    (ShipType::Diving, &["Diving", "diving ops"]),
+    (ShipType::Military, &["Military", "military ops"]),
+    (ShipType::Sailing, &["Sailing", "sailing vessel"]),
+    (
+        ShipType::PleasureCraft,
+        &["Pleasure", "pleasure craft", "yacht"],
+    ),
+    (ShipType::Pilot, &["Pilot", "pilot vessel"]),
+    (ShipType::SearchAndRescue, &["SAR", "search and rescue"]),
+    (ShipType::Tug, &["Tug"]),
+    (ShipType::PortTender, &["Port tender"]),
+    (
+        ShipType::AntiPollution,
+        &["Anti-pollution", "anti pollution equipment"],
+    ),
+    (ShipType::LawEnforcement, &["Law enforcement"]),
+    (
+        ShipType::MedicalTransport,
+        &["Medical", "medical transport"],
+    ),
+    (
+        ShipType::NonCombatant,
+        &["Not party to conflict", "noncombatant"],
+    ),
+];
+
+/// Ship type of a hazardous category.
+type GroupedShipType = fn(Hazard) -> ShipType;
+
+/// Labels of the ship types grouped by hazardous category - the first one is used for display.
+const GROUPED_SHIP_TYPE_LABELS: [(GroupedShipType, &[&str]); 6] = [
+    (ShipType::WingInGround, &["WIG", "wing in ground"]),
+    (ShipType::HighSpeedCraft, &["HSC", "high speed craft"]),
+    (ShipType::Passenger, &["Passenger"]),
+    (ShipType::Cargo, &["Cargo"]),
+    (ShipType::Tanker, &["Tanker"]),
+    (ShipType::Other, &["Other", "other type"]),
+];
+
+impl ShipType {
+    /// Returns the ship type for an AIS code - None if not within 0 to 99.
+    pub fn from_code(code: u8) -> Option<Self> {
+        let hazard = Hazard::from_digit(code % 10);
+        Some(match code {
+            0 => ShipType::NotAvailable,
+            1..=19 | 38 | 39 => ShipType::Reserved(code),
+            20..=29 => ShipType::WingInGround(hazard),
+            30 => ShipType::Fishing,
+            31 => ShipType::Towing,
+            32 => ShipType::TowingLarge,
+            33 => ShipType::Dredging,
+            34 => ShipType::Diving,
+            35 => ShipType::Military,
+            36 => ShipType::Sailing,
+            37 => ShipType::PleasureCraft,
+            40..=49 => ShipType::HighSpeedCraft(hazard),
+            50 => ShipType::Pilot,
+            51 => ShipType::SearchAndRescue,
+            52 => ShipType::Tug,
+            53 => ShipType::PortTender,
+            54 => ShipType::AntiPollution,
+            55 => ShipType::LawEnforcement,
+            56 | 57 => ShipType::LocalVessel(code),
+            58 => ShipType::MedicalTransport,
+            59 => ShipType::NonCombatant,
+            60..=69 => ShipType::Passenger(hazard),
+            70..=79 => ShipType::Cargo(hazard),
+            80..=89 => ShipType::Tanker(hazard),
+            90..=99 => ShipType::Other(hazard),
+            _ => return None,
+        })
+    }
+
+    /// Returns the AIS code of the ship type.
+    pub fn code(&self) -> u8 {
+        match self {
+            ShipType::NotAvailable => 0,
+            ShipType::Reserved(code) | ShipType::LocalVessel(code) => *code,
+            ShipType::WingInGround(hazard) => 20 + hazard.digit(),
+            ShipType::Fishing => 30,
+            ShipType::Towing => 31,
+            ShipType::TowingLarge => 32,
+            ShipType::Dredging => 33,
+            ShipType::Diving => 34,
+            ShipType::Military => 35,
+            ShipType::Sailing => 36,
+            ShipType::PleasureCraft => 37,
+            ShipType::HighSpeedCraft(hazard) => 40 + hazard.digit(),
+            ShipType::Pilot => 50,
+            ShipType::SearchAndRescue => 51,
+            ShipType::Tug => 52,
+            ShipType::PortTender => 53,
+            ShipType::AntiPollution => 54,
+            ShipType::LawEnforcement => 55,
+            ShipType::MedicalTransport => 58,
+            ShipType::NonCombatant => 59,
+            ShipType::Passenger(hazard) => 60 + hazard.digit(),
+            ShipType::Cargo(hazard) => 70 + hazard.digit(),
+            ShipType::Tanker(hazard) => 80 + hazard.digit(),
+            ShipType::Other(hazard) => 90 + hazard.digit(),
+        }
+    }
+
+    /// Returns the broad category of the ship type.
+    pub fn category(&self) -> ShipCategory {
+        match self {
+            ShipType::NotAvailable | ShipType::Reserved(_) => ShipCategory::Unknown,
+            ShipType::Cargo(_) => ShipCategory::Cargo,
+            ShipType::Tanker(_) => ShipCategory::Tanker,
+            ShipType::Passenger(_) => ShipCategory::Passenger,
+            ShipType::Tug | ShipType::Towing | ShipType::TowingLarge => ShipCategory::Tug,
+            ShipType::Fishing => ShipCategory::Fishing,
+            ShipType::Sailing | ShipType::PleasureCraft => ShipCategory::Pleasure,
+            ShipType::HighSpeedCraft(_) | ShipType::WingInGround(_) => ShipCategory::HighSpeed,
+            ShipType::Other(_) => ShipCategory::Other,
+            _ => ShipCategory::Special,
+        }
+    }
+}
+
+impl fmt::Display for ShipType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShipType::Reserved(code) => return write!(f, "Reserved ({})", code),
+            ShipType::LocalVessel(code) => return write!(f, "Local vessel ({})", code),
+            _ => {}
+        }
+        if let Some((_, labels)) = SHIP_TYPE_LABELS.iter().find(|(item, _)| item == self) {
+            return write!(f, "{}", labels[0]);
+        }
+        let hazard = Hazard::from_digit(self.code() % 10);
+        let (_, labels) = GROUPED_SHIP_TYPE_LABELS
+            .iter()
+            .find(|(item, _)| item(hazard) == *self)
+            .expect("all ship types have a label.");
+        write!(f, "{}{}", labels[0], hazard)
+    }
+}
+
+impl std::str::FromStr for ShipType {
+    type Err = AisError;
+
+    /// Parses a code or label - e.g. "70", "Cargo" or "Cargo, hazardous category A".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || AisError::UnknownShipType(s.to_string());
+        if let Ok(code) = s.trim().parse::<u8>() {
+            return ShipType::from_code(code).ok_or_else(unknown);
+        }
+        let label = normalize(s);
+        let words: Vec<&str> = label.split(' ').filter(|item| !item.is_empty()).collect();
+        match words[..] {
+            ["reserved", code] => {
+                return match code.parse().ok().and_then(ShipType::from_code) {
+                    Some(res @ ShipType::Reserved(_)) => Ok(res),
+                    _ => Err(unknown()),
+                }
+            }
+            ["local", "vessel", code] => {
+                return match code.parse().ok().and_then(ShipType::from_code) {
+                    Some(res @ ShipType::LocalVessel(_)) => Ok(res),
+                    _ => Err(unknown()),
+                }
+            }
+            _ => {}
+        }
+        if let Some((res, _)) = SHIP_TYPE_LABELS
+            .iter()
+            .find(|(_, labels)| labels.iter().any(|item| normalize(item) == label))
+        {
+            return Ok(*res);
+        }
+        // the label of the type is followed by the hazardous category.
+        for split in (1..=words.len()).rev() {
+            let base = words[..split].join(" ");
+            let found = GROUPED_SHIP_TYPE_LABELS
+                .iter()
+                .find(|(_, labels)| labels.iter().any(|item| normalize(item) == base));
+            if let Some((group, _)) = found {
+                let hazard = Hazard::parse(&words[split..]).ok_or_else(unknown)?;
+                return Ok(group(hazard));
+            }
+        }
+        Err(unknown())
+    }
+}
+
+/// Navigational status as reported in AIS position reports - codes 0 to 15.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NavStatus {
+    UnderWayUsingEngine,
+    AtAnchor,
+    NotUnderCommand,
+    RestrictedManoeuvrability,
+    ConstrainedByDraught,
+    Moored,
+    Aground,
+    EngagedInFishing,
+    UnderWaySailing,
+    /// Reserved for high speed craft.
+    ReservedHsc,
+    /// Reserved for wing in ground craft.
+    ReservedWig,
+    PowerDrivenTowingAstern,
+    PowerDrivenPushingAhead,
+    Reserved,
+    AisSartActive,
+    #[default]
+    NotDefined,
+}
+
+/// Navigational statuses in the order of their codes with their labels - the first one is used
+/// for display.
+const NAV_STATUS_LABELS: [(NavStatus, &[&str]); 16] = [
+    (
+        NavStatus::UnderWayUsingEngine,
+        &["Under way using engine", "under way", "underway"],
+    ),
+    (NavStatus::AtAnchor, &["At anchor", "anchored"]),
+    (NavStatus::NotUnderCommand, &["Not under command"]),
+    (
+        NavStatus::RestrictedManoeuvrability,
+        &["Restricted manoeuvrability", "restricted maneuverability"],
+    ),
+    (
+        NavStatus::ConstrainedByDraught,
+        &["Constrained by her draught", "constrained by draught"],
+    ),
+    (NavStatus::Moored, &["Moored"]),
+    (NavStatus::Aground, &["Aground"]),
+    (
+        NavStatus::EngagedInFishing,
+        &["Engaged in fishing", "fishing"],
+    ),
+    (
+        NavStatus::UnderWaySailing,
+        &["Under way sailing", "sailing"],
+    ),
+    (
+        NavStatus::ReservedHsc,
+        &["Reserved for future amendment [HSC]", "reserved hsc"],
+    ),
+    (
+        NavStatus::ReservedWig,
+        &["Reserved for future amendment [WIG]", "reserved wig"],
+    ),
+    (
+        NavStatus::PowerDrivenTowingAstern,
+        &["Power-driven vessel towing astern"],
+    ),
+    (
+        NavStatus::PowerDrivenPushingAhead,
+        &["Power-driven vessel pushing ahead or towing alongside"],
+    ),
+    (
+        NavStatus::Reserved,
+        &["Reserved for future use", "reserved"],
+    ),
+    (
+        NavStatus::AisSartActive,
+        &["AIS-SART is active", "ais sart"],
+    ),
+    (
+        NavStatus::NotDefined,
+        &[
+            "Not defined",
+            "undefined",
+            "unknown",
+            "unknown value",
+            "default",
+            "",
+        ],
+    ),
+];
+
+impl NavStatus {
+    /// Returns the navigational status for an AIS code - None if not within 0 to 15.
+    pub fn from_code(code: u8) -> Option<Self> {
+        NAV_STATUS_LABELS
+            .get(code as usize)
+            .map(|(status, _)| *status)
+    }
+
+    /// Returns the AIS code of the navigational status.
+    pub fn code(&self) -> u8 {
+        NAV_STATUS_LABELS
+            .iter()
+            .position(|(item, _)| item == self)
+            .expect("all statuses have a code.") as u8
+    }
+}
+
+impl fmt::Display for NavStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", NAV_STATUS_LABELS[self.code() as usize].1[0])
+    }
+}
+
+impl std::str::FromStr for NavStatus {
+    type Err = AisError;
+
+    /// Parses a code or label - e.g. "5" or "Moored".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || AisError::UnknownNavStatus(s.to_string());
+        if let Ok(code) = s.trim().parse::<u8>() {
+            return NavStatus::from_code(code).ok_or_else(unknown);
+        }
+        let label = normalize(s);
+        NAV_STATUS_LABELS
+            .iter()
+            .find(|(_, labels)| labels.iter().any(|item| normalize(item) == label))
+            .map(|(status, _)| *status)
+            .ok_or_else(unknown)
+    }
+}
+
+/// Visitor accepting codes & labels - falling back to the default for unknown values.
+struct LenientVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T: std::str::FromStr + Default> serde::de::Visitor<'_> for LenientVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an AIS code or label")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<T, E> {
+        Ok(value.to_string().parse().unwrap_or_default())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<T, E> {
+        Ok(value.to_string().parse().unwrap_or_default())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, _: f64) -> Result<T, E> {
+        Ok(T::default())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<T, E> {
+        Ok(value.parse().unwrap_or_default())
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<T, E> {
+        Ok(T::default())
+    }
+}
+
+macro_rules! impl_serde {
+    ($name:ident) => {
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(self.code())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(LenientVisitor(std::marker::PhantomData))
+            }
+        }
+    };
+}
+
+impl_serde!(ShipType);
+impl_serde!(NavStatus);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_ship_type_from_str_for_success() {
+        "70".parse::<ShipType>().expect("a valid ship type.");
+        "Cargo".parse::<ShipType>().expect("a valid ship type.");
+    }
+
+    #[test]
+    fn test_nav_status_from_str_for_success() {
+        "5".parse::<NavStatus>().expect("a valid status.");
+        "Moored".parse::<NavStatus>().expect("a valid status.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_ship_type_from_str_for_failure() {
+        for label in [
+            "100",
+            "-1",
+            "boat",
+            "cargo, hazardous category E",
+            "Tug, hazardous A",
+            "reserved 20",
+        ] {
+            assert_eq!(
+                label.parse::<ShipType>(),
+                Err(AisError::UnknownShipType(label.to_string()))
+            );
+        }
+        assert_eq!(
+            "boat".parse::<ShipType>().err().unwrap().to_string(),
+            "unknown ship type: boat"
+        );
+        assert_eq!(ShipType::from_code(100), None);
+    }
+
+    #[test]
+    fn test_nav_status_from_str_for_failure() {
+        for label in ["16", "floating", "moored 2"] {
+            assert_eq!(
+                label.parse::<NavStatus>(),
+                Err(AisError::UnknownNavStatus(label.to_string()))
+            );
+        }
+        assert_eq!(NavStatus::from_code(16), None);
+    }
+
+    #[test]
+    fn test_deserialize_for_failure() {
+        // unknown values are not available - instead of failing.
+        let res: Vec<ShipType> = serde_json::from_str("[\"boat\", 100, -1, 1.5, null]").unwrap();
+        assert!(res.iter().all(|item| *item == ShipType::NotAvailable));
+        let res: Vec<NavStatus> = serde_json::from_str("[\"floating\", 16, null]").unwrap();
+        assert!(res.iter().all(|item| *item == NavStatus::NotDefined));
+        assert!(serde_json::from_str::<ShipType>("[70]").is_err());
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_ship_category_for_sanity() {
+        // all categories are listed once.
+        for code in 0..100 {
+            let category = ShipType::from_code(code).unwrap().category();
+            assert!(ShipCategory::ALL.contains(&category));
+        }
+        let unique: std::collections::HashSet<_> = ShipCategory::ALL.into_iter().collect();
+        assert_eq!(unique.len(), ShipCategory::ALL.len());
+    }
+
+    #[test]
+    fn test_ship_type_for_sanity() {
+        for code in 0..100 {
+            let ship_type = ShipType::from_code(code).unwrap();
+            assert_eq!(ship_type.code(), code);
+            // the labels parse back to the same type.
+            assert_eq!(ship_type.to_string().parse::<ShipType>(), Ok(ship_type));
+        }
+        assert_eq!(
+            ShipType::from_code(74).unwrap().to_string(),
+            "Cargo, hazardous category D"
+        );
+        assert_eq!(ShipType::from_code(52).unwrap().to_string(), "Tug");
+
+        // lenient parsing.
+        for label in ["Cargo", "cargo", " 70", "Cargo, all ships of this type"] {
+            assert_eq!(label.parse(), Ok(ShipType::Cargo(Hazard::Unspecified)));
+        }
+        for label in [
+            "Cargo, hazardous A",
+            "CARGO - Hazard A (Major)",
+            "cargo hazardous cat a",
+            "71",
+        ] {
+            assert_eq!(label.parse(), Ok(ShipType::Cargo(Hazard::A)));
+        }
+        assert_eq!("Towing long/wide".parse(), Ok(ShipType::TowingLarge));
+        assert_eq!("Anti-pollution".parse(), Ok(ShipType::AntiPollution));
+        assert_eq!(
+            "High Speed Craft".parse(),
+            Ok(ShipType::HighSpeedCraft(Hazard::Unspecified))
+        );
+        assert_eq!("Undefined".parse(), Ok(ShipType::NotAvailable));
+        assert_eq!(
+            "Other type, no additional information"
+                .parse::<ShipType>()
+                .unwrap()
+                .code(),
+            99
+        );
+
+        // grouping.
+        let categories: Vec<ShipCategory> = ["Cargo", "cargo", "70", "Cargo, hazardous A", "79"]
+            .iter()
+            .map(|item| item.parse::<ShipType>().unwrap().category())
+            .collect();
+        assert!(categories.iter().all(|item| *item == ShipCategory::Cargo));
+        assert_eq!(ShipType::Towing.category(), ShipCategory::Tug);
+        assert_eq!(ShipType::Pilot.category(), ShipCategory::Special);
+        assert_eq!(ShipType::Reserved(12).category(), ShipCategory::Unknown);
+        assert_eq!(
+            serde_json::to_string(&ShipCategory::HighSpeed).unwrap(),
+            "\"high_speed\""
+        );
+    }
+
+    #[test]
+    fn test_nav_status_for_sanity() {
+        for code in 0..16 {
+            let status = NavStatus::from_code(code).unwrap();
+            assert_eq!(status.code(), code);
+            assert_eq!(status.to_string().parse::<NavStatus>(), Ok(status));
+        }
+        assert_eq!(NavStatus::default().code(), 15);
+        assert_eq!("Under way".parse(), Ok(NavStatus::UnderWayUsingEngine));
+        assert_eq!(
+            "Restricted maneuverability".parse(),
+            Ok(NavStatus::RestrictedManoeuvrability)
+        );
+        assert_eq!("AIS-SART".parse(), Ok(NavStatus::AisSartActive));
+        assert_eq!("Unknown value".parse(), Ok(NavStatus::NotDefined));
+    }
+
+    #[test]
+    fn test_serialize_for_sanity() {
+        let json =
+            serde_json::to_string(&(ShipType::Tanker(Hazard::B), NavStatus::Moored)).unwrap();
+        assert_eq!(json, "[82,5]");
+        let res: (ShipType, NavStatus) = serde_json::from_str(&json).unwrap();
+        assert_eq!(res, (ShipType::Tanker(Hazard::B), NavStatus::Moored));
+        let res: (ShipType, NavStatus) = serde_json::from_str("[\"Tanker\", \"moored\"]").unwrap();
+        assert_eq!(
+            res,
+            (ShipType::Tanker(Hazard::Unspecified), NavStatus::Moored)
+        );
+    }
+}
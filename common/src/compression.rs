@@ -0,0 +1,232 @@
+use std::io::{self, Write};
+use std::sync;
+
+use rocket::fairing;
+
+/// Bodies smaller than this (in bytes) are not worth compressing.
+const MIN_SIZE: usize = 1024;
+
+/// Quality used for brotli - favours speed as the responses are compressed on the fly.
+const BROTLI_QUALITY: u32 = 5;
+
+/// Window size (log2) used for brotli.
+const BROTLI_WINDOW: u32 = 22;
+
+/// Encodings supported - in order of preference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn compress(&self, body: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut writer =
+                    brotli::CompressorWriter::new(vec![], 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+                writer.write_all(body)?;
+                Ok(writer.into_inner())
+            }
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Picks the encoding based on the `Accept-Encoding` header of the request.
+fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let mut res: Option<(Encoding, f64)> = None;
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let quality = parts
+            .filter_map(|param| param.strip_prefix("q="))
+            .find_map(|value| value.parse::<f64>().ok())
+            .unwrap_or(1.0);
+        let encoding = match name.as_str() {
+            "br" => Encoding::Brotli,
+            "gzip" | "*" => Encoding::Gzip,
+            _ => continue,
+        };
+        // on equal quality the first (preferred) encoding wins.
+        let better = match res {
+            Some((current, q)) => {
+                quality > q || (quality == q && encoding == Encoding::Brotli && current != encoding)
+            }
+            None => true,
+        };
+        if quality > 0.0 && better {
+            res = Some((encoding, quality));
+        }
+    }
+    res.map(|(encoding, _)| encoding)
+}
+
+/// Fairing compressing response bodies using brotli or gzip - as accepted by the client.
+///
+/// Streamed bodies & bodies already encoded are left as is; all others carry `Vary:
+/// Accept-Encoding` - compressed or not.
+pub struct Compression;
+
+#[rocket::async_trait]
+impl fairing::Fairing for Compression {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Response compression",
+            kind: fairing::Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r rocket::Request<'_>, res: &mut rocket::Response<'r>) {
+        if res.headers().contains("Content-Encoding")
+            || res.body().preset_size().is_none_or(|size| size < MIN_SIZE)
+        {
+            return;
+        }
+        // the body depends on the accepted encodings - whether compressed or not.
+        res.adjoin_raw_header("Vary", "Accept-Encoding");
+        let Some(encoding) = req.headers().get_one("Accept-Encoding").and_then(negotiate) else {
+            return;
+        };
+        let body = match res.body_mut().to_bytes().await {
+            Ok(body) => sync::Arc::new(body),
+            Err(_) => {
+                res.set_status(rocket::http::Status::InternalServerError);
+                return;
+            }
+        };
+        let shared = body.clone();
+        let compressed =
+            rocket::tokio::task::spawn_blocking(move || encoding.compress(&shared)).await;
+        match compressed {
+            Ok(Ok(compressed)) => {
+                res.set_sized_body(compressed.len(), io::Cursor::new(compressed));
+                res.set_raw_header("Content-Encoding", encoding.name());
+            }
+            // fall back to the uncompressed body.
+            _ => {
+                let body = sync::Arc::try_unwrap(body).unwrap_or_else(|body| body.to_vec());
+                res.set_sized_body(body.len(), io::Cursor::new(body));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http;
+    use rocket::local::blocking;
+    use std::io::Read;
+
+    #[rocket::get("/<size>")]
+    fn index(size: usize) -> String {
+        "a".repeat(size)
+    }
+
+    fn get_client() -> blocking::Client {
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .attach(Compression);
+        blocking::Client::tracked(rocket).expect("a valid test client.")
+    }
+
+    fn accept_encoding(value: &'static str) -> http::Header<'static> {
+        http::Header::new("Accept-Encoding", value)
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_compress_for_success() {
+        Encoding::Brotli.compress(b"foo").expect("valid brotli.");
+        Encoding::Gzip.compress(b"foo").expect("valid gzip.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_negotiate_for_failure() {
+        assert_eq!(negotiate(""), None);
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(negotiate("deflate, compress"), None);
+        assert_eq!(negotiate("gzip;q=0, br;q=0"), None);
+    }
+
+    #[test]
+    fn test_compression_for_failure() {
+        let client = get_client();
+
+        // not accepted - still varies by the accepted encodings for caches.
+        let response = client.get("/2048").dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), None);
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+        assert_eq!(response.into_string().unwrap().len(), 2048);
+        let response = client
+            .get("/2048")
+            .header(accept_encoding("identity"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), None);
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+
+        // too small.
+        let response = client.get("/10").header(accept_encoding("gzip")).dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), None);
+        assert_eq!(response.headers().get_one("Vary"), None);
+        assert_eq!(response.into_string(), Some("a".repeat(10)));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_negotiate_for_sanity() {
+        assert_eq!(negotiate("gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("br;q=0.5, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("BR;q=0.8, gzip;q=0.2"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("*"), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn test_compression_for_sanity() {
+        let client = get_client();
+
+        let response = client
+            .get("/2048")
+            .header(accept_encoding("gzip"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+        let body = response.into_bytes().unwrap();
+        assert!(body.len() < 2048);
+        let mut res = String::new();
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_string(&mut res)
+            .unwrap();
+        assert_eq!(res, "a".repeat(2048));
+
+        let response = client
+            .get("/2048")
+            .header(accept_encoding("gzip, br"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("br"));
+        let body = response.into_bytes().unwrap();
+        let mut res = String::new();
+        brotli::Decompressor::new(body.as_slice(), 4096)
+            .read_to_string(&mut res)
+            .unwrap();
+        assert_eq!(res, "a".repeat(2048));
+    }
+}
@@ -0,0 +1,49 @@
+//! Configuration of the rocket services.
+
+use rocket::figment::{
+    providers::{Env, Format, Toml},
+    Figment, Profile,
+};
+
+/// Returns the configuration of a service - listening on all addresses at the given port by
+/// default.
+///
+/// Like `rocket::Config::figment()`, `Rocket.toml` & the `ROCKET_` environment variables take
+/// precedence - e.g. `ROCKET_PORT=8001`.
+pub fn figment(port: u16) -> Figment {
+    Figment::from(rocket::Config::default())
+        .merge(("address", "0.0.0.0"))
+        .merge(("port", port))
+        .merge(Toml::file(Env::var_or("ROCKET_CONFIG", "Rocket.toml")).nested())
+        .merge(Env::prefixed("ROCKET_").ignore(&["PROFILE"]).global())
+        .select(Profile::from_env_or(
+            "ROCKET_PROFILE",
+            rocket::Config::DEFAULT_PROFILE,
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    // Tests for success.
+
+    #[test]
+    fn test_figment_for_success() {
+        let config: rocket::Config = figment(8765).extract().expect("a valid config.");
+        assert_eq!(config.address.to_string(), "0.0.0.0");
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_figment_for_sanity() {
+        // the environment overrides the defaults of the service.
+        env::set_var("ROCKET_PORT", "8001");
+        let res = figment(8765).extract_inner::<u16>("port");
+        env::remove_var("ROCKET_PORT");
+        assert_eq!(res.unwrap(), 8001);
+        assert_eq!(figment(8765).extract_inner::<u16>("port").unwrap(), 8765);
+    }
+}
@@ -0,0 +1,271 @@
+use rocket::{fairing, http};
+
+/// Default value for how long (in seconds) browsers may cache a preflight response.
+const DEFAULT_MAX_AGE: usize = 86400;
+
+/// Cross-Origin Resource Sharing (CORS) settings for a rocket service.
+///
+/// Can be configured through the `cors` table of the rocket configuration - e.g. using
+/// `ROCKET_CORS={allowed_origins=["http://localhost:8080"]}`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Cors {
+    /// Origins allowed to access the service; `*` allows any.
+    pub allowed_origins: Vec<String>,
+    /// HTTP methods allowed for cross origin requests.
+    pub allowed_methods: Vec<String>,
+    /// HTTP headers allowed in cross origin requests.
+    pub allowed_headers: Vec<String>,
+    /// HTTP headers of the responses exposed to the browser.
+    pub exposed_headers: Vec<String>,
+    /// Time in seconds a preflight response can be cached - none if not set.
+    pub max_age: Option<usize>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Cors {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()],
+            allowed_headers: vec![
+                "Content-Type".to_string(),
+                crate::REQUEST_ID_HEADER.to_string(),
+            ],
+            exposed_headers: vec![crate::REQUEST_ID_HEADER.to_string()],
+            max_age: Some(DEFAULT_MAX_AGE),
+        }
+    }
+}
+
+impl Cors {
+    /// Returns the value for the Access-Control-Allow-Origin header - none if origin is not allowed.
+    fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self.allowed_origins.iter().any(|item| item == "*") {
+            Some("*".to_string())
+        } else if self.allowed_origins.iter().any(|item| item == origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl fairing::Fairing for Cors {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "CORS",
+            kind: fairing::Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r rocket::Request<'_>, res: &mut rocket::Response<'r>) {
+        // unless any origin is allowed, the headers depend on the origin - also if missing or
+        // not allowed - so caches must not share the responses between origins.
+        if !self.allowed_origins.iter().any(|item| item == "*") {
+            res.adjoin_raw_header("Vary", "Origin");
+        }
+        let origin = match req.headers().get_one("Origin") {
+            Some(origin) => origin,
+            None => return,
+        };
+        let allowed = match self.allow_origin(origin) {
+            Some(allowed) => allowed,
+            None => return,
+        };
+        res.set_raw_header("Access-Control-Allow-Origin", allowed);
+        if !self.exposed_headers.is_empty() {
+            res.set_raw_header(
+                "Access-Control-Expose-Headers",
+                self.exposed_headers.join(", "),
+            );
+        }
+
+        // preflight requests are answered here - no need for OPTIONS routes in the services.
+        if req.method() == http::Method::Options
+            && req.headers().contains("Access-Control-Request-Method")
+        {
+            res.set_status(http::Status::NoContent);
+            res.set_raw_header(
+                "Access-Control-Allow-Methods",
+                self.allowed_methods.join(", "),
+            );
+            res.set_raw_header(
+                "Access-Control-Allow-Headers",
+                self.allowed_headers.join(", "),
+            );
+            if let Some(max_age) = self.max_age {
+                res.set_raw_header("Access-Control-Max-Age", max_age.to_string());
+            }
+            res.set_sized_body(0, std::io::Cursor::new(""));
+            res.remove_header("Content-Type");
+        }
+    }
+}
+
+/// Returns a fairing which attaches CORS support based on the `cors` section of the configuration.
+pub fn fairing() -> fairing::AdHoc {
+    fairing::AdHoc::try_on_ignite("CORS configuration", |rocket| async move {
+        let cors = match rocket.figment().find_value("cors") {
+            Ok(_) => match rocket.figment().extract_inner::<Cors>("cors") {
+                Ok(cors) => cors,
+                Err(err) => {
+                    rocket::config::pretty_print_error(err);
+                    return Err(rocket);
+                }
+            },
+            Err(_) => Cors::default(),
+        };
+        Ok(rocket.attach(cors))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking;
+
+    #[rocket::get("/")]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    fn get_client(cors: Cors) -> blocking::Client {
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .attach(cors);
+        blocking::Client::tracked(rocket).expect("a valid test client.")
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_fairing_for_success() {
+        let figment = rocket::Config::figment().merge(("cors.max_age", 10));
+        let client = blocking::Client::tracked(rocket::custom(figment).attach(fairing()))
+            .expect("a valid test client.");
+        client.get("/").dispatch();
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_fairing_for_failure() {
+        let figment = rocket::Config::figment().merge(("cors.max_age", "foo"));
+        match blocking::Client::tracked(rocket::custom(figment).attach(fairing())) {
+            Ok(_) => panic!("Invalid configuration should abort."),
+            Err(err) => assert!(matches!(
+                err.kind(),
+                rocket::error::ErrorKind::FailedFairings(_)
+            )),
+        }
+    }
+
+    #[test]
+    fn test_on_response_for_failure() {
+        // origin not allowed --> no headers.
+        let client = get_client(Cors {
+            allowed_origins: vec!["http://example.com".to_string()],
+            ..Default::default()
+        });
+        let response = client
+            .get("/")
+            .header(http::Header::new("Origin", "http://foo.bar"))
+            .dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+        // ... but varies by origin - as does a response without one.
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+        let response = client.get("/").dispatch();
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_on_response_for_sanity() {
+        // no origin --> no headers.
+        let client = get_client(Cors::default());
+        let response = client.get("/").dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+        assert_eq!(response.headers().get_one("Vary"), None);
+
+        // any origin.
+        let response = client
+            .get("/")
+            .header(http::Header::new("Origin", "http://foo.bar"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("*")
+        );
+        assert_eq!(response.into_string(), Some("hello".into()));
+
+        // specific origin.
+        let client = get_client(Cors {
+            allowed_origins: vec!["http://example.com".to_string()],
+            ..Default::default()
+        });
+        let response = client
+            .get("/")
+            .header(http::Header::new("Origin", "http://example.com"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("http://example.com")
+        );
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+        assert_eq!(
+            response.headers().get_one("Access-Control-Expose-Headers"),
+            Some("X-Request-Id")
+        );
+
+        // preflight.
+        let response = client
+            .options("/vessels")
+            .header(http::Header::new("Origin", "http://example.com"))
+            .header(http::Header::new("Access-Control-Request-Method", "POST"))
+            .dispatch();
+        assert_eq!(response.status(), http::Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Methods"),
+            Some("GET, POST, OPTIONS")
+        );
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Headers"),
+            Some("Content-Type, X-Request-Id")
+        );
+        assert_eq!(
+            response.headers().get_one("Access-Control-Max-Age"),
+            Some("86400")
+        );
+    }
+
+    #[test]
+    fn test_fairing_for_sanity() {
+        let figment = rocket::Config::figment()
+            .merge(("cors.allowed_origins", ["http://example.com"]))
+            .merge(("cors.max_age", 10));
+        let client = blocking::Client::tracked(rocket::custom(figment).attach(fairing()))
+            .expect("a valid test client.");
+        let response = client
+            .options("/")
+            .header(http::Header::new("Origin", "http://example.com"))
+            .header(http::Header::new("Access-Control-Request-Method", "GET"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Max-Age"),
+            Some("10")
+        );
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("http://example.com")
+        );
+    }
+}
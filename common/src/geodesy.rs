@@ -0,0 +1,454 @@
+//! Geodesic calculations on coordinates - based on: <https://www.movable-type.co.uk/scripts/latlong.html>.
+//!
+//! Unless noted otherwise a spherical earth model is used, the coordinates are given as
+//! latitude/longitude in degrees, bearings in degrees clockwise from north and distances in meters.
+
+use crate::{normalize_longitude, Coordinate, RADIUS_EARTH};
+
+/// Semi-major axis of the WGS84 ellipsoid.
+const WGS84_A: f64 = 6378137.0;
+
+/// Flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+/// Maximum number of iterations for Vincenty's formulae.
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+
+/// Accuracy of the ellipsoidal distance calculation - the tolerance on the change of longitude on
+/// the auxiliary sphere at which the iteration stops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Accuracy {
+    /// ~1 m.
+    Low,
+    /// ~1 mm.
+    Medium,
+    /// ~0.1 mm and better.
+    High,
+}
+
+impl Accuracy {
+    fn tolerance(self) -> f64 {
+        match self {
+            Accuracy::Low => 1e-7,
+            Accuracy::Medium => 1e-10,
+            Accuracy::High => 1e-12,
+        }
+    }
+}
+
+/// Earth model used for calculating distances.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// Spherical model using the haversine formula - fast, errors up to ~0.5%.
+    Spherical,
+    /// WGS84 ellipsoid using Vincenty's inverse formula with the given accuracy.
+    Ellipsoidal(Accuracy),
+}
+
+/// Normalizes a bearing to the range [0, 360).
+fn normalize_bearing(bearing: f64) -> f64 {
+    (bearing + 360.0) % 360.0
+}
+
+/// Calculates the distance between two coordinates using the given earth model.
+///
+/// Returns none if the ellipsoidal calculation does not converge - e.g. for nearly antipodal points.
+pub fn distance(src: &Coordinate, trg: &Coordinate, model: Model) -> Option<f64> {
+    match model {
+        Model::Spherical => Some(crate::distance(src.lat, src.lon, trg.lat, trg.lon)),
+        Model::Ellipsoidal(accuracy) => vincenty_distance(src, trg, accuracy.tolerance()),
+    }
+}
+
+/// Distance on the WGS84 ellipsoid - based on: <https://en.wikipedia.org/wiki/Vincenty%27s_formulae>.
+fn vincenty_distance(src: &Coordinate, trg: &Coordinate, tolerance: f64) -> Option<f64> {
+    let b = (1.0 - WGS84_F) * WGS84_A;
+    let l = (trg.lon - src.lon).to_radians();
+    let u_1 = ((1.0 - WGS84_F) * src.lat.to_radians().tan()).atan();
+    let u_2 = ((1.0 - WGS84_F) * trg.lat.to_radians().tan()).atan();
+    let (sin_u_1, cos_u_1) = u_1.sin_cos();
+    let (sin_u_2, cos_u_2) = u_2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u_2 * sin_lambda).powi(2)
+            + (cos_u_1 * sin_u_2 - sin_u_1 * cos_u_2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            return Some(0.0); // coincident points.
+        }
+        let cos_sigma = sin_u_1 * sin_u_2 + cos_u_1 * cos_u_2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u_1 * cos_u_2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha.powi(2);
+        // on the equator cos_sq_alpha is zero.
+        let cos_2_sigma_m = if cos_sq_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u_1 * sin_u_2 / cos_sq_alpha
+        } else {
+            0.0
+        };
+        let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+        let lambda_prev = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2_sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2_sigma_m.powi(2))));
+
+        if (lambda - lambda_prev).abs() < tolerance {
+            let u_sq = cos_sq_alpha * (WGS84_A.powi(2) - b.powi(2)) / b.powi(2);
+            let a_k =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let b_k = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = b_k
+                * sin_sigma
+                * (cos_2_sigma_m
+                    + b_k / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2_sigma_m.powi(2))
+                            - b_k / 6.0
+                                * cos_2_sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2_sigma_m.powi(2))));
+            return Some(b * a_k * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// Calculates the initial bearing when following the great circle from source to target.
+pub fn initial_bearing(src: &Coordinate, trg: &Coordinate) -> f64 {
+    let src_lat = src.lat.to_radians();
+    let trg_lat = trg.lat.to_radians();
+    let long_delta = (trg.lon - src.lon).to_radians();
+
+    let y = long_delta.sin() * trg_lat.cos();
+    let x = src_lat.cos() * trg_lat.sin() - src_lat.sin() * trg_lat.cos() * long_delta.cos();
+    normalize_bearing(y.atan2(x).to_degrees())
+}
+
+/// Calculates the final bearing when arriving at the target following the great circle from source.
+pub fn final_bearing(src: &Coordinate, trg: &Coordinate) -> f64 {
+    normalize_bearing(initial_bearing(trg, src) + 180.0)
+}
+
+/// Calculates the destination when travelling the given distance on the given initial bearing.
+pub fn destination(src: &Coordinate, bearing: f64, distance: f64) -> Coordinate {
+    let angular_distance = distance / RADIUS_EARTH;
+    let bearing = bearing.to_radians();
+    let src_lat = src.lat.to_radians();
+    let src_long = src.lon.to_radians();
+
+    let trg_lat = (src_lat.sin() * angular_distance.cos()
+        + src_lat.cos() * angular_distance.sin() * bearing.cos())
+    .asin();
+    let trg_long = src_long
+        + (bearing.sin() * angular_distance.sin() * src_lat.cos())
+            .atan2(angular_distance.cos() - src_lat.sin() * trg_lat.sin());
+    Coordinate {
+        lat: trg_lat.to_degrees(),
+        lon: normalize_longitude(trg_long.to_degrees()),
+    }
+}
+
+/// Calculates the (signed) distance of a point from the great circle defined by a path start and
+/// end - negative if the point is left of the path.
+pub fn cross_track_distance(point: &Coordinate, start: &Coordinate, end: &Coordinate) -> f64 {
+    let angular_distance =
+        crate::distance(start.lat, start.lon, point.lat, point.lon) / RADIUS_EARTH;
+    let bearing_point = initial_bearing(start, point).to_radians();
+    let bearing_path = initial_bearing(start, end).to_radians();
+
+    (angular_distance.sin() * (bearing_point - bearing_path).sin()).asin() * RADIUS_EARTH
+}
+
+/// Calculates the distance from the path start to the closest point on the great circle defined by
+/// the path start and end - negative if that point lies behind the start.
+pub fn along_track_distance(point: &Coordinate, start: &Coordinate, end: &Coordinate) -> f64 {
+    let angular_distance =
+        crate::distance(start.lat, start.lon, point.lat, point.lon) / RADIUS_EARTH;
+    let cross_track = cross_track_distance(point, start, end) / RADIUS_EARTH;
+    let bearing_point = initial_bearing(start, point).to_radians();
+    let bearing_path = initial_bearing(start, end).to_radians();
+
+    let along_track = (angular_distance.cos() / cross_track.cos())
+        .clamp(-1.0, 1.0)
+        .acos();
+    along_track * (bearing_path - bearing_point).cos().signum() * RADIUS_EARTH
+}
+
+/// Calculates the half-way point along the great circle between two coordinates.
+pub fn midpoint(src: &Coordinate, trg: &Coordinate) -> Coordinate {
+    interpolate(src, trg, 0.5)
+}
+
+/// Calculates the point at the given fraction (0.0 = source, 1.0 = target) along the great circle
+/// between two coordinates.
+pub fn interpolate(src: &Coordinate, trg: &Coordinate, fraction: f64) -> Coordinate {
+    let src_lat = src.lat.to_radians();
+    let src_long = src.lon.to_radians();
+    let trg_lat = trg.lat.to_radians();
+    let trg_long = trg.lon.to_radians();
+    let angular_distance = crate::distance(src.lat, src.lon, trg.lat, trg.lon) / RADIUS_EARTH;
+    if angular_distance == 0.0 {
+        return *src;
+    }
+
+    let a = ((1.0 - fraction) * angular_distance).sin() / angular_distance.sin();
+    let b = (fraction * angular_distance).sin() / angular_distance.sin();
+    let x = a * src_lat.cos() * src_long.cos() + b * trg_lat.cos() * trg_long.cos();
+    let y = a * src_lat.cos() * src_long.sin() + b * trg_lat.cos() * trg_long.sin();
+    let z = a * src_lat.sin() + b * trg_lat.sin();
+    Coordinate {
+        lat: z.atan2((x.powi(2) + y.powi(2)).sqrt()).to_degrees(),
+        lon: normalize_longitude(y.atan2(x).to_degrees()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flinders Peak & Buninyong - the reference example for Vincenty's formulae.
+    const FLINDERS_PEAK: Coordinate = Coordinate {
+        lat: -37.95103341666667,
+        lon: 144.42486788888888,
+    };
+    const BUNINYONG: Coordinate = Coordinate {
+        lat: -37.65282113888889,
+        lon: 143.92649552777777,
+    };
+
+    fn assert_close(res: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (res - expected).abs() < tolerance,
+            "Expected {} to be within {} of {}.",
+            res,
+            tolerance,
+            expected
+        );
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_distance_for_success() {
+        distance(&FLINDERS_PEAK, &BUNINYONG, Model::Spherical);
+        distance(
+            &FLINDERS_PEAK,
+            &BUNINYONG,
+            Model::Ellipsoidal(Accuracy::Low),
+        );
+    }
+
+    #[test]
+    fn test_bearings_for_success() {
+        initial_bearing(&FLINDERS_PEAK, &BUNINYONG);
+        final_bearing(&FLINDERS_PEAK, &BUNINYONG);
+    }
+
+    #[test]
+    fn test_destination_for_success() {
+        destination(&FLINDERS_PEAK, 306.0, 1000.0);
+    }
+
+    #[test]
+    fn test_track_distances_for_success() {
+        let point = Coordinate { lat: 1.0, lon: 5.0 };
+        cross_track_distance(&point, &FLINDERS_PEAK, &BUNINYONG);
+        along_track_distance(&point, &FLINDERS_PEAK, &BUNINYONG);
+    }
+
+    #[test]
+    fn test_interpolate_for_success() {
+        midpoint(&FLINDERS_PEAK, &BUNINYONG);
+        interpolate(&FLINDERS_PEAK, &BUNINYONG, 0.25);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_distance_for_failure() {
+        // nearly antipodal points do not converge.
+        let res = distance(
+            &Coordinate { lat: 0.0, lon: 0.0 },
+            &Coordinate {
+                lat: 0.5,
+                lon: 179.7,
+            },
+            Model::Ellipsoidal(Accuracy::High),
+        );
+        assert!(res.is_none());
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_distance_for_sanity() {
+        let res = distance(
+            &FLINDERS_PEAK,
+            &BUNINYONG,
+            Model::Ellipsoidal(Accuracy::High),
+        )
+        .unwrap();
+        assert_close(res, 54972.271, 0.001);
+        let res = distance(
+            &FLINDERS_PEAK,
+            &BUNINYONG,
+            Model::Ellipsoidal(Accuracy::Low),
+        )
+        .unwrap();
+        assert_close(res, 54972.271, 1.0);
+        let res = distance(&FLINDERS_PEAK, &BUNINYONG, Model::Spherical).unwrap();
+        assert_close(res, 54972.271, 0.005 * 54972.271);
+
+        // coincident points & along the equator.
+        let res = distance(&BUNINYONG, &BUNINYONG, Model::Ellipsoidal(Accuracy::High));
+        assert_eq!(res, Some(0.0));
+        let res = distance(
+            &Coordinate { lat: 0.0, lon: 0.0 },
+            &Coordinate { lat: 0.0, lon: 1.0 },
+            Model::Ellipsoidal(Accuracy::High),
+        )
+        .unwrap();
+        assert_close(res, 111319.491, 0.001);
+    }
+
+    #[test]
+    fn test_bearings_for_sanity() {
+        let origin = Coordinate { lat: 0.0, lon: 0.0 };
+        assert_close(
+            initial_bearing(&origin, &Coordinate { lat: 1.0, lon: 0.0 }),
+            0.0,
+            1e-9,
+        );
+        assert_close(
+            initial_bearing(&origin, &Coordinate { lat: 0.0, lon: 1.0 }),
+            90.0,
+            1e-9,
+        );
+        assert_close(
+            initial_bearing(
+                &origin,
+                &Coordinate {
+                    lat: -1.0,
+                    lon: 0.0,
+                },
+            ),
+            180.0,
+            1e-9,
+        );
+        assert_close(
+            initial_bearing(
+                &origin,
+                &Coordinate {
+                    lat: 0.0,
+                    lon: -1.0,
+                },
+            ),
+            270.0,
+            1e-9,
+        );
+
+        // spherical approximation of the reference example (306°52' & 307°10').
+        assert_close(initial_bearing(&FLINDERS_PEAK, &BUNINYONG), 306.87, 0.2);
+        assert_close(final_bearing(&FLINDERS_PEAK, &BUNINYONG), 307.17, 0.2);
+    }
+
+    #[test]
+    fn test_destination_for_sanity() {
+        let one_degree = RADIUS_EARTH * 1.0_f64.to_radians();
+        let res = destination(&Coordinate { lat: 0.0, lon: 0.0 }, 90.0, one_degree);
+        assert_close(res.lat, 0.0, 1e-9);
+        assert_close(res.lon, 1.0, 1e-9);
+
+        // crossing the antimeridian.
+        let res = destination(
+            &Coordinate {
+                lat: 0.0,
+                lon: 179.5,
+            },
+            90.0,
+            one_degree,
+        );
+        assert_close(res.lon, -179.5, 1e-9);
+
+        // round trip.
+        let bearing = initial_bearing(&FLINDERS_PEAK, &BUNINYONG);
+        let distance = crate::distance(
+            FLINDERS_PEAK.lat,
+            FLINDERS_PEAK.lon,
+            BUNINYONG.lat,
+            BUNINYONG.lon,
+        );
+        let res = destination(&FLINDERS_PEAK, bearing, distance);
+        assert_close(res.lat, BUNINYONG.lat, 1e-9);
+        assert_close(res.lon, BUNINYONG.lon, 1e-9);
+    }
+
+    #[test]
+    fn test_track_distances_for_sanity() {
+        let one_degree = RADIUS_EARTH * 1.0_f64.to_radians();
+        let start = Coordinate { lat: 0.0, lon: 0.0 };
+        let end = Coordinate {
+            lat: 0.0,
+            lon: 10.0,
+        };
+
+        // left of the path.
+        let res = cross_track_distance(&Coordinate { lat: 1.0, lon: 5.0 }, &start, &end);
+        assert_close(res, -one_degree, 1e-6);
+        let res = along_track_distance(&Coordinate { lat: 1.0, lon: 5.0 }, &start, &end);
+        assert_close(res, 5.0 * one_degree, 1e-6);
+
+        // right of the path & behind the start.
+        let res = cross_track_distance(
+            &Coordinate {
+                lat: -1.0,
+                lon: -2.0,
+            },
+            &start,
+            &end,
+        );
+        assert_close(res, one_degree, 1e-6);
+        let res = along_track_distance(
+            &Coordinate {
+                lat: -1.0,
+                lon: -2.0,
+            },
+            &start,
+            &end,
+        );
+        assert_close(res, -2.0 * one_degree, 1e-6);
+    }
+
+    #[test]
+    fn test_interpolate_for_sanity() {
+        let res = midpoint(
+            &Coordinate { lat: 0.0, lon: 0.0 },
+            &Coordinate {
+                lat: 0.0,
+                lon: 10.0,
+            },
+        );
+        assert_close(res.lat, 0.0, 1e-9);
+        assert_close(res.lon, 5.0, 1e-9);
+
+        let res = interpolate(&FLINDERS_PEAK, &BUNINYONG, 0.0);
+        assert_close(res.lat, FLINDERS_PEAK.lat, 1e-9);
+        assert_close(res.lon, FLINDERS_PEAK.lon, 1e-9);
+        let res = interpolate(&FLINDERS_PEAK, &BUNINYONG, 1.0);
+        assert_close(res.lat, BUNINYONG.lat, 1e-9);
+        assert_close(res.lon, BUNINYONG.lon, 1e-9);
+        let res = interpolate(&BUNINYONG, &BUNINYONG, 0.3);
+        assert_close(res.lat, BUNINYONG.lat, 1e-9);
+
+        // midpoint is equally far from both ends.
+        let res = midpoint(&FLINDERS_PEAK, &BUNINYONG);
+        let src = crate::distance(FLINDERS_PEAK.lat, FLINDERS_PEAK.lon, res.lat, res.lon);
+        let trg = crate::distance(BUNINYONG.lat, BUNINYONG.lon, res.lat, res.lon);
+        assert_close(src, trg, 1e-6);
+    }
+}
@@ -0,0 +1,445 @@
+//! Conversion of vessels & ports from/to GeoJSON - see: <https://tools.ietf.org/html/rfc7946>.
+//!
+//! A vessel becomes a feature with a geometry collection holding its track (a LineString) and its
+//! latest position (a Point). The per-vertex attributes are stored as arrays in the
+//! `coordinateProperties` member of the properties, following the convention used by e.g. togeojson.
+
+use std::{error, fmt};
+
+use crate::mmsi::Mmsi;
+use crate::{Coordinate, CoordinateError, Port, Vessel};
+
+/// Name of the property holding the per-vertex attributes.
+const COORDINATE_PROPERTIES: &str = "coordinateProperties";
+
+/// Errors when converting GeoJSON into vessels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoJsonError {
+    /// The feature has no valid MMSI property.
+    MissingMmsi,
+    /// The feature has no geometry or one which does not describe a track.
+    InvalidGeometry(String),
+    /// A position is not a valid coordinate.
+    InvalidCoordinate(CoordinateError),
+    /// A property has the wrong type or length.
+    InvalidProperty(String),
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoJsonError::MissingMmsi => write!(f, "feature has no valid mmsi property"),
+            GeoJsonError::InvalidGeometry(msg) => write!(f, "invalid geometry: {}", msg),
+            GeoJsonError::InvalidCoordinate(err) => write!(f, "invalid position: {}", err),
+            GeoJsonError::InvalidProperty(name) => write!(f, "invalid property: {}", name),
+        }
+    }
+}
+
+impl error::Error for GeoJsonError {}
+
+/// Converts a vessel into a GeoJSON feature.
+pub fn vessel_to_feature(vessel: &Vessel) -> geojson::Feature {
+    let positions: Vec<geojson::Position> = vessel
+        .coordinates
+        .iter()
+        .map(|coord| coord.to_lon_lat().to_vec())
+        .collect();
+    let mut geometries = vec![];
+    if positions.len() > 1 {
+        geometries.push(geojson::Geometry::new(geojson::Value::LineString(
+            positions.clone(),
+        )));
+    }
+    if let Some(latest) = positions.last() {
+        geometries.push(geojson::Geometry::new(geojson::Value::Point(
+            latest.clone(),
+        )));
+    }
+
+    let mut coordinate_properties = geojson::JsonObject::new();
+    coordinate_properties.insert("timestamps".to_string(), vessel.timestamps.clone().into());
+    coordinate_properties.insert("speeds".to_string(), vessel.speeds.clone().into());
+    coordinate_properties.insert("headings".to_string(), vessel.headings.clone().into());
+    coordinate_properties.insert(
+        "statuses".to_string(),
+        vessel
+            .statuses
+            .iter()
+            .map(|item| item.code())
+            .collect::<Vec<_>>()
+            .into(),
+    );
+    coordinate_properties.insert(
+        "destinations".to_string(),
+        vessel.destinations.clone().into(),
+    );
+    let mut properties = geojson::JsonObject::new();
+    properties.insert("mmsi".to_string(), vessel.mmsi.value().into());
+    properties.insert("name".to_string(), vessel.name.clone().into());
+    properties.insert("ship_type".to_string(), vessel.ship_type.code().into());
+    properties.insert(
+        COORDINATE_PROPERTIES.to_string(),
+        coordinate_properties.into(),
+    );
+
+    geojson::Feature {
+        bbox: None,
+        geometry: if geometries.is_empty() {
+            None
+        } else {
+            Some(geojson::Geometry::new(geojson::Value::GeometryCollection(
+                geometries,
+            )))
+        },
+        id: Some(geojson::feature::Id::Number(vessel.mmsi.value().into())),
+        properties: Some(properties),
+        foreign_members: None,
+    }
+}
+
+/// Converts a set of vessels into a GeoJSON feature collection.
+pub fn vessels_to_feature_collection(vessels: &[Vessel]) -> geojson::FeatureCollection {
+    geojson::FeatureCollection {
+        bbox: None,
+        features: vessels.iter().map(vessel_to_feature).collect(),
+        foreign_members: None,
+    }
+}
+
+/// Converts all vessels of a port into a GeoJSON feature collection - ordered by MMSI.
+pub fn port_to_feature_collection(port: &Port) -> geojson::FeatureCollection {
+    let mut vessels: Vec<&Vessel> = port.vessels.values().collect();
+    vessels.sort_by_key(|vessel| vessel.mmsi);
+    geojson::FeatureCollection {
+        bbox: None,
+        features: vessels.into_iter().map(vessel_to_feature).collect(),
+        foreign_members: None,
+    }
+}
+
+/// Converts a GeoJSON position into a coordinate.
+fn to_coordinate(position: &geojson::Position) -> Result<Coordinate, GeoJsonError> {
+    if position.len() < 2 {
+        return Err(GeoJsonError::InvalidGeometry(
+            "position needs at least two elements".to_string(),
+        ));
+    }
+    Coordinate::from_lon_lat([position[0], position[1]]).map_err(GeoJsonError::InvalidCoordinate)
+}
+
+/// Extracts the track from a geometry - a LineString is preferred over a single Point.
+fn to_track(geometry: &geojson::Geometry) -> Result<Vec<Coordinate>, GeoJsonError> {
+    match &geometry.value {
+        geojson::Value::LineString(positions) | geojson::Value::MultiPoint(positions) => {
+            positions.iter().map(to_coordinate).collect()
+        }
+        geojson::Value::Point(position) => Ok(vec![to_coordinate(position)?]),
+        geojson::Value::GeometryCollection(geometries) => {
+            let line = geometries
+                .iter()
+                .find(|item| matches!(item.value, geojson::Value::LineString(_)));
+            let point = geometries
+                .iter()
+                .find(|item| matches!(item.value, geojson::Value::Point(_)));
+            match line.or(point) {
+                Some(item) => to_track(item),
+                None => Err(GeoJsonError::InvalidGeometry(
+                    "collection holds no LineString or Point".to_string(),
+                )),
+            }
+        }
+        _ => Err(GeoJsonError::InvalidGeometry(format!(
+            "unsupported geometry type {}",
+            geometry.value.type_name()
+        ))),
+    }
+}
+
+/// Reads an optional string property.
+fn get_string(properties: &geojson::JsonObject, name: &str) -> Result<String, GeoJsonError> {
+    match properties.get(name) {
+        None | Some(geojson::JsonValue::Null) => Ok(String::new()),
+        Some(geojson::JsonValue::String(value)) => Ok(value.clone()),
+        Some(_) => Err(GeoJsonError::InvalidProperty(name.to_string())),
+    }
+}
+
+/// Reads an optional AIS code (or label) property.
+fn get_code<T: serde::de::DeserializeOwned + Default>(
+    properties: &geojson::JsonObject,
+    name: &str,
+) -> Result<T, GeoJsonError> {
+    match properties.get(name) {
+        None => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|_| GeoJsonError::InvalidProperty(name.to_string())),
+    }
+}
+
+/// Reads an optional per-vertex property - if not empty it must have one entry per coordinate.
+fn get_vertex_property<T: serde::de::DeserializeOwned>(
+    properties: Option<&geojson::JsonObject>,
+    name: &str,
+    len: usize,
+) -> Result<Vec<T>, GeoJsonError> {
+    let value = match properties.and_then(|item| item.get(name)) {
+        None | Some(geojson::JsonValue::Null) => return Ok(vec![]),
+        Some(value) => value.clone(),
+    };
+    let res: Vec<T> = serde_json::from_value(value)
+        .map_err(|_| GeoJsonError::InvalidProperty(name.to_string()))?;
+    if !res.is_empty() && res.len() != len {
+        return Err(GeoJsonError::InvalidProperty(name.to_string()));
+    }
+    Ok(res)
+}
+
+/// Converts a GeoJSON feature into a vessel.
+pub fn vessel_from_feature(feature: &geojson::Feature) -> Result<Vessel, GeoJsonError> {
+    let empty = geojson::JsonObject::new();
+    let properties = feature.properties.as_ref().unwrap_or(&empty);
+    let mmsi = properties
+        .get("mmsi")
+        .and_then(|value| serde_json::from_value::<Mmsi>(value.clone()).ok())
+        .ok_or(GeoJsonError::MissingMmsi)?;
+    let coordinates = match &feature.geometry {
+        Some(geometry) => to_track(geometry)?,
+        None => return Err(GeoJsonError::InvalidGeometry("no geometry".to_string())),
+    };
+
+    let vertex_properties = match properties.get(COORDINATE_PROPERTIES) {
+        None | Some(geojson::JsonValue::Null) => None,
+        Some(geojson::JsonValue::Object(object)) => Some(object),
+        Some(_) => {
+            return Err(GeoJsonError::InvalidProperty(
+                COORDINATE_PROPERTIES.to_string(),
+            ))
+        }
+    };
+    let len = coordinates.len();
+    Ok(Vessel {
+        mmsi,
+        name: get_string(properties, "name")?,
+        ship_type: get_code(properties, "ship_type")?,
+        timestamps: get_vertex_property(vertex_properties, "timestamps", len)?,
+        speeds: get_vertex_property(vertex_properties, "speeds", len)?,
+        headings: get_vertex_property(vertex_properties, "headings", len)?,
+        statuses: get_vertex_property(vertex_properties, "statuses", len)?,
+        destinations: get_vertex_property(vertex_properties, "destinations", len)?,
+        coordinates,
+    })
+}
+
+/// Converts a GeoJSON feature collection into a set of vessels.
+pub fn vessels_from_feature_collection(
+    collection: &geojson::FeatureCollection,
+) -> Result<Vec<Vessel>, GeoJsonError> {
+    collection
+        .features
+        .iter()
+        .map(vessel_from_feature)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections;
+
+    fn get_vessel() -> Vessel {
+        Vessel {
+            mmsi: Mmsi::new(123).unwrap(),
+            name: "Boaty McBoatface".to_string(),
+            ship_type: crate::ais::ShipType::Tug,
+            coordinates: vec![
+                Coordinate {
+                    lat: 51.9,
+                    lon: 4.1,
+                },
+                Coordinate {
+                    lat: 51.95,
+                    lon: 4.2,
+                },
+            ],
+            timestamps: vec![
+                "2022-01-01T00:00:00Z".to_string(),
+                "2022-01-01T00:01:00Z".to_string(),
+            ],
+            speeds: vec![10.0, 11.5],
+            headings: vec![90.0, 91.0],
+            statuses: vec![crate::ais::NavStatus::UnderWayUsingEngine; 2],
+            destinations: vec!["NLRTM".to_string(), "NLRTM".to_string()],
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_vessel_to_feature_for_success() {
+        vessel_to_feature(&get_vessel());
+        vessels_to_feature_collection(&[get_vessel()]);
+        port_to_feature_collection(&Port::default());
+    }
+
+    #[test]
+    fn test_vessel_from_feature_for_success() {
+        vessel_from_feature(&vessel_to_feature(&get_vessel())).expect("a valid vessel.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_vessel_from_feature_for_failure() {
+        // no mmsi.
+        let mut feature = vessel_to_feature(&get_vessel());
+        feature.properties.as_mut().unwrap().remove("mmsi");
+        assert_eq!(
+            vessel_from_feature(&feature).err(),
+            Some(GeoJsonError::MissingMmsi)
+        );
+
+        // no geometry.
+        let mut feature = vessel_to_feature(&get_vessel());
+        feature.geometry = None;
+        assert!(matches!(
+            vessel_from_feature(&feature),
+            Err(GeoJsonError::InvalidGeometry(_))
+        ));
+
+        // unsupported geometry.
+        feature.geometry = Some(geojson::Geometry::new(geojson::Value::Polygon(vec![])));
+        assert!(matches!(
+            vessel_from_feature(&feature),
+            Err(GeoJsonError::InvalidGeometry(_))
+        ));
+
+        // invalid position.
+        feature.geometry = Some(geojson::Geometry::new(geojson::Value::Point(vec![
+            4.1, 95.0,
+        ])));
+        assert!(matches!(
+            vessel_from_feature(&feature),
+            Err(GeoJsonError::InvalidCoordinate(_))
+        ));
+        feature.geometry = Some(geojson::Geometry::new(geojson::Value::Point(vec![4.1])));
+        assert!(matches!(
+            vessel_from_feature(&feature),
+            Err(GeoJsonError::InvalidGeometry(_))
+        ));
+
+        // per-vertex properties not matching the track.
+        let mut vessel = get_vessel();
+        vessel.speeds = vec![1.0];
+        let feature = vessel_to_feature(&vessel);
+        assert_eq!(
+            vessel_from_feature(&feature).err(),
+            Some(GeoJsonError::InvalidProperty("speeds".to_string()))
+        );
+
+        // ship type neither code nor label.
+        let mut feature = vessel_to_feature(&get_vessel());
+        feature.set_property("ship_type", vec![70]);
+        assert_eq!(
+            vessel_from_feature(&feature).err(),
+            Some(GeoJsonError::InvalidProperty("ship_type".to_string()))
+        );
+
+        // wrong type.
+        let mut feature = vessel_to_feature(&get_vessel());
+        feature
+            .properties
+            .as_mut()
+            .unwrap()
+            .insert("name".to_string(), 42.into());
+        assert_eq!(
+            vessel_from_feature(&feature).err(),
+            Some(GeoJsonError::InvalidProperty("name".to_string()))
+        );
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_vessel_to_feature_for_sanity() {
+        let res = serde_json::to_value(vessel_to_feature(&get_vessel())).unwrap();
+        assert_eq!(res["type"], "Feature");
+        assert_eq!(res["id"], 123);
+        assert_eq!(res["properties"]["name"], "Boaty McBoatface");
+        assert_eq!(
+            res["properties"]["coordinateProperties"]["speeds"],
+            serde_json::json!([10.0, 11.5])
+        );
+        let geometries = &res["geometry"]["geometries"];
+        assert_eq!(geometries[0]["type"], "LineString");
+        assert_eq!(
+            geometries[0]["coordinates"],
+            serde_json::json!([[4.1, 51.9], [4.2, 51.95]])
+        );
+        assert_eq!(geometries[1]["type"], "Point");
+        assert_eq!(
+            geometries[1]["coordinates"],
+            serde_json::json!([4.2, 51.95])
+        );
+
+        // single position --> only a point; none --> no geometry.
+        let mut vessel = get_vessel();
+        vessel.coordinates.truncate(1);
+        let res = serde_json::to_value(vessel_to_feature(&vessel)).unwrap();
+        assert_eq!(res["geometry"]["geometries"][0]["type"], "Point");
+        vessel.coordinates.clear();
+        assert!(vessel_to_feature(&vessel).geometry.is_none());
+
+        let mut other = get_vessel();
+        other.mmsi = Mmsi::new(42).unwrap();
+        let port = Port {
+            vessels: collections::HashMap::from([
+                (Mmsi::new(123).unwrap(), get_vessel()),
+                (other.mmsi, other),
+            ]),
+        };
+        let res = serde_json::to_value(port_to_feature_collection(&port)).unwrap();
+        assert_eq!(res["type"], "FeatureCollection");
+        assert_eq!(res["features"][0]["id"], 42);
+        assert_eq!(res["features"][1]["id"], 123);
+    }
+
+    #[test]
+    fn test_vessel_from_feature_for_sanity() {
+        // round trip.
+        let vessel = get_vessel();
+        let res = vessel_from_feature(&vessel_to_feature(&vessel)).unwrap();
+        assert_eq!(res.mmsi, vessel.mmsi);
+        assert_eq!(res.name, vessel.name);
+        assert_eq!(res.coordinates, vessel.coordinates);
+        assert_eq!(res.timestamps, vessel.timestamps);
+        assert_eq!(res.speeds, vessel.speeds);
+        assert_eq!(res.headings, vessel.headings);
+        assert_eq!(res.statuses, vessel.statuses);
+        assert_eq!(res.destinations, vessel.destinations);
+
+        // plain LineString from a 3rd party tool.
+        let feature: geojson::Feature = serde_json::from_str(
+            "{\"type\": \"Feature\", \"properties\": {\"mmsi\": 244123456, \"ship_type\": \
+            \"Cargo, hazardous A\"}, \"geometry\": {\"type\": \"LineString\", \"coordinates\": \
+            [[4.1, 51.9, 0.0], [4.2, 51.95, 0.0]]}}",
+        )
+        .unwrap();
+        let res = vessel_from_feature(&feature).unwrap();
+        assert_eq!(res.mmsi.value(), 244123456);
+        assert_eq!(res.name, "");
+        assert_eq!(res.ship_type.code(), 71);
+        assert_eq!(
+            res.coordinates[1],
+            Coordinate {
+                lat: 51.95,
+                lon: 4.2
+            }
+        );
+        assert!(res.speeds.is_empty());
+
+        let collection = vessels_to_feature_collection(&[get_vessel(), get_vessel()]);
+        let res = vessels_from_feature_collection(&collection).unwrap();
+        assert_eq!(res.len(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+//! Export of vessel tracks as GPX 1.1 - based on: <https://www.topografix.com/GPX/1/1/>.
+//!
+//! Each vessel becomes a track with a single segment. The speeds & headings are added using the
+//! Garmin TrackPointExtension, which desktop GIS & most GPS tools understand.
+
+use std::fmt::Write;
+
+use crate::xml::escape;
+use crate::Vessel;
+
+/// Media type of GPX documents.
+pub const GPX_MEDIA_TYPE: (&str, &str) = ("application", "gpx+xml");
+
+/// Converts knots - as reported by AIS - to meters per second.
+const KNOTS_TO_MPS: f64 = 1852.0 / 3600.0;
+
+/// Writes the track of a vessel.
+fn write_track(vessel: &Vessel, res: &mut String) {
+    let name = if vessel.name.is_empty() {
+        vessel.mmsi.to_string()
+    } else {
+        vessel.name.clone()
+    };
+    let _ = write!(
+        res,
+        "<trk><name>{}</name><desc>MMSI {}</desc><type>{}</type><trkseg>",
+        escape(&name),
+        vessel.mmsi,
+        escape(&vessel.ship_type.to_string())
+    );
+    for (i, coordinate) in vessel.coordinates.iter().enumerate() {
+        let _ = write!(
+            res,
+            "<trkpt lat=\"{}\" lon=\"{}\">",
+            coordinate.lat, coordinate.lon
+        );
+        if let Some(time) = vessel.timestamps.get(i).filter(|item| !item.is_empty()) {
+            let _ = write!(res, "<time>{}</time>", escape(time));
+        }
+        let speed = vessel.speeds.get(i);
+        let course = vessel.headings.get(i);
+        if speed.is_some() || course.is_some() {
+            res.push_str("<extensions><gpxtpx:TrackPointExtension>");
+            if let Some(speed) = speed {
+                let _ = write!(res, "<gpxtpx:speed>{}</gpxtpx:speed>", speed * KNOTS_TO_MPS);
+            }
+            if let Some(course) = course {
+                let _ = write!(res, "<gpxtpx:course>{}</gpxtpx:course>", course);
+            }
+            res.push_str("</gpxtpx:TrackPointExtension></extensions>");
+        }
+        res.push_str("</trkpt>");
+    }
+    res.push_str("</trkseg></trk>");
+}
+
+/// Exports the vessels as GPX document - one track per vessel.
+pub fn vessels_to_gpx(vessels: &[Vessel]) -> String {
+    let mut res = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<gpx version=\"1.1\" creator=\"rusty-port\" ",
+        "xmlns=\"http://www.topografix.com/GPX/1/1\" ",
+        "xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v2\">"
+    ));
+    for vessel in vessels {
+        write_track(vessel, &mut res);
+    }
+    res.push_str("</gpx>\n");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+
+    fn get_vessel() -> Vessel {
+        Vessel {
+            name: "Tom & Jerry".to_string(),
+            ship_type: crate::ais::ShipType::Cargo(crate::ais::Hazard::Unspecified),
+            coordinates: vec![
+                Coordinate {
+                    lat: 51.9,
+                    lon: 4.1,
+                },
+                Coordinate {
+                    lat: 51.95,
+                    lon: 4.15,
+                },
+            ],
+            timestamps: vec![
+                "2022-01-01T00:00:00Z".to_string(),
+                "2022-01-01T00:10:00Z".to_string(),
+            ],
+            speeds: vec![10.0, 0.0],
+            headings: vec![45.0, 90.0],
+            ..Vessel::new(crate::mmsi::Mmsi::new(123).unwrap())
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_vessels_to_gpx_for_success() {
+        vessels_to_gpx(&[get_vessel()]);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_vessels_to_gpx_for_failure() {
+        // no vessels, no tracks - still a valid document.
+        let res = vessels_to_gpx(&[]);
+        assert!(!res.contains("<trk>"));
+        assert!(res.ends_with("</gpx>\n"));
+
+        // per vertex values are optional.
+        let mut vessel = get_vessel();
+        vessel.name = "".to_string();
+        vessel.timestamps = vec![];
+        vessel.speeds = vec![];
+        vessel.headings = vec![];
+        let res = vessels_to_gpx(&[vessel]);
+        assert!(res.contains("<name>000000123</name>"));
+        assert!(res.contains("<trkpt lat=\"51.9\" lon=\"4.1\"></trkpt>"));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_vessels_to_gpx_for_sanity() {
+        let res = vessels_to_gpx(&[get_vessel(), get_vessel()]);
+        assert!(res.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\""));
+        assert_eq!(res.matches("<trk>").count(), 2);
+        assert!(res.contains(
+            "<trk><name>Tom &amp; Jerry</name><desc>MMSI 000000123</desc><type>Cargo</type><trkseg>"
+        ));
+        assert!(res.contains(concat!(
+            "<trkpt lat=\"51.9\" lon=\"4.1\"><time>2022-01-01T00:00:00Z</time><extensions>",
+            "<gpxtpx:TrackPointExtension><gpxtpx:speed>5.144444444444445</gpxtpx:speed>",
+            "<gpxtpx:course>45</gpxtpx:course></gpxtpx:TrackPointExtension></extensions></trkpt>"
+        )));
+    }
+}
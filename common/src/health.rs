@@ -0,0 +1,141 @@
+use std::sync::{self, atomic};
+
+use rocket::{fairing, http, State};
+
+/// Liveness & readiness of a rocket service - exposed on `/healthz` and `/readyz` once attached.
+///
+/// The service is marked as not ready as soon as rocket starts shutting down so no new traffic
+/// is routed to it while in-flight requests are drained.
+#[derive(Clone, Default)]
+pub struct Health {
+    ready: sync::Arc<atomic::AtomicBool>,
+}
+
+impl Health {
+    /// Creates a new health state - services still loading data should start as not ready.
+    pub fn new(ready: bool) -> Self {
+        Health {
+            ready: sync::Arc::new(atomic::AtomicBool::new(ready)),
+        }
+    }
+
+    /// Marks the service as (not) ready to serve traffic.
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, atomic::Ordering::SeqCst);
+    }
+
+    /// Returns true if the service is ready to serve traffic.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(atomic::Ordering::SeqCst)
+    }
+}
+
+/// Liveness probe - the service is alive as long as rocket answers.
+#[rocket::get("/healthz")]
+fn healthz() -> &'static str {
+    "OK"
+}
+
+/// Readiness probe.
+#[rocket::get("/readyz")]
+fn readyz(health: &State<Health>) -> (http::Status, &'static str) {
+    if health.is_ready() {
+        (http::Status::Ok, "OK")
+    } else {
+        (http::Status::ServiceUnavailable, "Not ready.")
+    }
+}
+
+#[rocket::async_trait]
+impl fairing::Fairing for Health {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Health checks",
+            kind: fairing::Kind::Ignite | fairing::Kind::Shutdown,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: rocket::Rocket<rocket::Build>) -> fairing::Result {
+        Ok(rocket
+            .mount("/", rocket::routes![healthz, readyz])
+            .manage(self.clone()))
+    }
+
+    async fn on_shutdown(&self, _: &rocket::Rocket<rocket::Orbit>) {
+        self.set_ready(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking;
+
+    fn get_client(health: Health) -> blocking::Client {
+        blocking::Client::tracked(rocket::build().attach(health)).expect("a valid test client.")
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_health_for_success() {
+        let health = Health::new(false);
+        health.set_ready(true);
+        health.is_ready();
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_readyz_for_failure() {
+        let client = get_client(Health::new(false));
+        let response = client.get("/readyz").dispatch();
+        assert_eq!(response.status(), http::Status::ServiceUnavailable);
+        assert_eq!(response.into_string(), Some("Not ready.".into()));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_health_for_sanity() {
+        let health = Health::default();
+        assert!(!health.is_ready());
+        let other = health.clone();
+        other.set_ready(true);
+        assert!(health.is_ready(), "State should be shared between clones.");
+    }
+
+    #[test]
+    fn test_fairing_for_sanity() {
+        let health = Health::new(false);
+        let client = get_client(health.clone());
+
+        let response = client.get("/healthz").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(response.into_string(), Some("OK".into()));
+
+        let status = client.get("/readyz").dispatch().status();
+        assert_eq!(status, http::Status::ServiceUnavailable);
+
+        health.set_ready(true);
+        let response = client.get("/readyz").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(response.into_string(), Some("OK".into()));
+    }
+
+    #[rocket::async_test]
+    async fn test_on_shutdown_for_sanity() {
+        let health = Health::new(true);
+        let figment = rocket::Config::figment().merge(("port", 0));
+        let rocket = rocket::custom(figment)
+            .attach(health.clone())
+            .ignite()
+            .await
+            .expect("a valid rocket.");
+
+        // shutting down --> not ready anymore.
+        rocket.shutdown().notify();
+        rocket.launch().await.expect("a graceful shutdown.");
+        assert!(!health.is_ready());
+    }
+}
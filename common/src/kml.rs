@@ -0,0 +1,180 @@
+//! Export of vessel tracks as KML 2.2 - based on: <https://developers.google.com/kml/documentation/kmlreference>.
+//!
+//! Each vessel becomes a placemark holding its track as styled line string, with the time span of
+//! the track so Google Earth's timeline slider can be used to replay the port traffic.
+
+use std::fmt::Write;
+
+use crate::xml::escape;
+use crate::Vessel;
+
+/// Media type of KML documents.
+pub const KML_MEDIA_TYPE: (&str, &str) = ("application", "vnd.google-earth.kml+xml");
+
+/// Colors of the tracks - in the KML order alpha, blue, green, red.
+const TRACK_COLORS: [&str; 6] = [
+    "ff0000ff", "ffff0000", "ff00aa00", "ff00a5ff", "ffff00ff", "ffffff00",
+];
+
+/// Width of the tracks in pixels.
+const TRACK_WIDTH: u32 = 3;
+
+/// Writes the shared styles of the tracks.
+fn write_styles(res: &mut String) {
+    for (i, color) in TRACK_COLORS.iter().enumerate() {
+        let _ = write!(
+            res,
+            "<Style id=\"track-{}\"><LineStyle><color>{}</color><width>{}</width></LineStyle>\
+<IconStyle><color>{}</color></IconStyle></Style>",
+            i, color, TRACK_WIDTH, color
+        );
+    }
+}
+
+/// Writes the placemark of a vessel.
+fn write_placemark(vessel: &Vessel, style: usize, res: &mut String) {
+    let name = if vessel.name.is_empty() {
+        vessel.mmsi.to_string()
+    } else {
+        vessel.name.clone()
+    };
+    let _ = write!(
+        res,
+        "<Placemark id=\"{}\"><name>{}</name><description>MMSI: {}, type: {}</description>",
+        vessel.mmsi,
+        escape(&name),
+        vessel.mmsi,
+        escape(&vessel.ship_type.to_string())
+    );
+    let mut timestamps = vessel.timestamps.iter().filter(|item| !item.is_empty());
+    if let Some(begin) = timestamps.next() {
+        let end = timestamps.next_back().unwrap_or(begin);
+        let _ = write!(
+            res,
+            "<TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>",
+            escape(begin),
+            escape(end)
+        );
+    }
+    let _ = write!(res, "<styleUrl>#track-{}</styleUrl>", style);
+    let coordinates = vessel
+        .coordinates
+        .iter()
+        .map(|item| format!("{},{}", item.lon, item.lat))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match vessel.coordinates.len() {
+        0 => {}
+        1 => {
+            let _ = write!(
+                res,
+                "<Point><coordinates>{}</coordinates></Point>",
+                coordinates
+            );
+        }
+        _ => {
+            let _ = write!(
+                res,
+                "<LineString><tessellate>1</tessellate><coordinates>{}</coordinates></LineString>",
+                coordinates
+            );
+        }
+    }
+    res.push_str("</Placemark>");
+}
+
+/// Exports the vessels as KML document - one placemark per vessel.
+pub fn vessels_to_kml(vessels: &[Vessel]) -> String {
+    let mut res = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<kml xmlns=\"http://www.opengis.net/kml/2.2\"><Document><name>Vessel tracks</name>"
+    ));
+    write_styles(&mut res);
+    for (i, vessel) in vessels.iter().enumerate() {
+        write_placemark(vessel, i % TRACK_COLORS.len(), &mut res);
+    }
+    res.push_str("</Document></kml>\n");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+
+    fn get_vessel() -> Vessel {
+        Vessel {
+            name: "<Boaty>".to_string(),
+            ship_type: crate::ais::ShipType::Cargo(crate::ais::Hazard::Unspecified),
+            coordinates: vec![
+                Coordinate {
+                    lat: 51.9,
+                    lon: 4.1,
+                },
+                Coordinate {
+                    lat: 51.95,
+                    lon: 4.15,
+                },
+                Coordinate {
+                    lat: 52.0,
+                    lon: 4.2,
+                },
+            ],
+            timestamps: vec![
+                "2022-01-01T00:00:00Z".to_string(),
+                "2022-01-01T00:10:00Z".to_string(),
+                "2022-01-01T00:20:00Z".to_string(),
+            ],
+            ..Vessel::new(crate::mmsi::Mmsi::new(123).unwrap())
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_vessels_to_kml_for_success() {
+        vessels_to_kml(&[get_vessel()]);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_vessels_to_kml_for_failure() {
+        let res = vessels_to_kml(&[]);
+        assert!(!res.contains("<Placemark"));
+        assert!(res.ends_with("</Document></kml>\n"));
+
+        // no timestamps & a single position.
+        let mut vessel = get_vessel();
+        vessel.coordinates.truncate(1);
+        vessel.timestamps = vec![];
+        let res = vessels_to_kml(&[vessel]);
+        assert!(!res.contains("<TimeSpan>"));
+        assert!(res.contains("<Point><coordinates>4.1,51.9</coordinates></Point>"));
+
+        // no positions at all.
+        let mut vessel = get_vessel();
+        vessel.coordinates = vec![];
+        let res = vessels_to_kml(&[vessel]);
+        assert!(!res.contains("<coordinates>"));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_vessels_to_kml_for_sanity() {
+        let vessels = vec![get_vessel(); TRACK_COLORS.len() + 1];
+        let res = vessels_to_kml(&vessels);
+        assert_eq!(res.matches("<Style id=").count(), TRACK_COLORS.len());
+        assert_eq!(res.matches("<Placemark").count(), vessels.len());
+        // colors are reused.
+        assert_eq!(res.matches("<styleUrl>#track-0</styleUrl>").count(), 2);
+        assert!(res.contains(concat!(
+            "<Placemark id=\"000000123\"><name>&lt;Boaty&gt;</name>",
+            "<description>MMSI: 000000123, type: Cargo</description>",
+            "<TimeSpan><begin>2022-01-01T00:00:00Z</begin><end>2022-01-01T00:20:00Z</end></TimeSpan>",
+            "<styleUrl>#track-0</styleUrl><LineString><tessellate>1</tessellate>",
+            "<coordinates>4.1,51.9 4.15,51.95 4.2,52</coordinates></LineString></Placemark>"
+        )));
+    }
+}
@@ -1,46 +1,196 @@
-use std::collections;
+use std::{collections, error, fmt};
+
+pub mod ais;
+#[cfg(feature = "compression")]
+pub mod compression;
+#[cfg(feature = "rocket")]
+pub mod config;
+#[cfg(feature = "rocket")]
+pub mod cors;
+pub mod geodesy;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+pub mod gpx;
+#[cfg(feature = "rocket")]
+pub mod health;
+pub mod kml;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mmsi;
+pub mod polyline;
+#[cfg(feature = "trace")]
+pub mod trace;
+mod track;
+#[cfg(feature = "msgpack")]
+pub mod wire;
+pub mod xml;
 
 /// Radius of the earth - let's hope this stays constant :-)
 const RADIUS_EARTH: f64 = 6378137.0;
 
+/// HTTP header used to propagate the identifier of a request between the services.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
 /// Represents a port.
 #[derive(serde::Serialize, serde::Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Port {
-    pub vessels: collections::HashMap<i32, Vessel>,
+    #[cfg_attr(feature = "openapi", schema(value_type = HashMap<u32, Vessel>))]
+    pub vessels: collections::HashMap<mmsi::Mmsi, Vessel>,
+}
+
+/// a GPS coordinate - latitude & longitude in degrees.
+///
+/// Deserializes from the named form `{"lat": 51.9, "lon": 4.1}` as well as the legacy tuple form
+/// `[4.1, 51.9]` which uses the GeoJSON order (longitude first).
+#[derive(serde::Serialize, serde::Deserialize, Default, Copy, Clone, Debug, PartialEq)]
+#[serde(try_from = "CoordinateRepr")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Coordinate {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Errors for invalid coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoordinateError {
+    /// Latitude not within [-90, 90].
+    InvalidLatitude(f64),
+    /// Longitude is not a finite number.
+    InvalidLongitude(f64),
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateError::InvalidLatitude(lat) => write!(f, "invalid latitude: {}", lat),
+            CoordinateError::InvalidLongitude(lon) => write!(f, "invalid longitude: {}", lon),
+        }
+    }
+}
+
+impl error::Error for CoordinateError {}
+
+/// Accepted serialized forms of a coordinate.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum CoordinateRepr {
+    Named {
+        #[serde(alias = "latitude")]
+        lat: f64,
+        #[serde(alias = "lng", alias = "longitude")]
+        lon: f64,
+    },
+    Legacy(f64, f64),
+}
+
+impl TryFrom<CoordinateRepr> for Coordinate {
+    type Error = CoordinateError;
+
+    fn try_from(repr: CoordinateRepr) -> Result<Self, Self::Error> {
+        match repr {
+            CoordinateRepr::Named { lat, lon } => Coordinate::new(lat, lon),
+            CoordinateRepr::Legacy(lon, lat) => Coordinate::new(lat, lon),
+        }
+    }
+}
+
+impl Coordinate {
+    /// Creates a new coordinate - the longitude is normalized to [-180, 180).
+    pub fn new(lat: f64, lon: f64) -> Result<Self, CoordinateError> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(CoordinateError::InvalidLatitude(lat));
+        }
+        if !lon.is_finite() {
+            return Err(CoordinateError::InvalidLongitude(lon));
+        }
+        Ok(Coordinate {
+            lat,
+            lon: normalize_longitude(lon),
+        })
+    }
+
+    /// Creates a new coordinate from a position in GeoJSON order (longitude, latitude).
+    pub fn from_lon_lat(position: [f64; 2]) -> Result<Self, CoordinateError> {
+        Coordinate::new(position[1], position[0])
+    }
+
+    /// Returns the position in GeoJSON order (longitude, latitude).
+    pub fn to_lon_lat(&self) -> [f64; 2] {
+        [self.lon, self.lat]
+    }
+
+    /// Returns true if the latitude & longitude are within their valid ranges.
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lon)
+    }
 }
 
-/// a GPS coordinate.
-#[derive(serde::Serialize, serde::Deserialize, Default, Copy, Clone)]
-pub struct Coordinate(pub f64, pub f64);
+/// Normalizes a longitude to the range [-180, 180).
+pub fn normalize_longitude(lon: f64) -> f64 {
+    if (-180.0..180.0).contains(&lon) {
+        lon
+    } else {
+        (lon + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
 
 /// Represents a vessel within a port.
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Vessel {
-    pub mmsi: i32,
+    #[cfg_attr(feature = "openapi", schema(value_type = u32))]
+    pub mmsi: mmsi::Mmsi,
     pub name: String,
-    pub ship_type: String,
+    /// AIS ship type code.
+    #[cfg_attr(feature = "openapi", schema(value_type = u8))]
+    pub ship_type: ais::ShipType,
+    #[serde(with = "track")]
     pub coordinates: Vec<Coordinate>,
     pub timestamps: Vec<String>,
     pub speeds: Vec<f64>,
     pub headings: Vec<f64>,
-    pub statuses: Vec<String>,
+    /// AIS navigational status codes.
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<u8>))]
+    pub statuses: Vec<ais::NavStatus>,
     pub destinations: Vec<String>,
 }
 
+impl Vessel {
+    /// Creates a vessel without a name, type or any position reports - set the fields as needed.
+    pub fn new(mmsi: mmsi::Mmsi) -> Self {
+        Vessel {
+            mmsi,
+            name: String::new(),
+            ship_type: ais::ShipType::NotAvailable,
+            coordinates: vec![],
+            timestamps: vec![],
+            speeds: vec![],
+            headings: vec![],
+            statuses: vec![],
+            destinations: vec![],
+        }
+    }
+}
+
 /// List of Vessels.
 #[derive(serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct VesselList {
     pub vessels: Vec<Vessel>,
 }
 
 /// List of vessel identifiers - using MMSIs.
 #[derive(serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct MMSIList {
-    pub vessels: Vec<i32>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<u32>))]
+    pub vessels: Vec<mmsi::Mmsi>,
 }
 
 /// Defines the input to the path simplification function.
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SimplifyIn {
     pub vessel: Vessel,
     pub radius: f64,
@@ -48,10 +198,36 @@ pub struct SimplifyIn {
 
 /// Defines the output to the path simplification function.
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SimplifyOut {
+    #[serde(with = "track")]
     pub path: Vec<Coordinate>,
 }
 
+/// A path to simplify in a batch - only the coordinates of the track.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SimplifyPath {
+    #[serde(with = "track")]
+    pub coordinates: Vec<Coordinate>,
+    pub radius: f64,
+}
+
+/// Defines the input to the batched path simplification function.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SimplifyBatchIn {
+    pub paths: Vec<SimplifyPath>,
+}
+
+/// Defines the output to the batched path simplification function - in the order of the input,
+/// null for the paths which could not be simplified.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SimplifyBatchOut {
+    pub paths: Vec<Option<SimplifyOut>>,
+}
+
 /// Calculates distance between two coordinates - based on: <https://en.wikipedia.org/wiki/Haversine_formula>.
 pub fn distance(src_lat: f64, src_long: f64, trg_lat: f64, trg_long: f64) -> f64 {
     let src_lat_rad = src_lat.to_radians();
@@ -78,12 +254,97 @@ mod tests {
         distance(50.0, 2.0, 40.0, 2.0);
     }
 
+    #[test]
+    fn test_vessel_for_success() {
+        let vessel = Vessel::new(mmsi::Mmsi::new(244123456).unwrap());
+        assert_eq!(vessel.mmsi.value(), 244123456);
+        assert_eq!(vessel.ship_type, ais::ShipType::NotAvailable);
+        assert!(vessel.name.is_empty() && vessel.coordinates.is_empty());
+    }
+
+    #[test]
+    fn test_coordinate_for_success() {
+        Coordinate::new(51.9, 4.1).expect("a valid coordinate.");
+        Coordinate::from_lon_lat([4.1, 51.9]).expect("a valid coordinate.");
+    }
+
     // Tests for failure.
 
-    // n/A
+    #[test]
+    fn test_coordinate_for_failure() {
+        assert_eq!(
+            Coordinate::new(91.0, 4.1),
+            Err(CoordinateError::InvalidLatitude(91.0))
+        );
+        assert!(Coordinate::new(f64::NAN, 4.1).is_err());
+        assert_eq!(
+            Coordinate::new(51.9, f64::INFINITY),
+            Err(CoordinateError::InvalidLongitude(f64::INFINITY))
+        );
+        assert!(!Coordinate {
+            lat: 0.0,
+            lon: 190.0
+        }
+        .is_valid());
+
+        // the legacy form is (lon, lat) --> 91.9 is no valid latitude.
+        let res = serde_json::from_str::<Coordinate>("[4.1, 91.9]");
+        assert!(res.is_err());
+        let res = serde_json::from_str::<Coordinate>("{\"lat\": -100.0, \"lon\": 4.1}");
+        assert!(res.is_err());
+        let res = serde_json::from_str::<Coordinate>("{\"foo\": 1.0}");
+        assert!(res.is_err());
+    }
 
     // Tests for sanity.
 
+    #[test]
+    fn test_coordinate_for_sanity() {
+        let res = Coordinate::new(51.9, 4.1).unwrap();
+        assert_eq!(res.lat, 51.9);
+        assert_eq!(res.lon, 4.1);
+        assert!(res.is_valid());
+        assert_eq!(res.to_lon_lat(), [4.1, 51.9]);
+        assert_eq!(Coordinate::from_lon_lat([4.1, 51.9]).unwrap(), res);
+
+        // longitudes are normalized.
+        assert_eq!(Coordinate::new(0.0, 190.0).unwrap().lon, -170.0);
+        assert_eq!(Coordinate::new(0.0, -190.0).unwrap().lon, 170.0);
+        assert_eq!(Coordinate::new(0.0, 180.0).unwrap().lon, -180.0);
+        assert_eq!(Coordinate::new(0.0, 540.0).unwrap().lon, -180.0);
+        assert_eq!(normalize_longitude(-180.0), -180.0);
+
+        // serde - both legacy & named form.
+        let res: Coordinate = serde_json::from_str("[4.1, 51.9]").unwrap();
+        assert_eq!(
+            res,
+            Coordinate {
+                lat: 51.9,
+                lon: 4.1
+            }
+        );
+        let res: Coordinate = serde_json::from_str("{\"lat\": 51.9, \"lon\": 4.1}").unwrap();
+        assert_eq!(
+            res,
+            Coordinate {
+                lat: 51.9,
+                lon: 4.1
+            }
+        );
+        let res: Coordinate = serde_json::from_str("{\"lat\": 51.9, \"lng\": 4.1}").unwrap();
+        assert_eq!(
+            res,
+            Coordinate {
+                lat: 51.9,
+                lon: 4.1
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&res).unwrap(),
+            "{\"lat\":51.9,\"lon\":4.1}"
+        );
+    }
+
     #[test]
     fn test_distance_for_sanity() {
         let res: f64 = distance(52.3676, 4.9041, 51.9244, 4.4777);
@@ -0,0 +1,265 @@
+use std::{io, pin, task, time};
+
+use rocket::tokio::io::{AsyncRead, ReadBuf};
+use rocket::{fairing, http, State};
+
+/// Buckets (in seconds) for the request latency histogram.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Buckets (in bytes) for the response size histogram.
+const SIZE_BUCKETS: &[f64] = &[
+    100.0,
+    1000.0,
+    10000.0,
+    100000.0,
+    1000000.0,
+    10000000.0,
+    100000000.0,
+];
+
+/// Label used for requests not matching any route - keeps the cardinality of the labels bounded.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Prometheus metrics of a rocket service - exposed on `/metrics` once attached.
+///
+/// Attach it after the compression fairing so the response sizes are of the bodies as sent.
+/// Services can register their own (domain specific) collectors using the registry.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: prometheus::Registry,
+    requests: prometheus::IntCounterVec,
+    latency: prometheus::HistogramVec,
+    response_size: prometheus::HistogramVec,
+}
+
+/// Time at which rocket started handling the request.
+struct RequestStart(time::Instant);
+
+/// Streamed response body counting the bytes sent - the size is recorded once it's completely
+/// sent.
+struct CountedBody<'r> {
+    body: rocket::response::Body<'r>,
+    size: usize,
+    histogram: Option<prometheus::Histogram>,
+}
+
+impl AsyncRead for CountedBody<'_> {
+    fn poll_read(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> task::Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let res = pin::Pin::new(&mut self.body).poll_read(cx, buf);
+        if let task::Poll::Ready(Ok(())) = res {
+            let read = buf.filled().len() - filled;
+            self.size += read;
+            if read == 0 {
+                if let Some(histogram) = self.histogram.take() {
+                    histogram.observe(self.size as f64);
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Metrics {
+    /// Creates a new set of HTTP metrics with the given namespace - e.g. the name of the service.
+    pub fn new(namespace: &str) -> Result<Self, prometheus::Error> {
+        let registry = prometheus::Registry::new();
+        let requests = prometheus::IntCounterVec::new(
+            prometheus::Opts::new("http_requests_total", "Number of handled HTTP requests.")
+                .namespace(namespace),
+            &["method", "route", "status"],
+        )?;
+        let latency = prometheus::HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time spent handling HTTP requests.",
+            )
+            .namespace(namespace)
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route"],
+        )?;
+        let response_size = prometheus::HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "http_response_size_bytes",
+                "Size of the HTTP response bodies as sent - i.e. compressed.",
+            )
+            .namespace(namespace)
+            .buckets(SIZE_BUCKETS.to_vec()),
+            &["method", "route"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(response_size.clone()))?;
+        Ok(Metrics {
+            registry,
+            requests,
+            latency,
+            response_size,
+        })
+    }
+
+    /// The registry holding all metrics of this service.
+    pub fn registry(&self) -> &prometheus::Registry {
+        &self.registry
+    }
+
+    /// Renders all registered metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let encoder = prometheus::TextEncoder::new();
+        encoder
+            .encode_to_string(&self.registry.gather())
+            .unwrap_or_default()
+    }
+}
+
+/// Exposes the metrics in the Prometheus text format.
+#[rocket::get("/metrics")]
+fn metrics(metrics: &State<Metrics>) -> (http::ContentType, String) {
+    let content_type = http::ContentType::new("text", "plain")
+        .with_params([("version", "0.0.4"), ("charset", "utf-8")]);
+    (content_type, metrics.render())
+}
+
+#[rocket::async_trait]
+impl fairing::Fairing for Metrics {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Prometheus metrics",
+            kind: fairing::Kind::Ignite | fairing::Kind::Request | fairing::Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: rocket::Rocket<rocket::Build>) -> fairing::Result {
+        Ok(rocket
+            .mount("/", rocket::routes![metrics])
+            .manage(self.clone()))
+    }
+
+    async fn on_request(&self, req: &mut rocket::Request<'_>, _: &mut rocket::Data<'_>) {
+        req.local_cache(|| RequestStart(time::Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r rocket::Request<'_>, res: &mut rocket::Response<'r>) {
+        let start = req.local_cache(|| RequestStart(time::Instant::now()));
+        let method = req.method().as_str();
+        let route = req
+            .route()
+            .map_or(UNMATCHED_ROUTE, |route| route.uri.as_str());
+        let status = res.status().code.to_string();
+
+        self.requests
+            .with_label_values(&[method, route, &status])
+            .inc();
+        self.latency
+            .with_label_values(&[method, route])
+            .observe(start.0.elapsed().as_secs_f64());
+        let histogram = self.response_size.with_label_values(&[method, route]);
+        match res.body().preset_size() {
+            Some(size) => histogram.observe(size as f64),
+            None => {
+                let body = res.body_mut().take();
+                res.set_streamed_body(CountedBody {
+                    body,
+                    size: 0,
+                    histogram: Some(histogram),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking;
+
+    #[rocket::get("/")]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[rocket::get("/stream")]
+    fn stream() -> rocket::response::stream::ByteStream![Vec<u8>] {
+        rocket::response::stream::ByteStream! {
+            yield b"hello".to_vec();
+            yield b" world".to_vec();
+        }
+    }
+
+    fn get_client(metrics: Metrics) -> blocking::Client {
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index, stream])
+            .attach(metrics);
+        blocking::Client::tracked(rocket).expect("a valid test client.")
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_new_for_success() {
+        Metrics::new("foo").expect("valid metrics.");
+    }
+
+    #[test]
+    fn test_render_for_success() {
+        let metrics = Metrics::new("foo").expect("valid metrics.");
+        metrics.render();
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_new_for_failure() {
+        let res = Metrics::new("not a namespace");
+        assert!(res.is_err(), "Namespace should be validated.");
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_fairing_for_sanity() {
+        let metrics = Metrics::new("foo").expect("valid metrics.");
+        let gauge = prometheus::IntGauge::new("foo_bar", "Some gauge.").unwrap();
+        metrics
+            .registry()
+            .register(Box::new(gauge.clone()))
+            .unwrap();
+        gauge.set(42);
+
+        let client = get_client(metrics);
+        client.get("/").dispatch();
+        client.get("/").dispatch();
+        client.get("/nothing").dispatch();
+        // streamed bodies are counted while sent.
+        let response = client.get("/stream").dispatch();
+        assert_eq!(response.into_string(), Some("hello world".into()));
+
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Content-Type"),
+            Some("text/plain; version=0.0.4; charset=utf-8")
+        );
+        let body = response.into_string().unwrap();
+        assert!(
+            body.contains("foo_http_requests_total{method=\"GET\",route=\"/\",status=\"200\"} 2")
+        );
+        assert!(body.contains(
+            "foo_http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1"
+        ));
+        assert!(
+            body.contains("foo_http_request_duration_seconds_count{method=\"GET\",route=\"/\"} 2")
+        );
+        assert!(body.contains("foo_http_response_size_bytes_sum{method=\"GET\",route=\"/\"} 10"));
+        assert!(
+            body.contains("foo_http_response_size_bytes_sum{method=\"GET\",route=\"/stream\"} 11")
+        );
+        assert!(body.contains("foo_bar 42"));
+    }
+}
@@ -0,0 +1,647 @@
+//! Maritime Mobile Service Identities - based on: <https://www.itu.int/rec/R-REC-M.585>.
+//!
+//! An MMSI is a 9 digit identifier; leading zeros are significant (e.g. `002442000` is a Dutch
+//! coast station), so they are displayed zero padded. The digits encode the kind of station and
+//! - for most kinds - the Maritime Identification Digits (MID) of the flag state.
+
+use std::{error, fmt};
+
+/// Largest valid MMSI - 9 digits.
+const MAX_MMSI: u32 = 999_999_999;
+
+/// Errors for invalid MMSIs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MmsiError {
+    /// Not a number of at most 9 digits.
+    Invalid(String),
+    /// Zero or more than 9 digits.
+    OutOfRange(i64),
+}
+
+impl fmt::Display for MmsiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmsiError::Invalid(value) => write!(f, "invalid mmsi: {}", value),
+            MmsiError::OutOfRange(value) => write!(f, "invalid mmsi: {}", value),
+        }
+    }
+}
+
+impl error::Error for MmsiError {}
+
+/// Kind of station identified by an MMSI.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum StationKind {
+    /// MIDXXXXXX - MID starting with 2 to 7.
+    Ship,
+    /// 0MIDXXXXX
+    Group,
+    /// 00MIDXXXX
+    CoastStation,
+    /// 111MIDXXX
+    SarAircraft,
+    /// 99MIDXXXX
+    AidToNavigation,
+    /// 98MIDXXXX - e.g. tenders & lifeboats of a parent ship.
+    AuxiliaryCraft,
+    /// 8MIDXXXXX - handheld VHF transceivers, e.g. of divers.
+    Handheld,
+    /// 970XXYYYY (AIS-SART), 972XXYYYY (man overboard) & 974XXYYYY (EPIRB-AIS).
+    SarTransmitter,
+    Unknown,
+}
+
+impl fmt::Display for StationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            StationKind::Ship => "Ship",
+            StationKind::Group => "Group of ships",
+            StationKind::CoastStation => "Coast station",
+            StationKind::SarAircraft => "SAR aircraft",
+            StationKind::AidToNavigation => "Aid to navigation",
+            StationKind::AuxiliaryCraft => "Craft associated with a parent ship",
+            StationKind::Handheld => "Handheld VHF",
+            StationKind::SarTransmitter => "SAR transmitter",
+            StationKind::Unknown => "Unknown",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Flag state of a station - as decoded from its MID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagState {
+    /// ISO 3166-1 alpha-2 code.
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+/// Maritime Identification Digits with the ISO code & name of their flag state - ordered by MID.
+const MIDS: &[(u16, &str, &str)] = &[
+    (201, "AL", "Albania"),
+    (202, "AD", "Andorra"),
+    (203, "AT", "Austria"),
+    (204, "PT", "Azores"),
+    (205, "BE", "Belgium"),
+    (206, "BY", "Belarus"),
+    (207, "BG", "Bulgaria"),
+    (208, "VA", "Vatican City"),
+    (209, "CY", "Cyprus"),
+    (210, "CY", "Cyprus"),
+    (211, "DE", "Germany"),
+    (212, "CY", "Cyprus"),
+    (213, "GE", "Georgia"),
+    (214, "MD", "Moldova"),
+    (215, "MT", "Malta"),
+    (216, "AM", "Armenia"),
+    (218, "DE", "Germany"),
+    (219, "DK", "Denmark"),
+    (220, "DK", "Denmark"),
+    (224, "ES", "Spain"),
+    (225, "ES", "Spain"),
+    (226, "FR", "France"),
+    (227, "FR", "France"),
+    (228, "FR", "France"),
+    (229, "MT", "Malta"),
+    (230, "FI", "Finland"),
+    (231, "FO", "Faroe Islands"),
+    (232, "GB", "United Kingdom"),
+    (233, "GB", "United Kingdom"),
+    (234, "GB", "United Kingdom"),
+    (235, "GB", "United Kingdom"),
+    (236, "GI", "Gibraltar"),
+    (237, "GR", "Greece"),
+    (238, "HR", "Croatia"),
+    (239, "GR", "Greece"),
+    (240, "GR", "Greece"),
+    (241, "GR", "Greece"),
+    (242, "MA", "Morocco"),
+    (243, "HU", "Hungary"),
+    (244, "NL", "Netherlands"),
+    (245, "NL", "Netherlands"),
+    (246, "NL", "Netherlands"),
+    (247, "IT", "Italy"),
+    (248, "MT", "Malta"),
+    (249, "MT", "Malta"),
+    (250, "IE", "Ireland"),
+    (251, "IS", "Iceland"),
+    (252, "LI", "Liechtenstein"),
+    (253, "LU", "Luxembourg"),
+    (254, "MC", "Monaco"),
+    (255, "PT", "Madeira"),
+    (256, "MT", "Malta"),
+    (257, "NO", "Norway"),
+    (258, "NO", "Norway"),
+    (259, "NO", "Norway"),
+    (261, "PL", "Poland"),
+    (262, "ME", "Montenegro"),
+    (263, "PT", "Portugal"),
+    (264, "RO", "Romania"),
+    (265, "SE", "Sweden"),
+    (266, "SE", "Sweden"),
+    (267, "SK", "Slovakia"),
+    (268, "SM", "San Marino"),
+    (269, "CH", "Switzerland"),
+    (270, "CZ", "Czech Republic"),
+    (271, "TR", "Turkey"),
+    (272, "UA", "Ukraine"),
+    (273, "RU", "Russia"),
+    (274, "MK", "North Macedonia"),
+    (275, "LV", "Latvia"),
+    (276, "EE", "Estonia"),
+    (277, "LT", "Lithuania"),
+    (278, "SI", "Slovenia"),
+    (279, "RS", "Serbia"),
+    (301, "AI", "Anguilla"),
+    (303, "US", "Alaska"),
+    (304, "AG", "Antigua and Barbuda"),
+    (305, "AG", "Antigua and Barbuda"),
+    (306, "CW", "Curaçao"),
+    (307, "AW", "Aruba"),
+    (308, "BS", "Bahamas"),
+    (309, "BS", "Bahamas"),
+    (310, "BM", "Bermuda"),
+    (311, "BS", "Bahamas"),
+    (312, "BZ", "Belize"),
+    (314, "BB", "Barbados"),
+    (316, "CA", "Canada"),
+    (319, "KY", "Cayman Islands"),
+    (321, "CR", "Costa Rica"),
+    (323, "CU", "Cuba"),
+    (325, "DM", "Dominica"),
+    (327, "DO", "Dominican Republic"),
+    (329, "GP", "Guadeloupe"),
+    (330, "GD", "Grenada"),
+    (331, "GL", "Greenland"),
+    (332, "GT", "Guatemala"),
+    (334, "HN", "Honduras"),
+    (336, "HT", "Haiti"),
+    (338, "US", "United States"),
+    (339, "JM", "Jamaica"),
+    (341, "KN", "Saint Kitts and Nevis"),
+    (343, "LC", "Saint Lucia"),
+    (345, "MX", "Mexico"),
+    (347, "MQ", "Martinique"),
+    (348, "MS", "Montserrat"),
+    (350, "NI", "Nicaragua"),
+    (351, "PA", "Panama"),
+    (352, "PA", "Panama"),
+    (353, "PA", "Panama"),
+    (354, "PA", "Panama"),
+    (355, "PA", "Panama"),
+    (356, "PA", "Panama"),
+    (357, "PA", "Panama"),
+    (358, "PR", "Puerto Rico"),
+    (359, "SV", "El Salvador"),
+    (361, "PM", "Saint Pierre and Miquelon"),
+    (362, "TT", "Trinidad and Tobago"),
+    (364, "TC", "Turks and Caicos Islands"),
+    (366, "US", "United States"),
+    (367, "US", "United States"),
+    (368, "US", "United States"),
+    (369, "US", "United States"),
+    (370, "PA", "Panama"),
+    (371, "PA", "Panama"),
+    (372, "PA", "Panama"),
+    (373, "PA", "Panama"),
+    (374, "PA", "Panama"),
+    (375, "VC", "Saint Vincent and the Grenadines"),
+    (376, "VC", "Saint Vincent and the Grenadines"),
+    (377, "VC", "Saint Vincent and the Grenadines"),
+    (378, "VG", "British Virgin Islands"),
+    (379, "VI", "United States Virgin Islands"),
+    (401, "AF", "Afghanistan"),
+    (403, "SA", "Saudi Arabia"),
+    (405, "BD", "Bangladesh"),
+    (408, "BH", "Bahrain"),
+    (410, "BT", "Bhutan"),
+    (412, "CN", "China"),
+    (413, "CN", "China"),
+    (414, "CN", "China"),
+    (416, "TW", "Taiwan"),
+    (417, "LK", "Sri Lanka"),
+    (419, "IN", "India"),
+    (422, "IR", "Iran"),
+    (423, "AZ", "Azerbaijan"),
+    (425, "IQ", "Iraq"),
+    (428, "IL", "Israel"),
+    (431, "JP", "Japan"),
+    (432, "JP", "Japan"),
+    (434, "TM", "Turkmenistan"),
+    (436, "KZ", "Kazakhstan"),
+    (437, "UZ", "Uzbekistan"),
+    (438, "JO", "Jordan"),
+    (440, "KR", "South Korea"),
+    (441, "KR", "South Korea"),
+    (443, "PS", "Palestine"),
+    (445, "KP", "North Korea"),
+    (447, "KW", "Kuwait"),
+    (450, "LB", "Lebanon"),
+    (451, "KG", "Kyrgyzstan"),
+    (453, "MO", "Macao"),
+    (455, "MV", "Maldives"),
+    (457, "MN", "Mongolia"),
+    (459, "NP", "Nepal"),
+    (461, "OM", "Oman"),
+    (463, "PK", "Pakistan"),
+    (466, "QA", "Qatar"),
+    (468, "SY", "Syria"),
+    (470, "AE", "United Arab Emirates"),
+    (471, "AE", "United Arab Emirates"),
+    (472, "TJ", "Tajikistan"),
+    (473, "YE", "Yemen"),
+    (475, "YE", "Yemen"),
+    (477, "HK", "Hong Kong"),
+    (478, "BA", "Bosnia and Herzegovina"),
+    (501, "TF", "Adélie Land"),
+    (503, "AU", "Australia"),
+    (506, "MM", "Myanmar"),
+    (508, "BN", "Brunei"),
+    (510, "FM", "Micronesia"),
+    (511, "PW", "Palau"),
+    (512, "NZ", "New Zealand"),
+    (514, "KH", "Cambodia"),
+    (515, "KH", "Cambodia"),
+    (516, "CX", "Christmas Island"),
+    (518, "CK", "Cook Islands"),
+    (520, "FJ", "Fiji"),
+    (523, "CC", "Cocos (Keeling) Islands"),
+    (525, "ID", "Indonesia"),
+    (529, "KI", "Kiribati"),
+    (531, "LA", "Laos"),
+    (533, "MY", "Malaysia"),
+    (536, "MP", "Northern Mariana Islands"),
+    (538, "MH", "Marshall Islands"),
+    (540, "NC", "New Caledonia"),
+    (542, "NU", "Niue"),
+    (544, "NR", "Nauru"),
+    (546, "PF", "French Polynesia"),
+    (548, "PH", "Philippines"),
+    (550, "TL", "Timor-Leste"),
+    (553, "PG", "Papua New Guinea"),
+    (555, "PN", "Pitcairn Islands"),
+    (557, "SB", "Solomon Islands"),
+    (559, "AS", "American Samoa"),
+    (561, "WS", "Samoa"),
+    (563, "SG", "Singapore"),
+    (564, "SG", "Singapore"),
+    (565, "SG", "Singapore"),
+    (566, "SG", "Singapore"),
+    (567, "TH", "Thailand"),
+    (570, "TO", "Tonga"),
+    (572, "TV", "Tuvalu"),
+    (574, "VN", "Vietnam"),
+    (576, "VU", "Vanuatu"),
+    (577, "VU", "Vanuatu"),
+    (578, "WF", "Wallis and Futuna"),
+    (601, "ZA", "South Africa"),
+    (603, "AO", "Angola"),
+    (605, "DZ", "Algeria"),
+    (607, "TF", "Saint Paul and Amsterdam Islands"),
+    (608, "SH", "Ascension Island"),
+    (609, "BI", "Burundi"),
+    (610, "BJ", "Benin"),
+    (611, "BW", "Botswana"),
+    (612, "CF", "Central African Republic"),
+    (613, "CM", "Cameroon"),
+    (615, "CG", "Congo"),
+    (616, "KM", "Comoros"),
+    (617, "CV", "Cabo Verde"),
+    (618, "TF", "Crozet Archipelago"),
+    (619, "CI", "Côte d'Ivoire"),
+    (620, "KM", "Comoros"),
+    (621, "DJ", "Djibouti"),
+    (622, "EG", "Egypt"),
+    (624, "ET", "Ethiopia"),
+    (625, "ER", "Eritrea"),
+    (626, "GA", "Gabon"),
+    (627, "GH", "Ghana"),
+    (629, "GM", "Gambia"),
+    (630, "GW", "Guinea-Bissau"),
+    (631, "GQ", "Equatorial Guinea"),
+    (632, "GN", "Guinea"),
+    (633, "BF", "Burkina Faso"),
+    (634, "KE", "Kenya"),
+    (635, "TF", "Kerguelen Islands"),
+    (636, "LR", "Liberia"),
+    (637, "LR", "Liberia"),
+    (638, "SS", "South Sudan"),
+    (642, "LY", "Libya"),
+    (644, "LS", "Lesotho"),
+    (645, "MU", "Mauritius"),
+    (647, "MG", "Madagascar"),
+    (649, "ML", "Mali"),
+    (650, "MZ", "Mozambique"),
+    (654, "MR", "Mauritania"),
+    (655, "MW", "Malawi"),
+    (656, "NE", "Niger"),
+    (657, "NG", "Nigeria"),
+    (659, "NA", "Namibia"),
+    (660, "RE", "Réunion"),
+    (661, "RW", "Rwanda"),
+    (662, "SD", "Sudan"),
+    (663, "SN", "Senegal"),
+    (664, "SC", "Seychelles"),
+    (665, "SH", "Saint Helena"),
+    (666, "SO", "Somalia"),
+    (667, "SL", "Sierra Leone"),
+    (668, "ST", "Sao Tome and Principe"),
+    (669, "SZ", "Eswatini"),
+    (670, "TD", "Chad"),
+    (671, "TG", "Togo"),
+    (672, "TN", "Tunisia"),
+    (674, "TZ", "Tanzania"),
+    (675, "UG", "Uganda"),
+    (676, "CD", "Democratic Republic of the Congo"),
+    (677, "TZ", "Tanzania"),
+    (678, "ZM", "Zambia"),
+    (679, "ZW", "Zimbabwe"),
+    (701, "AR", "Argentina"),
+    (710, "BR", "Brazil"),
+    (720, "BO", "Bolivia"),
+    (725, "CL", "Chile"),
+    (730, "CO", "Colombia"),
+    (735, "EC", "Ecuador"),
+    (740, "FK", "Falkland Islands"),
+    (745, "GF", "French Guiana"),
+    (750, "GY", "Guyana"),
+    (755, "PY", "Paraguay"),
+    (760, "PE", "Peru"),
+    (765, "SR", "Suriname"),
+    (770, "UY", "Uruguay"),
+    (775, "VE", "Venezuela"),
+];
+
+/// Maritime Mobile Service Identity - a validated identifier of at most 9 digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Mmsi(u32);
+
+impl Mmsi {
+    /// Creates a new MMSI - fails for zero or more than 9 digits.
+    pub fn new(value: u32) -> Result<Self, MmsiError> {
+        if value == 0 || value > MAX_MMSI {
+            return Err(MmsiError::OutOfRange(value.into()));
+        }
+        Ok(Mmsi(value))
+    }
+
+    /// Returns the numeric value.
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the digits at the (zero based) position of the zero padded identifier.
+    fn digits(&self, start: u32, len: u32) -> u32 {
+        self.0 / 10u32.pow(9 - start - len) % 10u32.pow(len)
+    }
+
+    /// Returns the kind of station.
+    pub fn kind(&self) -> StationKind {
+        match (self.digits(0, 1), self.digits(0, 2), self.digits(0, 3)) {
+            (2..=7, _, _) => StationKind::Ship,
+            (_, 0, _) => StationKind::CoastStation,
+            (0, _, _) => StationKind::Group,
+            (_, _, 111) => StationKind::SarAircraft,
+            (8, _, _) => StationKind::Handheld,
+            (_, 98, _) => StationKind::AuxiliaryCraft,
+            (_, 99, _) => StationKind::AidToNavigation,
+            (_, _, 970 | 972 | 974) => StationKind::SarTransmitter,
+            _ => StationKind::Unknown,
+        }
+    }
+
+    /// Returns the Maritime Identification Digits - None if the kind has none or they are invalid.
+    pub fn mid(&self) -> Option<u16> {
+        let mid = match self.kind() {
+            StationKind::Ship => self.digits(0, 3),
+            StationKind::Group | StationKind::Handheld => self.digits(1, 3),
+            StationKind::CoastStation
+            | StationKind::AuxiliaryCraft
+            | StationKind::AidToNavigation => self.digits(2, 3),
+            StationKind::SarAircraft => self.digits(3, 3),
+            StationKind::SarTransmitter | StationKind::Unknown => return None,
+        };
+        (200..800).contains(&mid).then_some(mid as u16)
+    }
+
+    /// Returns the flag state - None if the MID is not assigned.
+    pub fn flag_state(&self) -> Option<FlagState> {
+        let mid = self.mid()?;
+        MIDS.binary_search_by_key(&mid, |(item, _, _)| *item)
+            .ok()
+            .map(|pos| FlagState {
+                code: MIDS[pos].1,
+                name: MIDS[pos].2,
+            })
+    }
+}
+
+impl TryFrom<u32> for Mmsi {
+    type Error = MmsiError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Mmsi::new(value)
+    }
+}
+
+impl TryFrom<i64> for Mmsi {
+    type Error = MmsiError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u32::try_from(value)
+            .map_err(|_| MmsiError::OutOfRange(value))
+            .and_then(Mmsi::new)
+    }
+}
+
+impl From<Mmsi> for u32 {
+    fn from(mmsi: Mmsi) -> Self {
+        mmsi.0
+    }
+}
+
+impl fmt::Display for Mmsi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:09}", self.0)
+    }
+}
+
+impl std::str::FromStr for Mmsi {
+    type Err = MmsiError;
+
+    /// Parses up to 9 digits - e.g. "244123456" or "002442000".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.trim();
+        if digits.is_empty() || digits.len() > 9 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(MmsiError::Invalid(s.to_string()));
+        }
+        Mmsi::new(digits.parse().expect("at most 9 digits."))
+    }
+}
+
+impl serde::Serialize for Mmsi {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+/// Visitor accepting numbers & strings of digits - the latter e.g. for keys of JSON objects.
+struct MmsiVisitor;
+
+impl serde::de::Visitor<'_> for MmsiVisitor {
+    type Value = Mmsi;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an MMSI of at most 9 digits")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Mmsi, E> {
+        let value = i64::try_from(value).unwrap_or(i64::MAX);
+        Mmsi::try_from(value).map_err(E::custom)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Mmsi, E> {
+        Mmsi::try_from(value).map_err(E::custom)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Mmsi, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Mmsi {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MmsiVisitor)
+    }
+}
+
+#[cfg(feature = "rocket")]
+impl<'v> rocket::form::FromFormField<'v> for Mmsi {
+    fn from_value(field: rocket::form::ValueField<'v>) -> rocket::form::Result<'v, Self> {
+        field
+            .value
+            .parse()
+            .map_err(|err: MmsiError| rocket::form::Error::validation(err.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_new_for_success() {
+        Mmsi::new(244123456).expect("a valid mmsi.");
+        Mmsi::new(1).expect("a valid mmsi.");
+        Mmsi::new(999_999_999).expect("a valid mmsi.");
+    }
+
+    #[test]
+    fn test_from_str_for_success() {
+        "244123456".parse::<Mmsi>().expect("a valid mmsi.");
+        "002442000".parse::<Mmsi>().expect("a valid mmsi.");
+        " 123 ".parse::<Mmsi>().expect("a valid mmsi.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_new_for_failure() {
+        assert_eq!(Mmsi::new(0), Err(MmsiError::OutOfRange(0)));
+        assert_eq!(
+            Mmsi::new(1_000_000_000),
+            Err(MmsiError::OutOfRange(1_000_000_000))
+        );
+        assert_eq!(Mmsi::try_from(-1i64), Err(MmsiError::OutOfRange(-1)));
+    }
+
+    #[test]
+    fn test_from_str_for_failure() {
+        for value in ["", "foo", "-123", "+123", "1234567890", "24412345a", "2.5"] {
+            assert_eq!(
+                value.parse::<Mmsi>(),
+                Err(MmsiError::Invalid(value.to_string()))
+            );
+        }
+        assert_eq!("000000000".parse::<Mmsi>(), Err(MmsiError::OutOfRange(0)));
+        assert_eq!(
+            "foo".parse::<Mmsi>().err().unwrap().to_string(),
+            "invalid mmsi: foo"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_for_failure() {
+        for value in ["0", "-1", "1000000000", "1.5", "\"foo\"", "null", "[1]"] {
+            assert!(serde_json::from_str::<Mmsi>(value).is_err());
+        }
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_kind_for_sanity() {
+        for (value, kind, mid) in [
+            (244123456, StationKind::Ship, Some(244)),
+            (24412345, StationKind::Group, Some(244)),
+            (2442000, StationKind::CoastStation, Some(244)),
+            (111244100, StationKind::SarAircraft, Some(244)),
+            (992446001, StationKind::AidToNavigation, Some(244)),
+            (982441234, StationKind::AuxiliaryCraft, Some(244)),
+            (824412345, StationKind::Handheld, Some(244)),
+            (970123456, StationKind::SarTransmitter, None),
+            (972123456, StationKind::SarTransmitter, None),
+            (974123456, StationKind::SarTransmitter, None),
+            (123456789, StationKind::Unknown, None),
+            (912345678, StationKind::Unknown, None),
+            // not a valid MID.
+            (123, StationKind::CoastStation, None),
+            (81234567, StationKind::Group, None),
+        ] {
+            let mmsi = Mmsi::new(value).unwrap();
+            assert_eq!(mmsi.kind(), kind, "{}", value);
+            assert_eq!(mmsi.mid(), mid, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_flag_state_for_sanity() {
+        let flag = |value| Mmsi::new(value).unwrap().flag_state();
+        assert_eq!(
+            flag(244123456),
+            Some(FlagState {
+                code: "NL",
+                name: "Netherlands"
+            })
+        );
+        assert_eq!(flag(2190064).unwrap().code, "DK");
+        assert_eq!(flag(367000001).unwrap().name, "United States");
+        assert_eq!(flag(992446001).unwrap().code, "NL");
+        assert_eq!(flag(775123456).unwrap().code, "VE");
+        // unassigned MID.
+        assert_eq!(flag(200123456), None);
+        assert_eq!(flag(970123456), None);
+        // the table is ordered for the binary search.
+        assert!(MIDS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_serialize_for_sanity() {
+        let mmsi = Mmsi::new(2442000).unwrap();
+        assert_eq!(mmsi.to_string(), "002442000");
+        assert_eq!("002442000".parse::<Mmsi>(), Ok(mmsi));
+        assert_eq!(serde_json::to_string(&mmsi).unwrap(), "2442000");
+        assert_eq!(serde_json::from_str::<Mmsi>("2442000").unwrap(), mmsi);
+        assert_eq!(serde_json::from_str::<Mmsi>("\"002442000\"").unwrap(), mmsi);
+        // as keys of JSON objects.
+        let map = std::collections::HashMap::from([(mmsi, 1)]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, "{\"2442000\":1}");
+        let res: std::collections::HashMap<Mmsi, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(res, map);
+    }
+}
@@ -0,0 +1,327 @@
+//! Encoded polylines - based on: <https://developers.google.com/maps/documentation/utilities/polylinealgorithm>.
+//!
+//! Tracks are encoded as a string of printable ASCII characters holding the zigzag & base64-ish
+//! encoded deltas of the latitudes & longitudes, rounded to 5 (Google Maps) or 6 (OSRM, Valhalla)
+//! decimals.
+
+use std::{error, fmt};
+
+use crate::ais::{NavStatus, ShipType};
+use crate::mmsi::Mmsi;
+use crate::{Coordinate, CoordinateError, Vessel};
+
+/// Precision used by Google Maps.
+pub const DEFAULT_PRECISION: u32 = 5;
+
+/// Supported precisions - the number of decimals kept.
+const PRECISIONS: [u32; 2] = [5, 6];
+
+/// Offset added to each 5-bit chunk to make it a printable character.
+const CHUNK_OFFSET: u8 = 63;
+
+/// Flag set on chunks followed by more chunks of the same value.
+const CONTINUATION: u8 = 0x20;
+
+/// Errors while encoding or decoding polylines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolylineError {
+    /// Only a precision of 5 or 6 is supported.
+    UnsupportedPrecision(u32),
+    /// Character outside of the encoding alphabet - with its position.
+    InvalidCharacter(usize),
+    /// The polyline ends within a value or coordinate.
+    Truncated,
+    /// Decoded value is not a valid coordinate.
+    InvalidCoordinate(CoordinateError),
+}
+
+impl fmt::Display for PolylineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolylineError::UnsupportedPrecision(precision) => {
+                write!(f, "unsupported precision: {} - use 5 or 6", precision)
+            }
+            PolylineError::InvalidCharacter(pos) => write!(f, "invalid character at {}", pos),
+            PolylineError::Truncated => write!(f, "truncated polyline"),
+            PolylineError::InvalidCoordinate(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for PolylineError {}
+
+/// A track as encoded polyline.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EncodedPath {
+    pub polyline: String,
+    pub precision: u32,
+}
+
+impl EncodedPath {
+    /// Encodes the track using the given precision.
+    pub fn new(path: &[Coordinate], precision: u32) -> Result<Self, PolylineError> {
+        Ok(EncodedPath {
+            polyline: encode(path, precision)?,
+            precision,
+        })
+    }
+
+    /// Decodes the track.
+    pub fn decode(&self) -> Result<Vec<Coordinate>, PolylineError> {
+        decode(&self.polyline, self.precision)
+    }
+}
+
+/// A vessel with its track as encoded polyline - deserializes as a [`Vessel`].
+#[derive(serde::Serialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EncodedVessel {
+    #[cfg_attr(feature = "openapi", schema(value_type = u32))]
+    pub mmsi: Mmsi,
+    pub name: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = u8))]
+    pub ship_type: ShipType,
+    pub coordinates: EncodedPath,
+    pub timestamps: Vec<String>,
+    pub speeds: Vec<f64>,
+    pub headings: Vec<f64>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<u8>))]
+    pub statuses: Vec<NavStatus>,
+    pub destinations: Vec<String>,
+}
+
+impl EncodedVessel {
+    /// Encodes the track of the vessel using the given precision.
+    pub fn new(vessel: &Vessel, precision: u32) -> Result<Self, PolylineError> {
+        Ok(EncodedVessel {
+            mmsi: vessel.mmsi,
+            name: vessel.name.clone(),
+            ship_type: vessel.ship_type,
+            coordinates: EncodedPath::new(&vessel.coordinates, precision)?,
+            timestamps: vessel.timestamps.clone(),
+            speeds: vessel.speeds.clone(),
+            headings: vessel.headings.clone(),
+            statuses: vessel.statuses.clone(),
+            destinations: vessel.destinations.clone(),
+        })
+    }
+}
+
+/// List of vessels with their tracks as encoded polylines.
+#[derive(serde::Serialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EncodedVesselList {
+    pub vessels: Vec<EncodedVessel>,
+}
+
+/// Returns the factor to scale degrees with for the precision.
+fn factor(precision: u32) -> Result<f64, PolylineError> {
+    if !PRECISIONS.contains(&precision) {
+        return Err(PolylineError::UnsupportedPrecision(precision));
+    }
+    Ok(10f64.powi(precision as i32))
+}
+
+/// Appends a single (delta) value to the polyline.
+fn encode_value(value: i64, res: &mut String) {
+    // zigzag encoding so small negative values stay small.
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= CONTINUATION as u64 {
+        res.push(((value as u8 & 0x1f | CONTINUATION) + CHUNK_OFFSET) as char);
+        value >>= 5;
+    }
+    res.push((value as u8 + CHUNK_OFFSET) as char);
+}
+
+/// Encodes a track as polyline - using a precision of 5 or 6 decimals.
+pub fn encode(path: &[Coordinate], precision: u32) -> Result<String, PolylineError> {
+    let factor = factor(precision)?;
+    let mut res = String::with_capacity(path.len() * 8);
+    let (mut last_lat, mut last_lon) = (0i64, 0i64);
+    for coordinate in path {
+        let lat = (coordinate.lat * factor).round() as i64;
+        let lon = (coordinate.lon * factor).round() as i64;
+        encode_value(lat - last_lat, &mut res);
+        encode_value(lon - last_lon, &mut res);
+        (last_lat, last_lon) = (lat, lon);
+    }
+    Ok(res)
+}
+
+/// Reads the next (delta) value from the polyline - None if at the end.
+fn decode_value(
+    chars: &mut impl Iterator<Item = (usize, u8)>,
+) -> Result<Option<i64>, PolylineError> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    let mut first = true;
+    loop {
+        let Some((pos, char)) = chars.next() else {
+            return if first {
+                Ok(None)
+            } else {
+                Err(PolylineError::Truncated)
+            };
+        };
+        // more than 64 bits can't be a valid value.
+        if !(CHUNK_OFFSET..CHUNK_OFFSET + 64).contains(&char) || shift >= 64 {
+            return Err(PolylineError::InvalidCharacter(pos));
+        }
+        let chunk = char - CHUNK_OFFSET;
+        value |= ((chunk & 0x1f) as u64) << shift;
+        shift += 5;
+        first = false;
+        if chunk & CONTINUATION == 0 {
+            return Ok(Some((value >> 1) as i64 ^ -((value & 1) as i64)));
+        }
+    }
+}
+
+/// Decodes a polyline - using a precision of 5 or 6 decimals.
+pub fn decode(polyline: &str, precision: u32) -> Result<Vec<Coordinate>, PolylineError> {
+    let factor = factor(precision)?;
+    let mut chars = polyline.bytes().enumerate();
+    let mut res = vec![];
+    let (mut lat, mut lon) = (0i64, 0i64);
+    while let Some(delta) = decode_value(&mut chars)? {
+        let Some(lon_delta) = decode_value(&mut chars)? else {
+            return Err(PolylineError::Truncated);
+        };
+        lat = lat.saturating_add(delta);
+        lon = lon.saturating_add(lon_delta);
+        let coordinate = Coordinate::new(lat as f64 / factor, lon as f64 / factor)
+            .map_err(PolylineError::InvalidCoordinate)?;
+        res.push(coordinate);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_path() -> Vec<Coordinate> {
+        vec![
+            Coordinate {
+                lat: 38.5,
+                lon: -120.2,
+            },
+            Coordinate {
+                lat: 40.7,
+                lon: -120.95,
+            },
+            Coordinate {
+                lat: 43.252,
+                lon: -126.453,
+            },
+        ]
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_encode_for_success() {
+        encode(&get_path(), 5).expect("a valid polyline.");
+        encode(&get_path(), 6).expect("a valid polyline.");
+        encode(&[], 5).expect("a valid polyline.");
+    }
+
+    #[test]
+    fn test_decode_for_success() {
+        decode("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).expect("a valid track.");
+        decode("", 6).expect("a valid track.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_encode_for_failure() {
+        assert_eq!(
+            encode(&get_path(), 7),
+            Err(PolylineError::UnsupportedPrecision(7))
+        );
+    }
+
+    #[test]
+    fn test_decode_for_failure() {
+        assert_eq!(
+            decode("_p~iF", 4),
+            Err(PolylineError::UnsupportedPrecision(4))
+        );
+        // only a latitude.
+        assert_eq!(decode("_p~iF", 5), Err(PolylineError::Truncated));
+        // ends with a continuation chunk.
+        assert_eq!(decode("_p~iF~ps|", 5), Err(PolylineError::Truncated));
+        assert_eq!(
+            decode("_p~iF ps|U", 5),
+            Err(PolylineError::InvalidCharacter(5))
+        );
+        assert_eq!(
+            decode("_p~iF~ps|Ué", 5),
+            Err(PolylineError::InvalidCharacter(10))
+        );
+        // too many chunks for a single value.
+        assert_eq!(
+            decode(&"~".repeat(20), 5),
+            Err(PolylineError::InvalidCharacter(13))
+        );
+        // latitude out of range.
+        let polyline = encode(
+            &[Coordinate {
+                lat: 89.0,
+                lon: 0.0,
+            }],
+            5,
+        )
+        .unwrap();
+        let mut path = polyline.clone();
+        path.push_str(&polyline);
+        assert!(matches!(
+            decode(&path, 5),
+            Err(PolylineError::InvalidCoordinate(_))
+        ));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_encode_for_sanity() {
+        // example from the algorithm description.
+        assert_eq!(
+            encode(&get_path(), 5).unwrap(),
+            "_p~iF~ps|U_ulLnnqC_mqNvxq`@"
+        );
+        assert_eq!(
+            decode("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).unwrap(),
+            get_path()
+        );
+    }
+
+    #[test]
+    fn test_decode_for_sanity() {
+        // round trips within the precision.
+        let path: Vec<Coordinate> = (0..100)
+            .map(|i| Coordinate {
+                lat: 51.9 + (i as f64 * 0.1234567).sin(),
+                lon: 179.5 + i as f64 * 0.0123456,
+            })
+            .map(|item| Coordinate::new(item.lat, item.lon).unwrap())
+            .collect();
+        for precision in PRECISIONS {
+            let tolerance = 0.5 / 10f64.powi(precision as i32) + 1e-12;
+            let res = EncodedPath::new(&path, precision)
+                .unwrap()
+                .decode()
+                .unwrap();
+            assert_eq!(res.len(), path.len());
+            for (a, b) in res.iter().zip(&path) {
+                assert!((a.lat - b.lat).abs() <= tolerance);
+                assert!((a.lon - b.lon).abs() <= tolerance);
+            }
+        }
+        // precision 6 is more compact than JSON.
+        let json = serde_json::to_string(&path).unwrap();
+        assert!(encode(&path, 6).unwrap().len() * 4 < json.len());
+    }
+}
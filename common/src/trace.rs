@@ -0,0 +1,267 @@
+use std::time;
+
+use rocket::{fairing, request, route};
+use tracing::Instrument;
+use tracing_subscriber::prelude::*;
+
+/// Maximum length of a request identifier propagated by a caller.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Label used for requests not matching any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Initializes structured JSON logging - filtered using the `RUST_LOG` environment variable.
+///
+/// Should be called once before launching rocket; rocket's own log messages are captured as well.
+pub fn init() {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(false),
+        )
+        .try_init();
+}
+
+/// Identifier of a request - either propagated by the caller using the `X-Request-Id` header or
+/// generated. Use it as request guard to forward it when calling other services.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Tracing information stored with each request.
+struct RequestTrace {
+    id: RequestId,
+    span: tracing::Span,
+    start: time::Instant,
+}
+
+impl RequestTrace {
+    fn new(req: &rocket::Request<'_>) -> Self {
+        let id = req
+            .headers()
+            .get_one(crate::REQUEST_ID_HEADER)
+            .filter(|id| is_valid_request_id(id))
+            .map_or_else(|| uuid::Uuid::new_v4().to_string(), String::from);
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %req.method(),
+            uri = %req.uri(),
+        );
+        RequestTrace {
+            id: RequestId(id),
+            span,
+            start: time::Instant::now(),
+        }
+    }
+}
+
+/// Checks if a request identifier given by a caller is safe to be used - e.g. in the logs.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+#[rocket::async_trait]
+impl<'r> request::FromRequest<'r> for RequestId {
+    type Error = ();
+
+    async fn from_request(req: &'r rocket::Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(req.local_cache(|| RequestTrace::new(req)).id.clone())
+    }
+}
+
+/// Handler running the wrapped handler within the span of the request.
+#[derive(Clone)]
+struct Instrumented(Box<dyn route::Handler>);
+
+#[rocket::async_trait]
+impl route::Handler for Instrumented {
+    async fn handle<'r>(
+        &self,
+        req: &'r rocket::Request<'_>,
+        data: rocket::Data<'r>,
+    ) -> route::Outcome<'r> {
+        let span = req.local_cache(|| RequestTrace::new(req)).span.clone();
+        self.0.handle(req, data).instrument(span).await
+    }
+}
+
+/// Runs the handlers of the routes within the span of the request - log messages of the handlers
+/// carry the request id. Pass `tracing::Span::current()` on to spawned (blocking) tasks.
+pub fn instrument(routes: Vec<rocket::Route>) -> Vec<rocket::Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(Instrumented(route.handler));
+            route
+        })
+        .collect()
+}
+
+/// Fairing assigning an identifier to each request and logging its outcome & duration.
+pub struct Tracing;
+
+#[rocket::async_trait]
+impl fairing::Fairing for Tracing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Request tracing",
+            kind: fairing::Kind::Request | fairing::Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut rocket::Request<'_>, _: &mut rocket::Data<'_>) {
+        let trace = RequestTrace::new(req);
+        req.local_cache(|| trace);
+    }
+
+    async fn on_response<'r>(&self, req: &'r rocket::Request<'_>, res: &mut rocket::Response<'r>) {
+        let trace = req.local_cache(|| RequestTrace::new(req));
+        let route = req
+            .route()
+            .map_or(UNMATCHED_ROUTE, |route| route.uri.as_str());
+        trace.span.in_scope(|| {
+            tracing::info!(
+                route,
+                status = res.status().code,
+                duration_ms = trace.start.elapsed().as_secs_f64() * 1000.0,
+                "Request handled."
+            )
+        });
+        res.set_raw_header(crate::REQUEST_ID_HEADER, trace.id.0.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http;
+    use rocket::local::blocking;
+
+    #[rocket::get("/")]
+    fn index(request_id: RequestId) -> String {
+        request_id.0
+    }
+
+    #[rocket::get("/span")]
+    fn span() -> String {
+        tracing::Span::current()
+            .metadata()
+            .map_or("none", |metadata| metadata.name())
+            .to_string()
+    }
+
+    fn get_client() -> blocking::Client {
+        let rocket = rocket::build()
+            .mount("/", instrument(rocket::routes![index, span]))
+            .attach(Tracing);
+        blocking::Client::tracked(rocket).expect("a valid test client.")
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_init_for_success() {
+        init();
+        init();
+    }
+
+    #[test]
+    fn test_tracing_for_success() {
+        let client = get_client();
+        client.get("/").dispatch();
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_is_valid_request_id_for_failure() {
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("foo bar"));
+        assert!(!is_valid_request_id("foo\nbar"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn test_tracing_for_failure() {
+        // invalid ids are replaced.
+        let client = get_client();
+        let response = client
+            .get("/")
+            .header(http::Header::new(crate::REQUEST_ID_HEADER, "foo\"bar"))
+            .dispatch();
+        let id = response
+            .headers()
+            .get_one(crate::REQUEST_ID_HEADER)
+            .unwrap();
+        assert_ne!(id, "foo\"bar");
+        assert_eq!(id.len(), 36);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_is_valid_request_id_for_sanity() {
+        assert!(is_valid_request_id("foo-bar_1.2"));
+        assert!(is_valid_request_id("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+    }
+
+    #[test]
+    fn test_instrument_for_sanity() {
+        // the handler runs within the span of the request.
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let client = get_client();
+        let response = client.get("/span").dispatch();
+        assert_eq!(response.into_string(), Some("request".into()));
+    }
+
+    #[test]
+    fn test_tracing_for_sanity() {
+        let client = get_client();
+
+        // generated.
+        let response = client.get("/").dispatch();
+        let id = response
+            .headers()
+            .get_one(crate::REQUEST_ID_HEADER)
+            .unwrap()
+            .to_string();
+        assert_eq!(response.into_string(), Some(id.clone()));
+        let response = client.get("/").dispatch();
+        assert_ne!(
+            response.headers().get_one(crate::REQUEST_ID_HEADER),
+            Some(id.as_str()),
+            "Each request should get its own identifier."
+        );
+
+        // propagated.
+        let response = client
+            .get("/")
+            .header(http::Header::new(crate::REQUEST_ID_HEADER, "foo-123"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one(crate::REQUEST_ID_HEADER),
+            Some("foo-123")
+        );
+        assert_eq!(response.into_string(), Some("foo-123".into()));
+
+        // also for unmatched routes.
+        let response = client
+            .get("/foo")
+            .header(http::Header::new(crate::REQUEST_ID_HEADER, "foo-456"))
+            .dispatch();
+        assert_eq!(response.status(), http::Status::NotFound);
+        assert_eq!(
+            response.headers().get_one(crate::REQUEST_ID_HEADER),
+            Some("foo-456")
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Compact serialization of tracks for binary formats - e.g. MessagePack.
+//!
+//! Human readable formats (JSON) keep the list of coordinates. Binary formats store the latitudes
+//! & longitudes as separate columns of delta encoded fixed-point integers, so the typically small
+//! steps between consecutive positions encode in one to three bytes each.
+//!
+//! Human readable formats also accept the track as [`EncodedPath`] - an encoded polyline.
+
+use std::fmt;
+
+use serde::{de, Deserialize, Serialize};
+
+use crate::polyline::EncodedPath;
+use crate::Coordinate;
+
+/// Scale of the fixed-point coordinates - 1e-7 degrees is about a centimeter.
+const SCALE: f64 = 1e7;
+
+/// Delta encodes a column of coordinates using fixed-point integers.
+fn encode(values: impl Iterator<Item = f64>) -> Vec<i64> {
+    let mut last = 0;
+    values
+        .map(|value| {
+            let fixed = (value * SCALE).round() as i64;
+            let delta = fixed - last;
+            last = fixed;
+            delta
+        })
+        .collect()
+}
+
+/// Decodes a delta encoded column of fixed-point integers.
+fn decode(deltas: &[i64]) -> impl Iterator<Item = f64> + '_ {
+    deltas.iter().scan(0i64, |last, delta| {
+        *last = last.saturating_add(*delta);
+        Some(*last as f64 / SCALE)
+    })
+}
+
+/// Visitor accepting a list of coordinates or an encoded polyline.
+struct TrackVisitor;
+
+impl<'de> de::Visitor<'de> for TrackVisitor {
+    type Value = Vec<Coordinate>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of coordinates or an encoded polyline")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        Vec::<Coordinate>::deserialize(de::value::SeqAccessDeserializer::new(seq))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        EncodedPath::deserialize(de::value::MapAccessDeserializer::new(map))?
+            .decode()
+            .map_err(de::Error::custom)
+    }
+}
+
+pub fn serialize<S: serde::Serializer>(
+    track: &[Coordinate],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        return track.serialize(serializer);
+    }
+    let lats = encode(track.iter().map(|item| item.lat));
+    let lons = encode(track.iter().map(|item| item.lon));
+    (lats, lons).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Coordinate>, D::Error> {
+    if deserializer.is_human_readable() {
+        return deserializer.deserialize_any(TrackVisitor);
+    }
+    let (lats, lons) = <(Vec<i64>, Vec<i64>)>::deserialize(deserializer)?;
+    if lats.len() != lons.len() {
+        return Err(de::Error::invalid_length(
+            lons.len(),
+            &"as many longitudes as latitudes",
+        ));
+    }
+    decode(&lats)
+        .zip(decode(&lons))
+        .map(|(lat, lon)| Coordinate::new(lat, lon).map_err(de::Error::custom))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_encode_for_success() {
+        encode([51.9, 4.1].into_iter());
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_decode_for_failure() {
+        // overflows are saturated instead of wrapping around.
+        let res: Vec<f64> = decode(&[i64::MAX, 1]).collect();
+        assert_eq!(res[0], res[1]);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_encode_for_sanity() {
+        let res = encode([51.9, 51.90001, 51.9].into_iter());
+        assert_eq!(res, vec![519000000, 100, -100]);
+        let res: Vec<f64> = decode(&res).collect();
+        assert_eq!(res, vec![51.9, 51.90001, 51.9]);
+    }
+
+    #[test]
+    fn test_serialize_for_sanity() {
+        // JSON is unaffected.
+        let track = vec![Coordinate {
+            lat: 51.9,
+            lon: 4.1,
+        }];
+        let mut serializer = serde_json::Serializer::new(vec![]);
+        serialize(&track, &mut serializer).unwrap();
+        assert_eq!(
+            String::from_utf8(serializer.into_inner()).unwrap(),
+            "[{\"lat\":51.9,\"lon\":4.1}]"
+        );
+        let mut deserializer = serde_json::Deserializer::from_str("[[4.1, 51.9]]");
+        assert_eq!(deserialize(&mut deserializer).unwrap(), track);
+
+        // as well as encoded polylines.
+        let mut deserializer =
+            serde_json::Deserializer::from_str("{\"polyline\": \"_vw{H_x_X\", \"precision\": 5}");
+        assert_eq!(deserialize(&mut deserializer).unwrap(), track);
+        let mut deserializer =
+            serde_json::Deserializer::from_str("{\"polyline\": \"_vw{H\", \"precision\": 5}");
+        assert!(deserialize(&mut deserializer).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Compact binary wire format (MessagePack) for transferring tracks between the services & the
+//! frontend - negotiated with JSON using the `Accept` & `Content-Type` headers.
+
+/// Media type of MessagePack documents.
+pub const MSGPACK_MEDIA_TYPE: (&str, &str) = ("application", "msgpack");
+
+/// Encodes a value as MessagePack - structs are encoded as maps so the format is self describing.
+pub fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(value)
+}
+
+/// Decodes a value from MessagePack.
+pub fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(bytes)
+}
+
+/// Checks if the media type is MessagePack - including the unregistered `x-` variant.
+#[cfg(feature = "rocket")]
+fn is_msgpack(media_type: &rocket::http::MediaType) -> bool {
+    let (top, sub) = MSGPACK_MEDIA_TYPE;
+    media_type.top() == top && (media_type.sub() == sub || media_type.sub() == "x-msgpack")
+}
+
+/// Data guard & responder using MessagePack if requested by the client - JSON otherwise.
+///
+/// Request bodies are decoded based on the `Content-Type`, responses are encoded based on the
+/// preferred media type of the `Accept` header.
+#[cfg(feature = "rocket")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Negotiated<T>(pub T);
+
+#[cfg(feature = "rocket")]
+impl<T> Negotiated<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "rocket")]
+impl<T> std::ops::Deref for Negotiated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "rocket")]
+impl<'r, T: serde::Serialize> rocket::response::Responder<'r, 'static> for Negotiated<T> {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let msgpack = req
+            .accept()
+            .is_some_and(|accept| is_msgpack(accept.preferred().media_type()));
+        let mut res = if msgpack {
+            let (top, sub) = MSGPACK_MEDIA_TYPE;
+            let body = encode(&self.0).map_err(|_| rocket::http::Status::InternalServerError)?;
+            (rocket::http::ContentType::new(top, sub), body).respond_to(req)?
+        } else {
+            rocket::serde::json::Json(self.0).respond_to(req)?
+        };
+        res.adjoin_raw_header("Vary", "Accept");
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "rocket")]
+#[rocket::async_trait]
+impl<'r, T: serde::de::DeserializeOwned> rocket::data::FromData<'r> for Negotiated<T> {
+    type Error = String;
+
+    async fn from_data(
+        req: &'r rocket::Request<'_>,
+        data: rocket::Data<'r>,
+    ) -> rocket::data::Outcome<'r, Self> {
+        use rocket::data::{Outcome, ToByteUnit};
+        use rocket::http::Status;
+
+        if !req.content_type().is_some_and(|item| is_msgpack(item)) {
+            return match rocket::serde::json::Json::<T>::from_data(req, data).await {
+                Outcome::Success(json) => Outcome::Success(Negotiated(json.into_inner())),
+                Outcome::Error((status, err)) => Outcome::Error((status, err.to_string())),
+                Outcome::Forward(forward) => Outcome::Forward(forward),
+            };
+        }
+        // own `msgpack` limit - with the same default as rocket uses for JSON documents.
+        let limit = req.limits().get("msgpack").unwrap_or(1.mebibytes());
+        match data.open(limit).into_bytes().await {
+            Ok(bytes) if bytes.is_complete() => match decode(&bytes) {
+                Ok(value) => Outcome::Success(Negotiated(value)),
+                Err(err) => Outcome::Error((Status::UnprocessableEntity, err.to_string())),
+            },
+            Ok(_) => Outcome::Error((Status::PayloadTooLarge, "Body too large.".to_string())),
+            Err(err) => Outcome::Error((Status::BadRequest, err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections;
+
+    fn get_vessels() -> crate::VesselList {
+        crate::VesselList {
+            vessels: vec![crate::Vessel {
+                mmsi: crate::mmsi::Mmsi::new(123).unwrap(),
+                name: "foo".to_string(),
+                ship_type: crate::ais::ShipType::Tanker(crate::ais::Hazard::A),
+                coordinates: vec![
+                    crate::Coordinate {
+                        lat: 51.9,
+                        lon: 4.1
+                    };
+                    100
+                ],
+                timestamps: vec!["2022-01-01T00:00:00Z".to_string(); 100],
+                speeds: vec![10.0; 100],
+                headings: vec![90.0; 100],
+                statuses: vec![crate::ais::NavStatus::UnderWayUsingEngine; 100],
+                destinations: vec!["NLRTM".to_string(); 100],
+            }],
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_encode_for_success() {
+        encode(&get_vessels()).expect("a valid encoding.");
+    }
+
+    #[test]
+    fn test_decode_for_success() {
+        let bytes = encode(&get_vessels()).unwrap();
+        decode::<crate::VesselList>(&bytes).expect("a valid decoding.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_decode_for_failure() {
+        assert!(decode::<crate::VesselList>(b"foo").is_err());
+        // invalid coordinates are still validated.
+        let bytes = encode(&crate::Coordinate {
+            lat: 91.0,
+            lon: 4.1,
+        })
+        .unwrap();
+        assert!(decode::<crate::Coordinate>(&bytes).is_err());
+        let path = (vec![910000000i64], vec![41000000i64]);
+        let bytes = encode(&collections::HashMap::from([("path", path)])).unwrap();
+        assert!(decode::<crate::SimplifyOut>(&bytes).is_err());
+
+        // columns of different length.
+        let path = (vec![519000000i64], vec![41000000i64, 1]);
+        let bytes = encode(&collections::HashMap::from([("path", path)])).unwrap();
+        assert!(decode::<crate::SimplifyOut>(&bytes).is_err());
+
+        // valid.
+        let path = (vec![519000000i64, 1], vec![41000000i64, -1]);
+        let bytes = encode(&collections::HashMap::from([("path", path)])).unwrap();
+        let res: crate::SimplifyOut = decode(&bytes).unwrap();
+        assert_eq!(res.path[1].lat, 51.9000001);
+        assert_eq!(res.path[1].lon, 4.0999999);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_encode_for_sanity() {
+        let vessels = get_vessels();
+        let bytes = encode(&vessels).unwrap();
+        let json = serde_json::to_vec(&vessels).unwrap();
+        assert!(bytes.len() < json.len());
+
+        let res: crate::VesselList = decode(&bytes).unwrap();
+        assert_eq!(res.vessels[0].mmsi.value(), 123);
+        assert_eq!(res.vessels[0].coordinates, vessels.vessels[0].coordinates);
+        assert_eq!(res.vessels[0].timestamps, vessels.vessels[0].timestamps);
+    }
+
+    #[cfg(feature = "rocket")]
+    mod negotiated {
+        use super::*;
+        use rocket::http;
+        use rocket::local::blocking;
+
+        #[rocket::post("/", data = "<list>")]
+        fn echo(list: Negotiated<crate::MMSIList>) -> Negotiated<crate::MMSIList> {
+            list
+        }
+
+        fn get_client() -> blocking::Client {
+            let rocket = rocket::build().mount("/", rocket::routes![echo]);
+            blocking::Client::tracked(rocket).expect("a valid test client.")
+        }
+
+        fn msgpack() -> http::ContentType {
+            let (top, sub) = MSGPACK_MEDIA_TYPE;
+            http::ContentType::new(top, sub)
+        }
+
+        // Tests for failure.
+
+        #[test]
+        fn test_negotiated_for_failure() {
+            let client = get_client();
+            let status = client
+                .post("/")
+                .header(msgpack())
+                .body("foo")
+                .dispatch()
+                .status();
+            assert_eq!(status, http::Status::UnprocessableEntity);
+            let status = client
+                .post("/")
+                .header(http::ContentType::JSON)
+                .body("foo")
+                .dispatch()
+                .status();
+            assert_eq!(status, http::Status::BadRequest);
+        }
+
+        // Tests for sanity.
+
+        #[test]
+        fn test_negotiated_for_sanity() {
+            let client = get_client();
+            let list = crate::MMSIList {
+                vessels: [1, 2, 3]
+                    .into_iter()
+                    .map(|item| crate::mmsi::Mmsi::new(item).unwrap())
+                    .collect(),
+            };
+
+            // JSON by default.
+            let response = client
+                .post("/")
+                .header(http::ContentType::JSON)
+                .body("{\"vessels\": [1, 2, 3]}")
+                .dispatch();
+            assert_eq!(response.content_type(), Some(http::ContentType::JSON));
+            assert_eq!(response.headers().get_one("Vary"), Some("Accept"));
+            let res = response.into_json::<crate::MMSIList>().unwrap();
+            assert_eq!(res.vessels, list.vessels);
+
+            // MessagePack if requested - in both directions.
+            let response = client
+                .post("/")
+                .header(msgpack())
+                .header(http::Header::new("Accept", "application/msgpack"))
+                .body(encode(&list).unwrap())
+                .dispatch();
+            assert_eq!(response.content_type(), Some(msgpack()));
+            let res: crate::MMSIList = decode(&response.into_bytes().unwrap()).unwrap();
+            assert_eq!(res.vessels, list.vessels);
+
+            // JSON preferred.
+            let response = client
+                .post("/")
+                .header(http::ContentType::JSON)
+                .header(http::Header::new(
+                    "Accept",
+                    "application/json, application/msgpack;q=0.5",
+                ))
+                .body("{\"vessels\": []}")
+                .dispatch();
+            assert_eq!(response.content_type(), Some(http::ContentType::JSON));
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Helpers for writing XML documents - also used for HTML, e.g. the popups of the frontend.
+
+use std::borrow::Cow;
+
+/// Escapes text for use within XML elements & attributes.
+pub fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['<', '>', '&', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut res = String::with_capacity(text.len() + 16);
+    for char in text.chars() {
+        match char {
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '&' => res.push_str("&amp;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&apos;"),
+            _ => res.push(char),
+        }
+    }
+    Cow::Owned(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_escape_for_success() {
+        escape("foo");
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_escape_for_sanity() {
+        assert!(matches!(escape("boaty"), Cow::Borrowed("boaty")));
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+}
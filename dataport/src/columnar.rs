@@ -0,0 +1,474 @@
+//! Columnar export of the position reports - as Arrow IPC stream or Parquet file, for offline
+//! analytics with e.g. pandas, polars or DuckDB.
+//!
+//! Every position report becomes a row; the rows are written in batches so the port data is only
+//! locked briefly & the export can be streamed to the client while it is written.
+
+use std::{error, fmt, io, sync};
+
+use arrow_array::builder::{
+    ArrayBuilder, Float64Builder, StringBuilder, TimestampSecondBuilder, UInt32Builder,
+    UInt8Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+
+/// Media type of Arrow IPC streams.
+pub const ARROW_MEDIA_TYPE: (&str, &str) = ("application", "vnd.apache.arrow.stream");
+
+/// Media type of Parquet files.
+pub const PARQUET_MEDIA_TYPE: (&str, &str) = ("application", "vnd.apache.parquet");
+
+/// Rows per record batch (and Parquet row group).
+const BATCH_ROWS: usize = 64 * 1024;
+
+/// Time zone of the timestamps.
+const TIME_ZONE: &str = "UTC";
+
+/// Errors while exporting the position reports.
+#[derive(Debug)]
+pub enum ExportError {
+    /// Bounds of the time window are not RFC 3339 timestamps.
+    InvalidTime(String),
+    /// Bounding box is not given as min_lon,min_lat,max_lon,max_lat.
+    InvalidBBox(String),
+    Arrow(ArrowError),
+    Parquet(parquet::errors::ParquetError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::InvalidTime(time) => write!(f, "invalid time: {}", time),
+            ExportError::InvalidBBox(bbox) => write!(
+                f,
+                "invalid bbox: {} - expected min_lon,min_lat,max_lon,max_lat",
+                bbox
+            ),
+            ExportError::Arrow(err) => write!(f, "{}", err),
+            ExportError::Parquet(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for ExportError {}
+
+impl From<ArrowError> for ExportError {
+    fn from(err: ArrowError) -> Self {
+        ExportError::Arrow(err)
+    }
+}
+
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        ExportError::Parquet(err)
+    }
+}
+
+/// Supported export formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Arrow,
+    Parquet,
+}
+
+/// Bounding box - crosses the antimeridian if min_lon is larger than max_lon.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BBox {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl std::str::FromStr for BBox {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ExportError::InvalidBBox(s.to_string());
+        let values = s
+            .split(',')
+            .map(|item| item.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let [min_lon, min_lat, max_lon, max_lat] = values[..] else {
+            return Err(invalid());
+        };
+        if min_lat > max_lat
+            || !(-90.0..=90.0).contains(&min_lat)
+            || !(-90.0..=90.0).contains(&max_lat)
+            || !(-180.0..=180.0).contains(&min_lon)
+            || !(-180.0..=180.0).contains(&max_lon)
+        {
+            return Err(invalid());
+        }
+        Ok(BBox {
+            min_lon,
+            min_lat,
+            max_lon,
+            max_lat,
+        })
+    }
+}
+
+impl BBox {
+    /// Returns true if the coordinate is within the bounding box.
+    pub fn contains(&self, coordinate: &common::Coordinate) -> bool {
+        let lon = if self.min_lon <= self.max_lon {
+            (self.min_lon..=self.max_lon).contains(&coordinate.lon)
+        } else {
+            coordinate.lon >= self.min_lon || coordinate.lon <= self.max_lon
+        };
+        lon && (self.min_lat..=self.max_lat).contains(&coordinate.lat)
+    }
+}
+
+/// Selection of the position reports to export.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// Start of the time window (inclusive) - seconds since the epoch.
+    pub from: Option<i64>,
+    /// End of the time window (exclusive) - seconds since the epoch.
+    pub to: Option<i64>,
+    pub bbox: Option<BBox>,
+}
+
+/// Parses a RFC 3339 timestamp into seconds since the epoch.
+fn parse_time(time: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|item| item.timestamp())
+}
+
+impl Filter {
+    /// Creates a filter from the (optional) query parameters.
+    pub fn new(
+        from: Option<&str>,
+        to: Option<&str>,
+        bbox: Option<&str>,
+    ) -> Result<Self, ExportError> {
+        let time = |time: Option<&str>| {
+            time.map(|item| parse_time(item).ok_or(ExportError::InvalidTime(item.to_string())))
+                .transpose()
+        };
+        Ok(Filter {
+            from: time(from)?,
+            to: time(to)?,
+            bbox: bbox.map(str::parse).transpose()?,
+        })
+    }
+
+    /// Returns true if the position report is selected - reports without a valid timestamp are
+    /// skipped if a time window is given.
+    fn matches(&self, coordinate: &common::Coordinate, time: Option<i64>) -> bool {
+        let after = |from| time.is_some_and(|time| time >= from);
+        let before = |to| time.is_some_and(|time| time < to);
+        self.from.is_none_or(after)
+            && self.to.is_none_or(before)
+            && self.bbox.is_none_or(|bbox| bbox.contains(coordinate))
+    }
+}
+
+/// Schema of the export - one row per position report.
+pub fn schema() -> SchemaRef {
+    let time = DataType::Timestamp(TimeUnit::Second, Some(TIME_ZONE.into()));
+    sync::Arc::new(Schema::new(vec![
+        Field::new("mmsi", DataType::UInt32, false),
+        Field::new("time", time, true),
+        Field::new("lat", DataType::Float64, false),
+        Field::new("lon", DataType::Float64, false),
+        Field::new("sog", DataType::Float64, true),
+        Field::new("heading", DataType::Float64, true),
+        Field::new("status", DataType::UInt8, true),
+        Field::new("destination", DataType::Utf8, true),
+    ]))
+}
+
+/// Returns the text of a position report - empty texts are missing.
+fn text(values: &[String], i: usize) -> Option<&str> {
+    values
+        .get(i)
+        .filter(|item| !item.is_empty())
+        .map(String::as_str)
+}
+
+/// Builders of the columns of a record batch.
+#[derive(Default)]
+struct Rows {
+    mmsi: UInt32Builder,
+    time: TimestampSecondBuilder,
+    lat: Float64Builder,
+    lon: Float64Builder,
+    sog: Float64Builder,
+    heading: Float64Builder,
+    status: UInt8Builder,
+    destination: StringBuilder,
+}
+
+impl Rows {
+    fn len(&self) -> usize {
+        self.mmsi.len()
+    }
+
+    /// Appends the selected position reports of the vessel.
+    fn append(&mut self, vessel: &common::Vessel, filter: &Filter) {
+        for (i, coordinate) in vessel.coordinates.iter().enumerate() {
+            let time = vessel.timestamps.get(i).and_then(|item| parse_time(item));
+            if !filter.matches(coordinate, time) {
+                continue;
+            }
+            self.mmsi.append_value(vessel.mmsi.value());
+            self.time.append_option(time);
+            self.lat.append_value(coordinate.lat);
+            self.lon.append_value(coordinate.lon);
+            self.sog.append_option(vessel.speeds.get(i).copied());
+            self.heading.append_option(vessel.headings.get(i).copied());
+            self.status
+                .append_option(vessel.statuses.get(i).map(|item| item.code()));
+            self.destination
+                .append_option(text(&vessel.destinations, i));
+        }
+    }
+
+    /// Turns the rows into a record batch - resets the builders.
+    fn finish(&mut self, schema: &SchemaRef) -> Result<RecordBatch, ArrowError> {
+        let columns: Vec<ArrayRef> = vec![
+            sync::Arc::new(self.mmsi.finish()),
+            sync::Arc::new(self.time.finish().with_timezone(TIME_ZONE)),
+            sync::Arc::new(self.lat.finish()),
+            sync::Arc::new(self.lon.finish()),
+            sync::Arc::new(self.sog.finish()),
+            sync::Arc::new(self.heading.finish()),
+            sync::Arc::new(self.status.finish()),
+            sync::Arc::new(self.destination.finish()),
+        ];
+        RecordBatch::try_new(schema.clone(), columns)
+    }
+}
+
+/// Calls `write` with the selected position reports in batches - ordered by MMSI & time.
+///
+/// The port data is only read locked while a single vessel is processed.
+fn for_each_batch(
+    port: &sync::RwLock<common::Port>,
+    filter: &Filter,
+    mut write: impl FnMut(&RecordBatch) -> Result<(), ExportError>,
+) -> Result<(), ExportError> {
+    let schema = schema();
+    let mut mmsis: Vec<common::mmsi::Mmsi> = port.read().unwrap().vessels.keys().copied().collect();
+    mmsis.sort_unstable();
+
+    let mut rows = Rows::default();
+    for mmsi in mmsis {
+        if let Some(vessel) = port.read().unwrap().vessels.get(&mmsi) {
+            rows.append(vessel, filter);
+        }
+        if rows.len() >= BATCH_ROWS {
+            write(&rows.finish(&schema)?)?;
+        }
+    }
+    if rows.len() > 0 {
+        write(&rows.finish(&schema)?)?;
+    }
+    Ok(())
+}
+
+/// Writes the selected position reports - returns the writer once the export is complete.
+pub fn write<W: io::Write + Send>(
+    port: &sync::RwLock<common::Port>,
+    filter: &Filter,
+    format: Format,
+    writer: W,
+) -> Result<W, ExportError> {
+    match format {
+        Format::Arrow => {
+            let mut writer = arrow_ipc::writer::StreamWriter::try_new(writer, &schema())?;
+            for_each_batch(port, filter, |batch| Ok(writer.write(batch)?))?;
+            Ok(writer.into_inner()?)
+        }
+        Format::Parquet => {
+            let props = parquet::file::properties::WriterProperties::builder()
+                .set_compression(parquet::basic::Compression::SNAPPY)
+                .set_max_row_group_size(BATCH_ROWS)
+                .build();
+            let mut writer = parquet::arrow::ArrowWriter::try_new(writer, schema(), Some(props))?;
+            for_each_batch(port, filter, |batch| Ok(writer.write(batch)?))?;
+            Ok(writer.into_inner()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, TimestampSecondType, UInt32Type, UInt8Type};
+    use arrow_array::Array;
+
+    fn get_port() -> sync::RwLock<common::Port> {
+        let vessel = |mmsi, lon| common::Vessel {
+            name: "boaty".to_string(),
+            ship_type: common::ais::ShipType::Cargo(common::ais::Hazard::Unspecified),
+            coordinates: vec![
+                common::Coordinate { lat: 51.0, lon },
+                common::Coordinate {
+                    lat: 51.1,
+                    lon: lon + 0.1,
+                },
+            ],
+            timestamps: vec![
+                "2022-01-01T00:00:00Z".to_string(),
+                "2022-01-01T01:00:00Z".to_string(),
+            ],
+            speeds: vec![10.0, 11.0],
+            statuses: vec![common::ais::NavStatus::UnderWayUsingEngine],
+            destinations: vec!["NLRTM".to_string(), "NLRTM".to_string()],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(mmsi).unwrap())
+        };
+        sync::RwLock::new(common::Port {
+            vessels: [vessel(456, 179.85), vessel(123, 4.0)]
+                .into_iter()
+                .map(|vessel| (vessel.mmsi, vessel))
+                .collect(),
+        })
+    }
+
+    fn read(bytes: &[u8]) -> Vec<RecordBatch> {
+        arrow_ipc::reader::StreamReader::try_new(bytes, None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn count(bytes: &[u8]) -> usize {
+        read(bytes).iter().map(RecordBatch::num_rows).sum()
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_write_for_success() {
+        write(&get_port(), &Filter::default(), Format::Arrow, vec![]).expect("an arrow stream.");
+        write(&get_port(), &Filter::default(), Format::Parquet, vec![]).expect("a parquet file.");
+    }
+
+    #[test]
+    fn test_filter_for_success() {
+        Filter::new(
+            Some("2022-01-01T00:00:00Z"),
+            Some("2022-01-02T00:00:00+01:00"),
+            Some("3.5,51.0,4.5,52.0"),
+        )
+        .expect("a valid filter.");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_filter_for_failure() {
+        for bbox in [
+            "",
+            "1,2,3",
+            "a,b,c,d",
+            "0,52,1,51",
+            "0,-91,1,0",
+            "0,0,181,1",
+        ] {
+            assert!(matches!(
+                Filter::new(None, None, Some(bbox)),
+                Err(ExportError::InvalidBBox(_))
+            ));
+        }
+        assert!(matches!(
+            Filter::new(Some("2022-01-01"), None, None),
+            Err(ExportError::InvalidTime(_))
+        ));
+        assert_eq!(
+            Filter::new(None, Some("yesterday"), None)
+                .err()
+                .unwrap()
+                .to_string(),
+            "invalid time: yesterday"
+        );
+    }
+
+    #[test]
+    fn test_write_for_failure() {
+        // nothing selected --> just the schema.
+        let filter = Filter::new(Some("2023-01-01T00:00:00Z"), None, None).unwrap();
+        let bytes = write(&get_port(), &filter, Format::Arrow, vec![]).unwrap();
+        let reader = arrow_ipc::reader::StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        assert_eq!(reader.schema(), schema());
+        assert_eq!(reader.count(), 0);
+
+        // reports without a timestamp can't be within the time window.
+        let port = get_port();
+        port.write()
+            .unwrap()
+            .vessels
+            .get_mut(&common::mmsi::Mmsi::new(123).unwrap())
+            .unwrap()
+            .timestamps = vec![];
+        let filter = Filter::new(Some("2022-01-01T00:00:00Z"), None, None).unwrap();
+        let bytes = write(&port, &filter, Format::Arrow, vec![]).unwrap();
+        assert_eq!(count(&bytes), 2);
+        let bytes = write(&port, &Filter::default(), Format::Arrow, vec![]).unwrap();
+        let batch = &read(&bytes)[0];
+        assert_eq!(batch.num_rows(), 4);
+        assert_eq!(batch.column(1).null_count(), 2);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_write_for_sanity() {
+        let bytes = write(&get_port(), &Filter::default(), Format::Arrow, vec![]).unwrap();
+        let batches = read(&bytes);
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 4);
+        // ordered by mmsi.
+        let mmsi = batch.column(0).as_primitive::<UInt32Type>();
+        assert_eq!(mmsi.values().to_vec(), vec![123, 123, 456, 456]);
+        let time = batch.column(1).as_primitive::<TimestampSecondType>();
+        assert_eq!(time.value(1), 1640998800);
+        let sog = batch.column(4).as_primitive::<Float64Type>();
+        assert_eq!(sog.value(1), 11.0);
+        // missing values are null.
+        assert_eq!(batch.column(5).null_count(), 4);
+        let status = batch.column(6).as_primitive::<UInt8Type>();
+        assert_eq!(status.value(0), 0);
+        assert!(status.is_null(1));
+        assert_eq!(batch.column(7).as_string::<i32>().value(3), "NLRTM");
+    }
+
+    #[test]
+    fn test_filter_for_sanity() {
+        // time window - the end is exclusive.
+        let filter = Filter::new(
+            Some("2022-01-01T00:30:00Z"),
+            Some("2022-01-01T02:00:00+01:00"),
+            None,
+        )
+        .unwrap();
+        let bytes = write(&get_port(), &filter, Format::Arrow, vec![]).unwrap();
+        assert_eq!(count(&bytes), 0);
+        let filter = Filter::new(Some("2022-01-01T00:30:00Z"), None, None).unwrap();
+        let bytes = write(&get_port(), &filter, Format::Arrow, vec![]).unwrap();
+        assert_eq!(count(&bytes), 2);
+
+        // bounding box.
+        let filter = Filter::new(None, None, Some("3.5,51.05,4.5,52")).unwrap();
+        let bytes = write(&get_port(), &filter, Format::Arrow, vec![]).unwrap();
+        let batch = &read(&bytes)[0];
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.column(3).as_primitive::<Float64Type>().value(0), 4.1);
+
+        // crossing the antimeridian.
+        let filter = Filter::new(None, None, Some("179.8,50,-179.9,52")).unwrap();
+        let bytes = write(&get_port(), &filter, Format::Arrow, vec![]).unwrap();
+        let batch = &read(&bytes)[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.column(0).as_primitive::<UInt32Type>().value(0), 456);
+    }
+}
@@ -0,0 +1,727 @@
+//! Bulk import of historical AIS data from CSV dumps - e.g. as published by MarineCadastre or the
+//! Danish Maritime Authority (DMA).
+
+use std::{collections, error, fmt, io};
+
+/// Format of the timestamps stored with the vessels - sorts lexicographically.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Heading reported by AIS transponders if not available.
+const HEADING_NOT_AVAILABLE: f64 = 511.0;
+
+/// Speed over ground reported by AIS transponders if not available.
+const SPEED_NOT_AVAILABLE: f64 = 102.3;
+
+/// Course over ground reported by AIS transponders if not available.
+const COURSE_NOT_AVAILABLE: f64 = 360.0;
+
+/// Maximum number of rejected rows reported in detail.
+const MAX_REPORTED_REJECTIONS: usize = 100;
+
+/// Well known layouts of AIS CSV dumps.
+#[derive(Clone, Copy, Debug, PartialEq, rocket::FromFormField)]
+pub enum Layout {
+    #[field(value = "marinecadastre")]
+    MarineCadastre,
+    #[field(value = "dma")]
+    Dma,
+}
+
+impl std::str::FromStr for Layout {
+    type Err = ImportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "marinecadastre" => Ok(Layout::MarineCadastre),
+            "dma" => Ok(Layout::Dma),
+            _ => Err(ImportError::UnknownLayout(s.to_string())),
+        }
+    }
+}
+
+/// Names of the CSV columns holding the vessel attributes.
+///
+/// The MMSI, timestamp, latitude & longitude columns are required; all others are optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMapping {
+    pub mmsi: String,
+    pub timestamp: String,
+    pub lat: String,
+    pub lon: String,
+    pub speed: String,
+    pub course: String,
+    pub heading: String,
+    pub name: String,
+    pub ship_type: String,
+    pub status: String,
+    pub destination: String,
+    /// chrono format string of the timestamps.
+    pub timestamp_format: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping::new(Layout::MarineCadastre)
+    }
+}
+
+impl ColumnMapping {
+    /// Creates the column mapping for a well known layout.
+    pub fn new(layout: Layout) -> Self {
+        let columns = match layout {
+            Layout::MarineCadastre => [
+                "MMSI",
+                "BaseDateTime",
+                "LAT",
+                "LON",
+                "SOG",
+                "COG",
+                "Heading",
+                "VesselName",
+                "VesselType",
+                "Status",
+                "Destination",
+                "%Y-%m-%dT%H:%M:%S",
+            ],
+            Layout::Dma => [
+                "MMSI",
+                "# Timestamp",
+                "Latitude",
+                "Longitude",
+                "SOG",
+                "COG",
+                "Heading",
+                "Name",
+                "Ship type",
+                "Navigational status",
+                "Destination",
+                "%d/%m/%Y %H:%M:%S",
+            ],
+        };
+        // fields are initialized in the order of the columns above.
+        let mut columns = columns.into_iter().map(String::from);
+        let mut next = || columns.next().unwrap_or_default();
+        ColumnMapping {
+            mmsi: next(),
+            timestamp: next(),
+            lat: next(),
+            lon: next(),
+            speed: next(),
+            course: next(),
+            heading: next(),
+            name: next(),
+            ship_type: next(),
+            status: next(),
+            destination: next(),
+            timestamp_format: next(),
+        }
+    }
+
+    /// Overrides the column (or timestamp format) of a single attribute.
+    pub fn set(&mut self, field: &str, column: &str) -> Result<(), ImportError> {
+        let target = match field {
+            "mmsi" => &mut self.mmsi,
+            "timestamp" => &mut self.timestamp,
+            "lat" => &mut self.lat,
+            "lon" => &mut self.lon,
+            "speed" => &mut self.speed,
+            "course" => &mut self.course,
+            "heading" => &mut self.heading,
+            "name" => &mut self.name,
+            "ship_type" => &mut self.ship_type,
+            "status" => &mut self.status,
+            "destination" => &mut self.destination,
+            "timestamp_format" => &mut self.timestamp_format,
+            _ => return Err(ImportError::UnknownField(field.to_string())),
+        };
+        *target = column.to_string();
+        Ok(())
+    }
+}
+
+/// Errors preventing an import altogether - invalid rows are rejected individually instead.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The layout is not known.
+    UnknownLayout(String),
+    /// The field of the column mapping is not known.
+    UnknownField(String),
+    /// A required column is missing in the header.
+    MissingColumn(String),
+    /// The CSV header could not be read.
+    Csv(csv::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnknownLayout(layout) => write!(f, "unknown layout: {}", layout),
+            ImportError::UnknownField(field) => write!(f, "unknown field: {}", field),
+            ImportError::MissingColumn(column) => write!(f, "missing column: {}", column),
+            ImportError::Csv(err) => write!(f, "invalid CSV: {}", err),
+        }
+    }
+}
+
+impl error::Error for ImportError {}
+
+impl From<csv::Error> for ImportError {
+    fn from(err: csv::Error) -> Self {
+        ImportError::Csv(err)
+    }
+}
+
+/// A row which could not be imported.
+#[derive(
+    rocket::serde::Serialize, rocket::serde::Deserialize, Clone, Debug, PartialEq, utoipa::ToSchema,
+)]
+#[serde(crate = "rocket::serde")]
+pub struct RejectedRow {
+    pub line: u64,
+    pub reason: String,
+}
+
+/// Outcome of an import.
+#[derive(
+    rocket::serde::Serialize,
+    rocket::serde::Deserialize,
+    Default,
+    Debug,
+    PartialEq,
+    utoipa::ToSchema,
+)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportReport {
+    /// MMSIs of the vessels imported.
+    #[schema(value_type = Vec<u32>)]
+    pub vessels: Vec<common::mmsi::Mmsi>,
+    /// Number of rows imported.
+    pub accepted: usize,
+    /// Number of rows rejected.
+    pub rejected_count: usize,
+    /// Details about the first rejected rows.
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl ImportReport {
+    fn reject(&mut self, line: u64, reason: String) {
+        self.rejected_count += 1;
+        if self.rejected.len() < MAX_REPORTED_REJECTIONS {
+            self.rejected.push(RejectedRow { line, reason });
+        }
+    }
+}
+
+/// A single position report of a vessel.
+struct Point {
+    timestamp: String,
+    coordinate: common::Coordinate,
+    speed: f64,
+    heading: f64,
+    status: common::ais::NavStatus,
+    destination: String,
+}
+
+/// Index of the mapped columns within the CSV header.
+struct Columns {
+    mmsi: usize,
+    timestamp: usize,
+    lat: usize,
+    lon: usize,
+    speed: Option<usize>,
+    course: Option<usize>,
+    heading: Option<usize>,
+    name: Option<usize>,
+    ship_type: Option<usize>,
+    status: Option<usize>,
+    destination: Option<usize>,
+}
+
+impl Columns {
+    fn new(header: &csv::StringRecord, mapping: &ColumnMapping) -> Result<Self, ImportError> {
+        let find = |column: &str| header.iter().position(|item| item == column);
+        let require =
+            |column: &str| find(column).ok_or_else(|| ImportError::MissingColumn(column.into()));
+        Ok(Columns {
+            mmsi: require(&mapping.mmsi)?,
+            timestamp: require(&mapping.timestamp)?,
+            lat: require(&mapping.lat)?,
+            lon: require(&mapping.lon)?,
+            speed: find(&mapping.speed),
+            course: find(&mapping.course),
+            heading: find(&mapping.heading),
+            name: find(&mapping.name),
+            ship_type: find(&mapping.ship_type),
+            status: find(&mapping.status),
+            destination: find(&mapping.destination),
+        })
+    }
+}
+
+/// Returns the value of an optional column - empty if not available.
+fn get(record: &csv::StringRecord, index: Option<usize>) -> &str {
+    index.and_then(|i| record.get(i)).unwrap_or_default()
+}
+
+/// Parses an optional number - not available values are returned as None.
+fn parse_number(value: &str, name: &str) -> Result<Option<f64>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(Some(number)),
+        _ => Err(format!("invalid {}: {}", name, value)),
+    }
+}
+
+/// Parses a single row into the MMSI & position report of a vessel.
+fn parse_row(
+    record: &csv::StringRecord,
+    columns: &Columns,
+    mapping: &ColumnMapping,
+) -> Result<(common::mmsi::Mmsi, Point), String> {
+    let mmsi = get(record, Some(columns.mmsi))
+        .parse::<common::mmsi::Mmsi>()
+        .map_err(|err| err.to_string())?;
+    let timestamp = get(record, Some(columns.timestamp));
+    let timestamp = chrono::NaiveDateTime::parse_from_str(timestamp, &mapping.timestamp_format)
+        .map_err(|_| format!("invalid timestamp: {}", timestamp))?;
+    let lat = parse_number(get(record, Some(columns.lat)), "latitude")?;
+    let lon = parse_number(get(record, Some(columns.lon)), "longitude")?;
+    let coordinate = match (lat, lon) {
+        (Some(lat), Some(lon)) => {
+            common::Coordinate::new(lat, lon).map_err(|err| err.to_string())?
+        }
+        _ => return Err("missing position".to_string()),
+    };
+
+    // fall back to the course over ground if no heading is available.
+    let heading = parse_number(get(record, columns.heading), "heading")?
+        .filter(|value| *value != HEADING_NOT_AVAILABLE);
+    let heading = match heading {
+        Some(value) => value,
+        None => parse_number(get(record, columns.course), "course")?
+            .filter(|value| *value != COURSE_NOT_AVAILABLE)
+            .unwrap_or(HEADING_NOT_AVAILABLE),
+    };
+    Ok((
+        mmsi,
+        Point {
+            timestamp: timestamp.format(TIMESTAMP_FORMAT).to_string(),
+            coordinate,
+            speed: parse_number(get(record, columns.speed), "speed")?
+                .unwrap_or(SPEED_NOT_AVAILABLE),
+            heading,
+            // unknown statuses are not defined - instead of rejecting the report.
+            status: get(record, columns.status).parse().unwrap_or_default(),
+            destination: get(record, columns.destination).to_string(),
+        },
+    ))
+}
+
+/// Splits a vessel into its position reports - missing speeds & headings are filled with the AIS
+/// not-available values, other attributes with defaults.
+fn to_points(vessel: common::Vessel) -> Vec<Point> {
+    let common::Vessel {
+        coordinates,
+        timestamps,
+        speeds,
+        headings,
+        statuses,
+        destinations,
+        ..
+    } = vessel;
+    let mut timestamps = timestamps.into_iter();
+    let mut speeds = speeds.into_iter();
+    let mut headings = headings.into_iter();
+    let mut statuses = statuses.into_iter();
+    let mut destinations = destinations.into_iter();
+    coordinates
+        .into_iter()
+        .map(|coordinate| Point {
+            timestamp: timestamps.next().unwrap_or_default(),
+            coordinate,
+            speed: speeds.next().unwrap_or(SPEED_NOT_AVAILABLE),
+            heading: headings.next().unwrap_or(HEADING_NOT_AVAILABLE),
+            status: statuses.next().unwrap_or_default(),
+            destination: destinations.next().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Sorts the position reports by time - of reports with the same timestamp the last one is kept.
+fn normalize(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    points.reverse();
+    points.dedup_by(|a, b| !a.timestamp.is_empty() && a.timestamp == b.timestamp);
+    points.reverse();
+    points
+}
+
+/// Sets the track of a vessel.
+fn set_points(vessel: &mut common::Vessel, points: Vec<Point>) {
+    vessel.coordinates = points.iter().map(|point| point.coordinate).collect();
+    vessel.speeds = points.iter().map(|point| point.speed).collect();
+    vessel.headings = points.iter().map(|point| point.heading).collect();
+    vessel.timestamps = vec![];
+    vessel.statuses = vec![];
+    vessel.destinations = vec![];
+    for point in points {
+        vessel.timestamps.push(point.timestamp);
+        vessel.statuses.push(point.status);
+        vessel.destinations.push(point.destination);
+    }
+}
+
+/// Reads the vessels from an AIS CSV dump - rows are grouped by MMSI & sorted by time.
+pub fn read_vessels<R: io::Read>(
+    reader: R,
+    mapping: &ColumnMapping,
+) -> Result<(Vec<common::Vessel>, ImportReport), ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let columns = Columns::new(reader.headers()?, mapping)?;
+
+    let mut report = ImportReport::default();
+    let mut vessels: collections::BTreeMap<common::mmsi::Mmsi, (common::Vessel, Vec<Point>)> =
+        collections::BTreeMap::new();
+    // line of the report kept per vessel & timestamp.
+    let mut lines: collections::HashMap<(common::mmsi::Mmsi, String), u64> =
+        collections::HashMap::new();
+    let mut record = csv::StringRecord::new();
+    loop {
+        let line = reader.position().line();
+        match reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {}
+            Err(err) => {
+                report.reject(line, err.to_string());
+                // the reader cannot recover from I/O errors.
+                if matches!(err.kind(), csv::ErrorKind::Io(_)) {
+                    break;
+                }
+                continue;
+            }
+        }
+        let (mmsi, point) = match parse_row(&record, &columns, mapping) {
+            Ok(res) => res,
+            Err(reason) => {
+                report.reject(line, reason);
+                continue;
+            }
+        };
+        let (vessel, points) = vessels
+            .entry(mmsi)
+            .or_insert_with(|| (common::Vessel::new(mmsi), vec![]));
+        let (name, ship_type) = (get(&record, columns.name), get(&record, columns.ship_type));
+        if !name.is_empty() {
+            vessel.name = name.to_string();
+        }
+        if let Ok(ship_type) = ship_type.parse() {
+            if ship_type != common::ais::ShipType::NotAvailable {
+                vessel.ship_type = ship_type;
+            }
+        }
+        // of reports with the same timestamp the last one is kept.
+        match lines.insert((mmsi, point.timestamp.clone()), line) {
+            Some(previous) => report.reject(
+                previous,
+                format!(
+                    "duplicate timestamp: {} - see line {}",
+                    point.timestamp, line
+                ),
+            ),
+            None => report.accepted += 1,
+        }
+        points.push(point);
+    }
+
+    report.vessels = vessels.keys().copied().collect();
+    let vessels = vessels
+        .into_values()
+        .map(|(mut vessel, points)| {
+            set_points(&mut vessel, normalize(points));
+            vessel
+        })
+        .collect();
+    Ok((vessels, report))
+}
+
+/// Merges the vessels into the port - tracks of existing vessels are extended.
+pub fn merge(port: &mut common::Port, vessels: Vec<common::Vessel>) {
+    for vessel in vessels {
+        let Some(existing) = port.vessels.remove(&vessel.mmsi) else {
+            port.vessels.insert(vessel.mmsi, vessel);
+            continue;
+        };
+        let mut track = common::Vessel {
+            name: if vessel.name.is_empty() {
+                existing.name.clone()
+            } else {
+                vessel.name.clone()
+            },
+            ship_type: if vessel.ship_type == common::ais::ShipType::NotAvailable {
+                existing.ship_type
+            } else {
+                vessel.ship_type
+            },
+            ..common::Vessel::new(vessel.mmsi)
+        };
+        let mut points = to_points(existing);
+        points.extend(to_points(vessel));
+        set_points(&mut track, normalize(points));
+        port.vessels.insert(track.mmsi, track);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARINE_CADASTRE: &str = "\
+MMSI,BaseDateTime,LAT,LON,SOG,COG,Heading,VesselName,IMO,CallSign,VesselType,Status
+367000001,2022-01-01T00:02:00,51.95,4.2,11.5,92.0,511,BOATY,,,70,0
+367000001,2022-01-01T00:00:00,51.9,4.1,10.0,90.0,91.0,BOATY,,,70,0
+367000002,2022-01-01T00:00:00,52.0,4.0,0.0,360.0,511,,,,,5
+367000002,2022-01-01 00:01:00,52.0,4.0,0.0,360.0,511,,,,,5
+367000003,2022-01-01T00:00:00,91.0,181.0,0.0,360.0,511,,,,,5
+foo,2022-01-01T00:00:00,52.0,4.0,0.0,360.0,511,,,,,5
+367000001,2022-01-01T00:01:00,51.92,4.15,abc,90.0,511,BOATY,,,70,0
+";
+
+    const DMA: &str = "\
+# Timestamp,Type of mobile,MMSI,Latitude,Longitude,Navigational status,ROT,SOG,COG,Heading,Name,Ship type,Destination
+01/01/2022 00:00:00,Class A,219000001,55.7,12.6,Moored,,0.0,,,SEA PONY,Cargo,DKCPH
+";
+
+    fn get_mmsi(value: u32) -> common::mmsi::Mmsi {
+        common::mmsi::Mmsi::new(value).unwrap()
+    }
+
+    fn get_vessel(mmsi: u32, points: &[(&str, f64)]) -> common::Vessel {
+        common::Vessel {
+            mmsi: get_mmsi(mmsi),
+            name: "foo".to_string(),
+            ship_type: common::ais::ShipType::Sailing,
+            coordinates: points
+                .iter()
+                .map(|(_, lat)| common::Coordinate {
+                    lat: *lat,
+                    lon: 4.0,
+                })
+                .collect(),
+            timestamps: points.iter().map(|(time, _)| time.to_string()).collect(),
+            speeds: vec![1.0; points.len()],
+            headings: vec![2.0; points.len()],
+            statuses: vec![common::ais::NavStatus::Moored; points.len()],
+            destinations: vec!["NLRTM".to_string(); points.len()],
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_read_vessels_for_success() {
+        read_vessels(MARINE_CADASTRE.as_bytes(), &ColumnMapping::default())
+            .expect("a valid import.");
+        read_vessels(DMA.as_bytes(), &ColumnMapping::new(Layout::Dma)).expect("a valid import.");
+    }
+
+    #[test]
+    fn test_merge_for_success() {
+        let mut port = common::Port::default();
+        merge(
+            &mut port,
+            vec![get_vessel(1, &[("2022-01-01T00:00:00Z", 51.0)])],
+        );
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_column_mapping_for_failure() {
+        let mut mapping = ColumnMapping::default();
+        assert!(matches!(
+            mapping.set("foo", "bar"),
+            Err(ImportError::UnknownField(_))
+        ));
+        assert!(matches!(
+            "foo".parse::<Layout>(),
+            Err(ImportError::UnknownLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_vessels_for_failure() {
+        // wrong layout.
+        let res = read_vessels(DMA.as_bytes(), &ColumnMapping::default());
+        assert!(matches!(res, Err(ImportError::MissingColumn(column)) if column == "BaseDateTime"));
+
+        // rejected rows.
+        let (vessels, report) =
+            read_vessels(MARINE_CADASTRE.as_bytes(), &ColumnMapping::default()).unwrap();
+        assert_eq!(vessels.len(), 2);
+        assert_eq!(report.accepted, 3);
+        assert_eq!(report.rejected_count, 4);
+        assert_eq!(
+            report.rejected,
+            vec![
+                RejectedRow {
+                    line: 5,
+                    reason: "invalid timestamp: 2022-01-01 00:01:00".to_string()
+                },
+                RejectedRow {
+                    line: 6,
+                    reason: "invalid latitude: 91".to_string()
+                },
+                RejectedRow {
+                    line: 7,
+                    reason: "invalid mmsi: foo".to_string()
+                },
+                RejectedRow {
+                    line: 8,
+                    reason: "invalid speed: abc".to_string()
+                },
+            ]
+        );
+
+        // only the first rejections are reported in detail.
+        let mut csv = String::from("MMSI,BaseDateTime,LAT,LON\n");
+        csv.push_str(&"foo,,,\n".repeat(MAX_REPORTED_REJECTIONS + 1));
+        let (_, report) = read_vessels(csv.as_bytes(), &ColumnMapping::default()).unwrap();
+        assert_eq!(report.rejected_count, MAX_REPORTED_REJECTIONS + 1);
+        assert_eq!(report.rejected.len(), MAX_REPORTED_REJECTIONS);
+
+        // duplicate reports - the last one is kept.
+        let csv = "MMSI,BaseDateTime,LAT,LON\n\
+            367000001,2022-01-01T00:00:00,51.9,4.1\n\
+            367000001,2022-01-01T00:00:00,51.95,4.2\n\
+            367000002,2022-01-01T00:00:00,51.9,4.1\n";
+        let (vessels, report) = read_vessels(csv.as_bytes(), &ColumnMapping::default()).unwrap();
+        assert_eq!(report.accepted, 2);
+        assert_eq!(report.rejected_count, 1);
+        assert_eq!(
+            report.rejected,
+            vec![RejectedRow {
+                line: 2,
+                reason: "duplicate timestamp: 2022-01-01T00:00:00Z - see line 3".to_string()
+            }]
+        );
+        assert_eq!(vessels[0].coordinates.len(), 1);
+        assert_eq!(vessels[0].coordinates[0].lat, 51.95);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_column_mapping_for_sanity() {
+        let mut mapping = ColumnMapping::new("dma".parse().unwrap());
+        assert_eq!(mapping.lat, "Latitude");
+        mapping.set("lat", "lat").unwrap();
+        mapping.set("timestamp_format", "%s").unwrap();
+        assert_eq!(mapping.lat, "lat");
+        assert_eq!(mapping.timestamp_format, "%s");
+    }
+
+    #[test]
+    fn test_read_vessels_for_sanity() {
+        let (vessels, report) =
+            read_vessels(MARINE_CADASTRE.as_bytes(), &ColumnMapping::default()).unwrap();
+        assert_eq!(
+            report.vessels,
+            vec![get_mmsi(367000001), get_mmsi(367000002)]
+        );
+
+        // grouped by mmsi & sorted by time.
+        let vessel = &vessels[0];
+        assert_eq!(vessel.mmsi.value(), 367000001);
+        assert_eq!(vessel.name, "BOATY");
+        assert_eq!(
+            vessel.ship_type,
+            common::ais::ShipType::Cargo(common::ais::Hazard::Unspecified)
+        );
+        assert_eq!(
+            vessel.timestamps,
+            vec!["2022-01-01T00:00:00Z", "2022-01-01T00:02:00Z"]
+        );
+        assert_eq!(
+            vessel.coordinates,
+            vec![
+                common::Coordinate {
+                    lat: 51.9,
+                    lon: 4.1
+                },
+                common::Coordinate {
+                    lat: 51.95,
+                    lon: 4.2
+                }
+            ]
+        );
+        assert_eq!(vessel.speeds, vec![10.0, 11.5]);
+        // heading not available --> course over ground.
+        assert_eq!(vessel.headings, vec![91.0, 92.0]);
+        assert_eq!(
+            vessel.statuses,
+            vec![common::ais::NavStatus::UnderWayUsingEngine; 2]
+        );
+        assert_eq!(vessel.destinations, vec!["", ""]);
+        assert_eq!(vessels[1].coordinates.len(), 1);
+        // neither heading nor course over ground available.
+        assert_eq!(vessels[1].headings, vec![511.0]);
+
+        let (vessels, report) =
+            read_vessels(DMA.as_bytes(), &ColumnMapping::new(Layout::Dma)).unwrap();
+        assert_eq!(report.rejected_count, 0);
+        assert_eq!(vessels[0].mmsi.value(), 219000001);
+        assert_eq!(vessels[0].name, "SEA PONY");
+        assert_eq!(vessels[0].timestamps, vec!["2022-01-01T00:00:00Z"]);
+        assert_eq!(vessels[0].statuses, vec![common::ais::NavStatus::Moored]);
+        assert_eq!(vessels[0].destinations, vec!["DKCPH"]);
+        assert_eq!(vessels[0].speeds, vec![0.0]);
+        assert_eq!(vessels[0].headings, vec![511.0]);
+    }
+
+    #[test]
+    fn test_merge_for_sanity() {
+        let mut port = common::Port::default();
+        merge(
+            &mut port,
+            vec![get_vessel(
+                1,
+                &[
+                    ("2022-01-01T00:00:00Z", 51.0),
+                    ("2022-01-01T00:02:00Z", 51.2),
+                ],
+            )],
+        );
+
+        // interleaved with the existing track; newer reports replace existing ones.
+        let mut vessel = get_vessel(
+            1,
+            &[
+                ("2022-01-01T00:01:00Z", 51.1),
+                ("2022-01-01T00:02:00Z", 51.3),
+            ],
+        );
+        vessel.name = String::new();
+        vessel.speeds = vec![];
+        merge(&mut port, vec![vessel, get_vessel(2, &[])]);
+        assert_eq!(port.vessels.len(), 2);
+        let vessel = &port.vessels[&get_mmsi(1)];
+        assert_eq!(vessel.name, "foo");
+        assert_eq!(
+            vessel.timestamps,
+            vec![
+                "2022-01-01T00:00:00Z",
+                "2022-01-01T00:01:00Z",
+                "2022-01-01T00:02:00Z"
+            ]
+        );
+        let lats: Vec<f64> = vessel.coordinates.iter().map(|item| item.lat).collect();
+        assert_eq!(lats, vec![51.0, 51.1, 51.3]);
+        assert_eq!(vessel.speeds, vec![1.0, 102.3, 102.3]);
+        assert_eq!(vessel.statuses.len(), 3);
+    }
+}
@@ -1,62 +1,797 @@
 #[macro_use]
 extern crate rocket;
 
-use std::{fs, io};
+mod columnar;
+mod import;
 
+use std::{collections, env, error, fs, io, process, sync};
+
+use common::wire;
+use rocket::data::ToByteUnit;
+use rocket::response::stream;
 use rocket::serde::json;
+use rocket::tokio::sync::mpsc;
+use rocket::{fairing, http, request};
+use utoipa::OpenApi;
+
+/// File from which the port data is loaded.
+const DATA_FILE: &str = "data.json";
+
+/// Media type of GeoJSON documents.
+const GEOJSON_MEDIA_TYPE: (&str, &str) = ("application", "geo+json");
+
+/// Default size limit of CSV uploads - configurable using the `csv` limit.
+const CSV_LIMIT_MIB: usize = 64;
+
+/// Size of the chunks columnar exports are streamed in.
+const EXPORT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Number of chunks buffered before the export waits for the client.
+const EXPORT_CHUNKS: usize = 4;
+
+/// OpenAPI specification of this service.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        index,
+        vessels,
+        vessels_status,
+        import_vessels,
+        import_csv,
+        export_gpx,
+        export_kml,
+        export_arrow,
+        export_parquet,
+        openapi
+    ),
+    components(schemas(
+        common::MMSIList,
+        common::VesselList,
+        common::Vessel,
+        common::Coordinate,
+        common::polyline::EncodedPath,
+        common::polyline::EncodedVessel,
+        common::polyline::EncodedVesselList,
+        import::ImportReport,
+        import::RejectedRow
+    ))
+)]
+struct ApiDoc;
+
+/// Port data shared between the routes and the background loading of the data.
+#[derive(Clone, Default)]
+struct PortData(sync::Arc<sync::RwLock<common::Port>>);
+
+impl From<common::Port> for PortData {
+    fn from(port: common::Port) -> Self {
+        PortData(sync::Arc::new(sync::RwLock::new(port)))
+    }
+}
+
+impl PortData {
+    /// Read access to the port data.
+    fn read(&self) -> sync::RwLockReadGuard<'_, common::Port> {
+        self.0.read().expect("Port data lock poisoned.")
+    }
+
+    /// Replaces the current port data.
+    fn replace(&self, port: common::Port) {
+        *self.0.write().expect("Port data lock poisoned.") = port;
+    }
+
+    /// Write access to the port data.
+    fn write(&self) -> sync::RwLockWriteGuard<'_, common::Port> {
+        self.0.write().expect("Port data lock poisoned.")
+    }
+
+    /// Inserts the given vessels - replacing existing vessels with the same MMSI.
+    fn insert(&self, vessels: Vec<common::Vessel>) {
+        let mut port = self.write();
+        for vessel in vessels {
+            port.vessels.insert(vessel.mmsi, vessel);
+        }
+    }
+}
+
+/// Gauges describing the port data.
+#[derive(Clone)]
+struct PortMetrics {
+    vessels: prometheus::IntGauge,
+    points: prometheus::IntGauge,
+}
+
+impl PortMetrics {
+    /// Updates the gauges based on the given port data.
+    fn update(&self, data: &common::Port) {
+        self.vessels.set(data.vessels.len() as i64);
+        self.points.set(
+            data.vessels
+                .values()
+                .map(|vessel| vessel.coordinates.len() as i64)
+                .sum(),
+        );
+    }
+}
+
+/// Request guard succeeding if the client prefers GeoJSON responses - forwards otherwise.
+struct GeoJsonAccepted;
+
+#[rocket::async_trait]
+impl<'r> request::FromRequest<'r> for GeoJsonAccepted {
+    type Error = ();
+
+    async fn from_request(req: &'r rocket::Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let (top, sub) = GEOJSON_MEDIA_TYPE;
+        match req.accept() {
+            Some(accept) if accept.preferred().media_type() == &http::MediaType::new(top, sub) => {
+                request::Outcome::Success(GeoJsonAccepted)
+            }
+            _ => request::Outcome::Forward(http::Status::NotAcceptable),
+        }
+    }
+}
+
+/// GeoJSON response.
+type GeoJson = (http::ContentType, json::Json<geojson::FeatureCollection>);
+
+/// Wraps a feature collection in a GeoJSON response.
+fn to_geojson(collection: geojson::FeatureCollection) -> GeoJson {
+    let (top, sub) = GEOJSON_MEDIA_TYPE;
+    (http::ContentType::new(top, sub), json::Json(collection))
+}
 
-/// Retrieve a list of vessels.
-#[get("/vessels")]
-fn vessels(data: &rocket::State<common::Port>) -> json::Json<common::MMSIList> {
-    let mmsi = data.vessels.keys().copied().collect();
-    json::Json(common::MMSIList { vessels: mmsi })
+/// Retrieve a list of vessels - optionally only those with position reports within a bounding box.
+#[utoipa::path(
+    get,
+    path = "/vessels",
+    params(
+        ("bbox" = Option<String>, Query, description = "Bounding box: min_lon,min_lat,max_lon,max_lat.")
+    ),
+    responses(
+        (status = 200, content(
+            (common::MMSIList = "application/json"),
+            (common::MMSIList = "application/msgpack"),
+            (Object = "application/geo+json")
+        )),
+        (status = 422, body = String)
+    )
+)]
+#[get("/vessels?<bbox>", rank = 2)]
+fn vessels(
+    bbox: Option<&str>,
+    data: &rocket::State<PortData>,
+) -> Result<wire::Negotiated<common::MMSIList>, (http::Status, String)> {
+    let bbox = parse_bbox(bbox)?;
+    let mmsi = data
+        .read()
+        .vessels
+        .values()
+        .filter(|vessel| within(vessel, bbox))
+        .map(|vessel| vessel.mmsi)
+        .collect();
+    Ok(wire::Negotiated(common::MMSIList { vessels: mmsi }))
+}
+
+/// Retrieve all vessels as GeoJSON features.
+#[get("/vessels?<bbox>", rank = 1)]
+fn vessels_geojson(
+    _geojson: GeoJsonAccepted,
+    bbox: Option<&str>,
+    data: &rocket::State<PortData>,
+) -> Result<GeoJson, (http::Status, String)> {
+    let bbox = parse_bbox(bbox)?;
+    let mut vessels: Vec<common::Vessel> = data
+        .read()
+        .vessels
+        .values()
+        .filter(|vessel| within(vessel, bbox))
+        .cloned()
+        .collect();
+    vessels.sort_by_key(|vessel| vessel.mmsi);
+    Ok(to_geojson(common::geojson::vessels_to_feature_collection(
+        &vessels,
+    )))
+}
+
+/// Parses the optional `bbox` query parameter.
+fn parse_bbox(bbox: Option<&str>) -> Result<Option<columnar::BBox>, (http::Status, String)> {
+    bbox.map(str::parse::<columnar::BBox>)
+        .transpose()
+        .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))
+}
+
+/// Whether any position of the vessel lies within the bounding box, if any.
+fn within(vessel: &common::Vessel, bbox: Option<columnar::BBox>) -> bool {
+    bbox.is_none_or(|bbox| vessel.coordinates.iter().any(|item| bbox.contains(item)))
+}
+
+/// Returns the vessels with the given MMSIs - unknown MMSIs are skipped.
+fn get_vessels(data: &PortData, mmsis: &common::MMSIList) -> Vec<common::Vessel> {
+    let data = data.read();
+    mmsis
+        .vessels
+        .iter()
+        .filter_map(|item| data.vessels.get(item).cloned())
+        .collect()
 }
 
 /// Retrieve details about a particular set vessels.
-#[post("/vessels", format = "application/json", data = "<mmsis>")]
+#[utoipa::path(
+    post,
+    path = "/vessels",
+    request_body(content(
+        (common::MMSIList = "application/json"),
+        (common::MMSIList = "application/msgpack")
+    )),
+    params(
+        ("polyline" = Option<u32>, Query,
+            description = "Return the tracks as `EncodedVesselList` - polylines with a precision of 5 or 6.")
+    ),
+    responses(
+        (status = 200, content(
+            (Option<common::VesselList> = "application/json"),
+            (Option<common::VesselList> = "application/msgpack"),
+            (Object = "application/geo+json")
+        )),
+        (status = 422, body = String, description = "Unsupported polyline precision.")
+    )
+)]
+#[post("/vessels", data = "<mmsis>", rank = 3)]
 fn vessels_status(
-    mmsis: json::Json<common::MMSIList>,
-    data: &rocket::State<common::Port>,
-) -> json::Json<Option<common::VesselList>> {
-    let mut status = vec![];
-    for item in &mmsis.vessels {
-        if data.vessels.contains_key(item) {
-            status.push(data.vessels.get(item).cloned().unwrap());
+    mmsis: wire::Negotiated<common::MMSIList>,
+    data: &rocket::State<PortData>,
+) -> wire::Negotiated<Option<common::VesselList>> {
+    let status = get_vessels(data, &mmsis);
+    if !status.is_empty() {
+        wire::Negotiated(Option::from(common::VesselList { vessels: status }))
+    } else {
+        wire::Negotiated(None)
+    }
+}
+
+/// Retrieve a particular set of vessels with their tracks as encoded polylines.
+#[post("/vessels?<polyline>", data = "<mmsis>", rank = 2)]
+fn vessels_status_polyline(
+    mmsis: wire::Negotiated<common::MMSIList>,
+    polyline: u32,
+    data: &rocket::State<PortData>,
+) -> Result<wire::Negotiated<Option<common::polyline::EncodedVesselList>>, (http::Status, String)> {
+    let status = get_vessels(data, &mmsis);
+    if status.is_empty() {
+        return Ok(wire::Negotiated(None));
+    }
+    let vessels = status
+        .iter()
+        .map(|vessel| common::polyline::EncodedVessel::new(vessel, polyline))
+        .collect::<Result<_, _>>()
+        .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))?;
+    Ok(wire::Negotiated(Some(
+        common::polyline::EncodedVesselList { vessels },
+    )))
+}
+
+/// Retrieve a particular set of vessels as GeoJSON features.
+#[post("/vessels", data = "<mmsis>", rank = 1)]
+fn vessels_status_geojson(
+    _geojson: GeoJsonAccepted,
+    mmsis: wire::Negotiated<common::MMSIList>,
+    data: &rocket::State<PortData>,
+) -> GeoJson {
+    let status = get_vessels(data, &mmsis);
+    to_geojson(common::geojson::vessels_to_feature_collection(&status))
+}
+
+/// Imports are rejected until the port data is loaded - they would be overwritten otherwise.
+fn check_ready(health: &common::health::Health) -> Result<(), (http::Status, String)> {
+    if health.is_ready() {
+        Ok(())
+    } else {
+        Err((
+            http::Status::ServiceUnavailable,
+            "Port data not loaded yet.".to_string(),
+        ))
+    }
+}
+
+/// Import vessel tracks from a GeoJSON feature (collection) - replaces vessels with the same MMSI.
+#[utoipa::path(
+    post,
+    path = "/vessels/import",
+    request_body(content((Object = "application/geo+json"))),
+    responses(
+        (status = 200, body = common::MMSIList),
+        (status = 422, body = String),
+        (status = 503, body = String)
+    )
+)]
+#[post(
+    "/vessels/import",
+    format = "application/geo+json",
+    data = "<features>"
+)]
+fn import_vessels(
+    features: json::Json<geojson::GeoJson>,
+    data: &rocket::State<PortData>,
+    health: &rocket::State<common::health::Health>,
+    metrics: &rocket::State<PortMetrics>,
+) -> Result<json::Json<common::MMSIList>, (http::Status, String)> {
+    check_ready(health)?;
+    let vessels = match features.into_inner() {
+        geojson::GeoJson::Feature(feature) => {
+            common::geojson::vessel_from_feature(&feature).map(|vessel| vec![vessel])
+        }
+        geojson::GeoJson::FeatureCollection(collection) => {
+            common::geojson::vessels_from_feature_collection(&collection)
+        }
+        geojson::GeoJson::Geometry(_) => {
+            return Err((
+                http::Status::UnprocessableEntity,
+                "Expected a Feature or FeatureCollection.".to_string(),
+            ))
         }
     }
-    if !status.is_empty() {
-        json::Json(Option::from(common::VesselList { vessels: status }))
+    .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))?;
+
+    let mmsis = vessels.iter().map(|vessel| vessel.mmsi).collect();
+    data.insert(vessels);
+    metrics.update(&data.read());
+    Ok(json::Json(common::MMSIList { vessels: mmsis }))
+}
+
+/// Bulk import of AIS position reports from a CSV dump - extends the tracks of existing vessels.
+#[utoipa::path(
+    post,
+    path = "/vessels/import/csv",
+    params(
+        ("layout" = Option<String>, Query, description = "Layout of the dump: marinecadastre (default) or dma."),
+        ("columns" = Option<Object>, Query, style = DeepObject, description = "Column overrides - e.g. columns[lat]=Latitude.")
+    ),
+    request_body(content(("text/csv"))),
+    responses(
+        (status = 200, body = import::ImportReport),
+        (status = 413, body = String),
+        (status = 422, body = String),
+        (status = 503, body = String)
+    )
+)]
+#[post(
+    "/vessels/import/csv?<layout>&<columns>",
+    format = "text/csv",
+    data = "<upload>"
+)]
+async fn import_csv(
+    layout: Option<import::Layout>,
+    columns: collections::HashMap<String, String>,
+    upload: rocket::Data<'_>,
+    limits: &rocket::data::Limits,
+    data: &rocket::State<PortData>,
+    health: &rocket::State<common::health::Health>,
+    metrics: &rocket::State<PortMetrics>,
+) -> Result<json::Json<import::ImportReport>, (http::Status, String)> {
+    check_ready(health)?;
+    let mut mapping = import::ColumnMapping::new(layout.unwrap_or(import::Layout::MarineCadastre));
+    for (field, column) in &columns {
+        mapping
+            .set(field, column)
+            .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))?;
+    }
+
+    let limit = limits.get("csv").unwrap_or(CSV_LIMIT_MIB.mebibytes());
+    let body = upload
+        .open(limit)
+        .into_bytes()
+        .await
+        .map_err(|err| (http::Status::InternalServerError, err.to_string()))?;
+    if !body.is_complete() {
+        return Err((
+            http::Status::PayloadTooLarge,
+            format!("Upload exceeds the limit of {}.", limit),
+        ));
+    }
+    let span = tracing::Span::current();
+    let (vessels, report) = rocket::tokio::task::spawn_blocking(move || {
+        span.in_scope(|| import::read_vessels(body.as_slice(), &mapping))
+    })
+    .await
+    .map_err(|err| (http::Status::InternalServerError, err.to_string()))?
+    .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))?;
+
+    let mut port = data.write();
+    import::merge(&mut port, vessels);
+    metrics.update(&port);
+    Ok(json::Json(report))
+}
+
+/// Exported document - offered as download to the client.
+#[derive(Responder)]
+struct Export<R> {
+    body: R,
+    content_type: http::ContentType,
+    disposition: http::Header<'static>,
+}
+
+impl<R> Export<R> {
+    fn new(body: R, (top, sub): (&str, &str), extension: &str) -> Self {
+        Export {
+            body,
+            content_type: http::ContentType::new(top.to_string(), sub.to_string()),
+            disposition: http::Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"vessels.{}\"", extension),
+            ),
+        }
+    }
+}
+
+/// Exports the vessels with the given MMSIs - or all vessels if none are given.
+fn export(
+    data: &PortData,
+    mmsi: Vec<common::mmsi::Mmsi>,
+    media_type: (&str, &str),
+    extension: &str,
+    to_document: fn(&[common::Vessel]) -> String,
+) -> Option<Export<String>> {
+    let vessels = if mmsi.is_empty() {
+        let mut vessels: Vec<common::Vessel> = data.read().vessels.values().cloned().collect();
+        vessels.sort_by_key(|vessel| vessel.mmsi);
+        vessels
     } else {
-        json::Json(None)
+        let vessels = get_vessels(data, &common::MMSIList { vessels: mmsi });
+        if vessels.is_empty() {
+            return None;
+        }
+        vessels
+    };
+    Some(Export::new(to_document(&vessels), media_type, extension))
+}
+
+/// Export vessel tracks as GPX 1.1 - speeds (m/s) & courses are added as Garmin extensions.
+#[utoipa::path(
+    get,
+    path = "/vessels/export.gpx",
+    params(("mmsi" = Option<Vec<u32>>, Query, description = "Vessels to export - all if omitted.")),
+    responses(
+        (status = 200, content((String = "application/gpx+xml"))),
+        (status = 404, description = "None of the vessels are known.")
+    )
+)]
+#[get("/vessels/export.gpx?<mmsi>")]
+fn export_gpx(
+    mmsi: Vec<common::mmsi::Mmsi>,
+    data: &rocket::State<PortData>,
+) -> Option<Export<String>> {
+    export(
+        data,
+        mmsi,
+        common::gpx::GPX_MEDIA_TYPE,
+        "gpx",
+        common::gpx::vessels_to_gpx,
+    )
+}
+
+/// Export vessel tracks as KML - styled line strings with time spans for Google Earth.
+#[utoipa::path(
+    get,
+    path = "/vessels/export.kml",
+    params(("mmsi" = Option<Vec<u32>>, Query, description = "Vessels to export - all if omitted.")),
+    responses(
+        (status = 200, content((String = "application/vnd.google-earth.kml+xml"))),
+        (status = 404, description = "None of the vessels are known.")
+    )
+)]
+#[get("/vessels/export.kml?<mmsi>")]
+fn export_kml(
+    mmsi: Vec<common::mmsi::Mmsi>,
+    data: &rocket::State<PortData>,
+) -> Option<Export<String>> {
+    export(
+        data,
+        mmsi,
+        common::kml::KML_MEDIA_TYPE,
+        "kml",
+        common::kml::vessels_to_kml,
+    )
+}
+
+/// Writer passing the written bytes in chunks to a channel - used to stream exports.
+struct ChannelWriter {
+    sender: mpsc::Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl ChannelWriter {
+    fn new(sender: mpsc::Sender<Vec<u8>>) -> Self {
+        ChannelWriter {
+            sender,
+            buffer: Vec::with_capacity(EXPORT_CHUNK_SIZE),
+        }
     }
 }
 
+impl io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= EXPORT_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Blocks until the client caught up - fails once the client is gone.
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(EXPORT_CHUNK_SIZE));
+        self.sender
+            .blocking_send(chunk)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+/// Streams the selected position reports in a columnar format - written while streamed.
+fn export_columnar(
+    data: &PortData,
+    filter: (Option<&str>, Option<&str>, Option<&str>),
+    format: columnar::Format,
+) -> Result<Export<stream::ByteStream![Vec<u8>]>, (http::Status, String)> {
+    let (from, to, bbox) = filter;
+    let filter = columnar::Filter::new(from, to, bbox)
+        .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))?;
+    let (sender, mut receiver) = mpsc::channel(EXPORT_CHUNKS);
+    let port = data.0.clone();
+    let span = tracing::Span::current();
+    rocket::tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let res = columnar::write(&port, &filter, format, ChannelWriter::new(sender))
+            .map_err(|err| err.to_string())
+            .and_then(|mut writer| io::Write::flush(&mut writer).map_err(|err| err.to_string()));
+        // the response is already on its way - all we can do is to stop & log.
+        if let Err(err) = res {
+            tracing::error!(error = %err, "Columnar export failed.");
+        }
+    });
+    let body = stream::ByteStream! {
+        while let Some(chunk) = receiver.recv().await {
+            yield chunk;
+        }
+    };
+    Ok(match format {
+        columnar::Format::Arrow => Export::new(body, columnar::ARROW_MEDIA_TYPE, "arrows"),
+        columnar::Format::Parquet => Export::new(body, columnar::PARQUET_MEDIA_TYPE, "parquet"),
+    })
+}
+
+/// Export all position reports as Arrow IPC stream - one row per report, ordered by MMSI.
+#[utoipa::path(
+    get,
+    path = "/vessels/export.arrows",
+    params(
+        ("from" = Option<String>, Query, description = "Start of the time window (RFC 3339, inclusive)."),
+        ("to" = Option<String>, Query, description = "End of the time window (RFC 3339, exclusive)."),
+        ("bbox" = Option<String>, Query, description = "Bounding box: min_lon,min_lat,max_lon,max_lat.")
+    ),
+    responses(
+        (status = 200, content(("application/vnd.apache.arrow.stream"))),
+        (status = 422, body = String)
+    )
+)]
+#[get("/vessels/export.arrows?<from>&<to>&<bbox>")]
+fn export_arrow(
+    from: Option<&str>,
+    to: Option<&str>,
+    bbox: Option<&str>,
+    data: &rocket::State<PortData>,
+) -> Result<Export<stream::ByteStream![Vec<u8>]>, (http::Status, String)> {
+    export_columnar(data, (from, to, bbox), columnar::Format::Arrow)
+}
+
+/// Export all position reports as Parquet file - one row per report, ordered by MMSI.
+#[utoipa::path(
+    get,
+    path = "/vessels/export.parquet",
+    params(
+        ("from" = Option<String>, Query, description = "Start of the time window (RFC 3339, inclusive)."),
+        ("to" = Option<String>, Query, description = "End of the time window (RFC 3339, exclusive)."),
+        ("bbox" = Option<String>, Query, description = "Bounding box: min_lon,min_lat,max_lon,max_lat.")
+    ),
+    responses(
+        (status = 200, content(("application/vnd.apache.parquet"))),
+        (status = 422, body = String)
+    )
+)]
+#[get("/vessels/export.parquet?<from>&<to>&<bbox>")]
+fn export_parquet(
+    from: Option<&str>,
+    to: Option<&str>,
+    bbox: Option<&str>,
+    data: &rocket::State<PortData>,
+) -> Result<Export<stream::ByteStream![Vec<u8>]>, (http::Status, String)> {
+    export_columnar(data, (from, to, bbox), columnar::Format::Parquet)
+}
+
 /// Returns an emtpy index page.
+#[utoipa::path(get, path = "/", responses((status = 200, body = String)))]
 #[get("/")]
 fn index() -> &'static str {
     "Nothing to see here."
 }
 
+/// Returns the OpenAPI specification of this service.
+#[utoipa::path(get, path = "/openapi.json", responses((status = 200, body = Object)))]
+#[get("/openapi.json")]
+fn openapi() -> json::Json<utoipa::openapi::OpenApi> {
+    json::Json(ApiDoc::openapi())
+}
+
 /// Returns example port data as defined in a JSON file.
-fn get_port_data() -> common::Port {
-    let file = fs::File::open("data.json").expect("Expected data.json.");
+fn get_port_data(path: &str) -> Result<common::Port, io::Error> {
+    let file = fs::File::open(path)?;
     let buffered_reader = io::BufReader::new(file);
-    json::serde_json::from_reader(buffered_reader).expect("Could not parse json.")
+    Ok(json::serde_json::from_reader(buffered_reader)?)
+}
+
+/// Loads the port data and marks the service as ready once done.
+fn load_port_data(
+    path: &str,
+    data: &PortData,
+    health: &common::health::Health,
+    metrics: &PortMetrics,
+) -> Result<(), io::Error> {
+    let port = get_port_data(path)?;
+    metrics.update(&port);
+    data.replace(port);
+    health.set_ready(true);
+    Ok(())
+}
+
+/// Loads the port data - if that fails the service starts with an empty port (filled by uploads)
+/// rather than never becoming ready.
+fn init_port_data(
+    path: &str,
+    data: &PortData,
+    health: &common::health::Health,
+    metrics: &PortMetrics,
+) {
+    match load_port_data(path, data, health, metrics) {
+        Ok(()) => tracing::info!("Loaded port data."),
+        Err(err) => {
+            tracing::error!(error = %err, "Could not load port data - starting with an empty port.");
+            health.set_ready(true);
+        }
+    }
+}
+
+/// Returns a fairing loading the port data in the background once rocket is up and running.
+fn port_data_loader(
+    data: PortData,
+    health: common::health::Health,
+    metrics: PortMetrics,
+) -> fairing::AdHoc {
+    fairing::AdHoc::on_liftoff("Port data loader", move |_| {
+        let (data, health, metrics) = (data.clone(), health.clone(), metrics.clone());
+        Box::pin(async move {
+            let span = tracing::info_span!("port_data_loader", file = DATA_FILE);
+            rocket::tokio::task::spawn_blocking(move || {
+                let _entered = span.enter();
+                init_port_data(DATA_FILE, &data, &health, &metrics);
+            });
+        })
+    })
+}
+
+/// Returns the service metrics including gauges describing the port data.
+fn get_metrics() -> Result<(common::metrics::Metrics, PortMetrics), prometheus::Error> {
+    let metrics = common::metrics::Metrics::new("dataport")?;
+    let vessels = prometheus::IntGauge::new("dataport_vessels", "Number of vessels in the port.")?;
+    let points = prometheus::IntGauge::new(
+        "dataport_track_points",
+        "Total number of track points of all vessels in the port.",
+    )?;
+    metrics.registry().register(Box::new(vessels.clone()))?;
+    metrics.registry().register(Box::new(points.clone()))?;
+    Ok((metrics, PortMetrics { vessels, points }))
+}
+
+/// Builds the rocket engine.
+fn rocket() -> rocket::Rocket<rocket::Build> {
+    // Port data is loaded in the background - will replace with MongoDB or similar soon.
+    let data = PortData::default();
+    let health = common::health::Health::new(false);
+    let (metrics, port_metrics) = get_metrics().expect("Could not setup metrics.");
+
+    rocket::custom(common::config::figment(8000))
+        .mount(
+            "/",
+            common::trace::instrument(routes![
+                index,
+                vessels,
+                vessels_geojson,
+                vessels_status,
+                vessels_status_polyline,
+                vessels_status_geojson,
+                import_vessels,
+                import_csv,
+                export_gpx,
+                export_kml,
+                export_arrow,
+                export_parquet,
+                openapi
+            ]),
+        )
+        .manage(data.clone())
+        .manage(port_metrics.clone())
+        .attach(common::cors::fairing())
+        .attach(common::trace::Tracing)
+        .attach(common::compression::Compression)
+        .attach(metrics)
+        .attach(health.clone())
+        .attach(port_data_loader(data, health, port_metrics))
 }
 
-/// Launches the rocket engine.
-#[launch]
-fn rocket() -> _ {
-    // Get some port data - will replace with MongoDB or similar soon.
-    let data: common::Port = get_port_data();
+/// Usage of the import command.
+const IMPORT_USAGE: &str = "Usage: dataport import [--layout marinecadastre|dma] \
+[--column FIELD=COLUMN]... [--data FILE] CSV_FILE";
+
+/// Imports an AIS CSV dump into the port data file - the command line counterpart of import_csv.
+fn run_import(args: &[String]) -> Result<import::ImportReport, Box<dyn error::Error>> {
+    let mut mapping = import::ColumnMapping::default();
+    let mut overrides = vec![];
+    let mut data_file = DATA_FILE.to_string();
+    let mut csv_file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--layout" => {
+                let layout = args.next().ok_or(IMPORT_USAGE)?;
+                mapping = import::ColumnMapping::new(layout.parse()?);
+            }
+            "--column" => {
+                let column = args.next().ok_or(IMPORT_USAGE)?;
+                overrides.push(column.split_once('=').ok_or(IMPORT_USAGE)?);
+            }
+            "--data" => data_file = args.next().ok_or(IMPORT_USAGE)?.clone(),
+            _ if csv_file.is_none() && !arg.starts_with("--") => csv_file = Some(arg),
+            _ => return Err(IMPORT_USAGE.into()),
+        }
+    }
+    for (field, column) in overrides {
+        mapping.set(field, column)?;
+    }
+    let csv_file = csv_file.ok_or(IMPORT_USAGE)?;
 
-    // Configure rocket engines
-    let figment = rocket::Config::figment()
-        .merge(("address", "0.0.0.0"))
-        .merge(("port", 8000));
-    rocket::custom(figment)
-        .mount("/", routes![index, vessels, vessels_status])
-        .manage(data)
+    let mut port = match fs::metadata(&data_file) {
+        Ok(_) => get_port_data(&data_file)?,
+        Err(_) => common::Port::default(),
+    };
+    let (vessels, report) = import::read_vessels(fs::File::open(csv_file)?, &mapping)?;
+    import::merge(&mut port, vessels);
+    let writer = io::BufWriter::new(fs::File::create(&data_file)?);
+    json::serde_json::to_writer_pretty(writer, &port)?;
+    Ok(report)
+}
+
+/// Launches the rocket engine - or runs the import command.
+#[rocket::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import") {
+        match run_import(&args[1..]) {
+            Ok(report) => println!(
+                "{}",
+                json::serde_json::to_string_pretty(&report).unwrap_or_default()
+            ),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    common::trace::init();
+    // launch errors are reported by rocket itself once dropped.
+    let _ = rocket().launch().await;
 }
 
 #[cfg(test)]
@@ -64,7 +799,66 @@ mod tests {
     use super::*;
     use rocket::http;
     use rocket::local::blocking;
-    use std::collections;
+    use std::{collections, env, thread, time};
+
+    fn get_mmsi(value: u32) -> common::mmsi::Mmsi {
+        common::mmsi::Mmsi::new(value).unwrap()
+    }
+
+    fn get_vessel(mmsi: u32) -> common::Vessel {
+        common::Vessel {
+            name: "boaty mcboatface".to_string(),
+            ship_type: common::ais::ShipType::Tug,
+            coordinates: vec![
+                common::Coordinate {
+                    lat: 51.0,
+                    lon: 4.0,
+                },
+                common::Coordinate {
+                    lat: 51.1,
+                    lon: 4.1,
+                },
+            ],
+            ..common::Vessel::new(get_mmsi(mmsi))
+        }
+    }
+
+    fn get_client(port: common::Port, ready: bool) -> blocking::Client {
+        let (_, port_metrics) = get_metrics().unwrap();
+        let figment = rocket::Config::figment().merge(("limits.csv", "1 KiB"));
+        let rocket = rocket::custom(figment)
+            .mount(
+                "/",
+                routes![
+                    vessels,
+                    vessels_geojson,
+                    vessels_status,
+                    vessels_status_polyline,
+                    vessels_status_geojson,
+                    import_vessels,
+                    import_csv,
+                    export_gpx,
+                    export_kml,
+                    export_arrow,
+                    export_parquet
+                ],
+            )
+            .manage(PortData::from(port))
+            .manage(port_metrics)
+            .attach(common::compression::Compression)
+            .attach(common::health::Health::new(ready));
+        blocking::Client::tracked(rocket).expect("a valid test client.")
+    }
+
+    fn geojson_header() -> http::Header<'static> {
+        http::Header::new("Accept", "application/geo+json")
+    }
+
+    const CSV: &str = "\
+MMSI,BaseDateTime,LAT,LON,SOG,COG,Heading,VesselName,VesselType,Status
+123,2022-01-01T00:01:00,51.05,4.05,10.0,90.0,511,BOATY,70,0
+456,2022-01-01T00:00:00,91.0,181.0,10.0,90.0,511,FOO,70,0
+";
 
     // Tests for success.
 
@@ -75,26 +869,291 @@ mod tests {
 
     #[test]
     fn test_vessels_for_success() {
-        let rocket = rocket::build().manage(common::Port {
+        let rocket = rocket::build().manage(PortData::from(common::Port {
             vessels: Default::default(),
-        });
+        }));
         let data = rocket::State::get(&rocket).expect("Port state.`");
-        vessels(data);
+        assert!(vessels(None, data).is_ok());
     }
 
     #[test]
     fn test_vessels_status_for_success() {
-        let rocket = rocket::build().manage(common::Port {
+        let rocket = rocket::build().manage(PortData::from(common::Port {
             vessels: Default::default(),
-        });
+        }));
         let data = rocket::State::get(&rocket).expect("Port state.`");
-        let mmsis = json::Json(common::MMSIList { vessels: vec![] });
+        let mmsis = wire::Negotiated(common::MMSIList { vessels: vec![] });
         vessels_status(mmsis, data);
     }
 
+    #[test]
+    fn test_import_vessels_for_success() {
+        let client = get_client(common::Port::default(), true);
+        let body =
+            json::serde_json::to_string(&common::geojson::vessel_to_feature(&get_vessel(123)))
+                .unwrap();
+        let status = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body(body)
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::Ok);
+    }
+
+    #[test]
+    fn test_import_csv_for_success() {
+        let client = get_client(common::Port::default(), true);
+        let status = client
+            .post("/vessels/import/csv")
+            .header(http::ContentType::CSV)
+            .body(CSV)
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::Ok);
+    }
+
+    #[test]
+    fn test_export_for_success() {
+        let client = get_client(common::Port::default(), true);
+        let status = client.get("/vessels/export.gpx").dispatch().status();
+        assert_eq!(status, http::Status::Ok);
+        let status = client.get("/vessels/export.kml").dispatch().status();
+        assert_eq!(status, http::Status::Ok);
+    }
+
+    #[test]
+    fn test_run_import_for_success() {
+        let dir = env::temp_dir();
+        let csv_file = dir.join("dataport_test_run_import_success.csv");
+        let data_file = dir.join("dataport_test_run_import_success.json");
+        fs::write(&csv_file, CSV).unwrap();
+        let _ = fs::remove_file(&data_file);
+        let args = [
+            "--data".to_string(),
+            data_file.to_str().unwrap().to_string(),
+            csv_file.to_str().unwrap().to_string(),
+        ];
+        run_import(&args).expect("a valid import.");
+        fs::remove_file(&csv_file).unwrap();
+        fs::remove_file(&data_file).unwrap();
+    }
+
+    #[test]
+    fn test_get_metrics_for_success() {
+        get_metrics().expect("valid metrics.");
+    }
+
+    #[test]
+    fn test_load_port_data_for_success() {
+        let (_, metrics) = get_metrics().unwrap();
+        let health = common::health::Health::new(false);
+        load_port_data(DATA_FILE, &PortData::default(), &health, &metrics)
+            .expect("valid port data.");
+    }
+
+    #[test]
+    fn test_openapi_for_success() {
+        openapi();
+    }
+
     // Tests for failure.
 
-    // n/a.
+    #[test]
+    fn test_vessels_for_failure() {
+        let client = get_client(common::Port::default(), true);
+        let res = client.get("/vessels?bbox=4,52,5").dispatch();
+        assert_eq!(res.status(), http::Status::UnprocessableEntity);
+        assert_eq!(
+            res.into_string(),
+            Some("invalid bbox: 4,52,5 - expected min_lon,min_lat,max_lon,max_lat".into())
+        );
+    }
+
+    #[test]
+    fn test_load_port_data_for_failure() {
+        let (_, metrics) = get_metrics().unwrap();
+        let health = common::health::Health::new(false);
+        let res = load_port_data("foo.json", &PortData::default(), &health, &metrics);
+        assert!(res.is_err());
+        assert!(!health.is_ready(), "Should not be ready without data.");
+
+        let res = load_port_data("Cargo.toml", &PortData::default(), &health, &metrics);
+        assert!(res.is_err());
+        assert!(!health.is_ready(), "Should not be ready without data.");
+    }
+
+    #[test]
+    fn test_init_port_data_for_failure() {
+        // ready with an empty port - vessels uploaded meanwhile are kept.
+        let (_, metrics) = get_metrics().unwrap();
+        let health = common::health::Health::new(false);
+        let data = PortData::from(common::Port {
+            vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+        });
+        init_port_data("foo.json", &data, &health, &metrics);
+        assert!(health.is_ready(), "Should be ready without data.");
+        assert_eq!(data.read().vessels.len(), 1);
+    }
+
+    #[test]
+    fn test_import_vessels_for_failure() {
+        let body =
+            json::serde_json::to_string(&common::geojson::vessel_to_feature(&get_vessel(123)))
+                .unwrap();
+
+        // data not loaded yet.
+        let client = get_client(common::Port::default(), false);
+        let status = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body(&body)
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::ServiceUnavailable);
+
+        // not a feature.
+        let client = get_client(common::Port::default(), true);
+        let response = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body("{\"type\": \"Point\", \"coordinates\": [4.1, 51.9]}")
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+
+        // invalid feature.
+        let response = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body(body.replace("\"mmsi\"", "\"foo\""))
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string(),
+            Some("feature has no valid mmsi property".into())
+        );
+
+        // not JSON at all.
+        let status = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body("foo")
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::BadRequest);
+
+        // nothing imported.
+        let response = client.get("/vessels").dispatch();
+        assert_eq!(
+            response.into_json::<common::MMSIList>().unwrap().vessels,
+            Vec::<common::mmsi::Mmsi>::new()
+        );
+    }
+
+    #[test]
+    fn test_import_csv_for_failure() {
+        // data not loaded yet.
+        let client = get_client(common::Port::default(), false);
+        let status = client
+            .post("/vessels/import/csv")
+            .header(http::ContentType::CSV)
+            .body(CSV)
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::ServiceUnavailable);
+
+        // unknown field in the column mapping.
+        let client = get_client(common::Port::default(), true);
+        let response = client
+            .post("/vessels/import/csv?columns.foo=bar")
+            .header(http::ContentType::CSV)
+            .body(CSV)
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+        assert_eq!(response.into_string(), Some("unknown field: foo".into()));
+
+        // wrong layout.
+        let response = client
+            .post("/vessels/import/csv?layout=dma")
+            .header(http::ContentType::CSV)
+            .body(CSV)
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string(),
+            Some("missing column: # Timestamp".into())
+        );
+
+        // upload too large.
+        let status = client
+            .post("/vessels/import/csv")
+            .header(http::ContentType::CSV)
+            .body(CSV.repeat(20))
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::PayloadTooLarge);
+        assert!(client
+            .get("/vessels")
+            .dispatch()
+            .into_json::<common::MMSIList>()
+            .unwrap()
+            .vessels
+            .is_empty());
+    }
+
+    #[test]
+    fn test_export_columnar_for_success() {
+        let client = get_client(common::Port::default(), true);
+        let status = client.get("/vessels/export.arrows").dispatch().status();
+        assert_eq!(status, http::Status::Ok);
+        let status = client.get("/vessels/export.parquet").dispatch().status();
+        assert_eq!(status, http::Status::Ok);
+    }
+
+    #[test]
+    fn test_export_for_failure() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+            },
+            true,
+        );
+        // unknown vessels.
+        let status = client
+            .get("/vessels/export.gpx?mmsi=456")
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::NotFound);
+        let status = client
+            .get("/vessels/export.kml?mmsi=456")
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::NotFound);
+        // invalid mmsi.
+        let status = client
+            .get("/vessels/export.gpx?mmsi=1234567890")
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn test_run_import_for_failure() {
+        let res = run_import(&[]);
+        assert_eq!(res.unwrap_err().to_string(), IMPORT_USAGE);
+        let res = run_import(&["--column".to_string(), "foo".to_string()]);
+        assert_eq!(res.unwrap_err().to_string(), IMPORT_USAGE);
+        let res = run_import(&["--layout".to_string(), "foo".to_string()]);
+        assert_eq!(res.unwrap_err().to_string(), "unknown layout: foo");
+        let res = run_import(&["foo.csv".to_string(), "bar.csv".to_string()]);
+        assert_eq!(res.unwrap_err().to_string(), IMPORT_USAGE);
+        let res = run_import(&[
+            "--data".to_string(),
+            "foo.json".to_string(),
+            "foo.csv".to_string(),
+        ]);
+        assert!(res.is_err());
+    }
 
     // Tests for sanity.
 
@@ -107,67 +1166,601 @@ mod tests {
     #[test]
     fn test_vessels_for_sanity() {
         let vessel = common::Vessel {
-            mmsi: 123,
             name: "boaty mcboatface".to_string(),
-            ship_type: "special".to_string(),
-            coordinates: vec![],
-            timestamps: vec![],
-            speeds: vec![],
-            headings: vec![],
-            statuses: vec![],
-            destinations: vec![],
+            ship_type: common::ais::ShipType::Tug,
+            ..common::Vessel::new(get_mmsi(123))
         };
-        let rocket = rocket::build().manage(common::Port {
-            vessels: collections::HashMap::from([(123, vessel)]),
-        });
+        let rocket = rocket::build().manage(PortData::from(common::Port {
+            vessels: collections::HashMap::from([(get_mmsi(123), vessel)]),
+        }));
         let data = rocket::State::get(&rocket).expect("Port state.`");
-        let res = vessels(data);
+        let res = vessels(None, data).unwrap();
         assert_eq!(res.vessels.len(), 1);
-        assert_eq!(res.vessels[0], 123);
+        assert_eq!(res.vessels[0], get_mmsi(123));
+    }
+
+    #[test]
+    fn test_vessels_bbox_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+            },
+            true,
+        );
+        // any position within the bounding box is enough.
+        for (bbox, expected) in [
+            ("4.05,51.05,4.5,51.5", 1),
+            ("3.5,50.5,3.9,50.9", 0),
+            ("-180,-90,180,90", 1),
+        ] {
+            let res: common::MMSIList = client
+                .get(format!("/vessels?bbox={}", bbox))
+                .dispatch()
+                .into_json()
+                .unwrap();
+            assert_eq!(res.vessels.len(), expected, "bbox: {}", bbox);
+
+            let res: json::Value = client
+                .get(format!("/vessels?bbox={}", bbox))
+                .header(geojson_header())
+                .dispatch()
+                .into_json()
+                .unwrap();
+            assert_eq!(
+                res["features"].as_array().unwrap().len(),
+                expected,
+                "bbox: {}",
+                bbox
+            );
+        }
+
+        let res = client
+            .get("/vessels?bbox=4,52,5")
+            .header(geojson_header())
+            .dispatch();
+        assert_eq!(res.status(), http::Status::UnprocessableEntity);
     }
 
     #[test]
     fn test_vessels_status_for_sanity() {
         // empty list --> empty result.
-        let rocket = rocket::build().manage(common::Port {
+        let rocket = rocket::build().manage(PortData::from(common::Port {
             vessels: Default::default(),
-        });
+        }));
         let data = rocket::State::get(&rocket).expect("Port state.`");
-        let mmsi = json::Json(common::MMSIList { vessels: vec![] });
+        let mmsi = wire::Negotiated(common::MMSIList { vessels: vec![] });
         let res = vessels_status(mmsi, data);
-        assert_eq!(res.is_none(), true);
+        assert!(res.is_none());
 
         // non existing mmsi.
-        let mmsi = json::Json(common::MMSIList { vessels: vec![456] });
+        let mmsi = wire::Negotiated(common::MMSIList {
+            vessels: vec![get_mmsi(456)],
+        });
         let res = vessels_status(mmsi, data);
-        assert_eq!(res.is_none(), true);
+        assert!(res.is_none());
 
         // success.
         let vessel = common::Vessel {
-            mmsi: 456,
             name: "foo".to_string(),
-            ship_type: "bar".to_string(),
-            coordinates: vec![],
-            timestamps: vec![],
-            speeds: vec![],
-            headings: vec![],
-            statuses: vec![],
-            destinations: vec![],
+            ship_type: common::ais::ShipType::Sailing,
+            ..common::Vessel::new(get_mmsi(456))
         };
-        let rocket = rocket::build().manage(common::Port {
-            vessels: collections::HashMap::from([(456, vessel)]),
-        });
+        let rocket = rocket::build().manage(PortData::from(common::Port {
+            vessels: collections::HashMap::from([(get_mmsi(456), vessel)]),
+        }));
         let data = rocket::State::get(&rocket).expect("Port state.`");
-        let mmsi = json::Json(common::MMSIList { vessels: vec![456] });
+        let mmsi = wire::Negotiated(common::MMSIList {
+            vessels: vec![get_mmsi(456)],
+        });
         let res = vessels_status(mmsi, data);
         assert_eq!(res.0.unwrap().vessels.len(), 1);
     }
 
+    #[test]
+    fn test_vessels_status_polyline_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+            },
+            true,
+        );
+        let body = "{\"vessels\": [123]}";
+        let response = client
+            .post("/vessels?polyline=6")
+            .header(http::ContentType::JSON)
+            .body(body)
+            .dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        let res = response.into_json::<json::Value>().unwrap();
+        assert_eq!(
+            res["vessels"][0]["coordinates"],
+            json::serde_json::json!({"polyline": "_kxg`B_ocsF_ibE_ibE", "precision": 6})
+        );
+        // encoded tracks decode as plain vessels.
+        let res: common::VesselList = json::serde_json::from_value(res).unwrap();
+        assert_eq!(res.vessels[0].coordinates, get_vessel(123).coordinates);
+        assert_eq!(res.vessels[0].name, "boaty mcboatface");
+
+        // unknown vessels.
+        let response = client
+            .post("/vessels?polyline=5")
+            .header(http::ContentType::JSON)
+            .body("{\"vessels\": [456]}")
+            .dispatch();
+        assert_eq!(response.into_string(), Some("null".into()));
+
+        let response = client
+            .post("/vessels?polyline=4")
+            .header(http::ContentType::JSON)
+            .body(body)
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+
+        // plain tracks by default.
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .body(body)
+            .dispatch();
+        let res = response.into_json::<json::Value>().unwrap();
+        assert!(res["vessels"][0]["coordinates"].is_array());
+    }
+
+    #[test]
+    fn test_vessels_geojson_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([
+                    (get_mmsi(123), get_vessel(123)),
+                    (get_mmsi(456), get_vessel(456)),
+                ]),
+            },
+            true,
+        );
+
+        // plain JSON by default.
+        let response = client.get("/vessels").dispatch();
+        assert_eq!(response.content_type(), Some(http::ContentType::JSON));
+        let response = client
+            .get("/vessels")
+            .header(http::Header::new("Accept", "application/json"))
+            .dispatch();
+        assert_eq!(response.content_type(), Some(http::ContentType::JSON));
+
+        // GeoJSON if preferred.
+        let response = client.get("/vessels").header(geojson_header()).dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new("application", "geo+json"))
+        );
+        let res = response.into_json::<json::Value>().unwrap();
+        assert_eq!(res["type"], "FeatureCollection");
+        assert_eq!(res["features"].as_array().unwrap().len(), 2);
+        assert_eq!(res["features"][0]["properties"]["mmsi"], 123);
+
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .header(geojson_header())
+            .body("{\"vessels\": [456, 789]}")
+            .dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new("application", "geo+json"))
+        );
+        let res = response.into_json::<json::Value>().unwrap();
+        assert_eq!(res["features"].as_array().unwrap().len(), 1);
+        assert_eq!(res["features"][0]["id"], 456);
+        assert_eq!(
+            res["features"][0]["geometry"]["geometries"][0]["coordinates"],
+            json::serde_json::json!([[4.0, 51.0], [4.1, 51.1]])
+        );
+
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .body("{\"vessels\": [456]}")
+            .dispatch();
+        let res = response.into_json::<json::Value>().unwrap();
+        assert_eq!(res["vessels"][0]["mmsi"], 456);
+    }
+
+    #[test]
+    fn test_vessels_msgpack_for_sanity() {
+        // without the loader - which would replace the vessels inserted below.
+        let client = get_client(common::Port::default(), true);
+        let port = client.rocket().state::<PortData>().unwrap();
+        port.insert(vec![get_vessel(123)]);
+
+        let response = client
+            .get("/vessels")
+            .header(http::Header::new("Accept", "application/msgpack"))
+            .dispatch();
+        let res: common::MMSIList = wire::decode(&response.into_bytes().unwrap()).unwrap();
+        assert_eq!(res.vessels, vec![get_mmsi(123)]);
+
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::new("application", "msgpack"))
+            .header(http::Header::new("Accept", "application/msgpack"))
+            .body(
+                wire::encode(&common::MMSIList {
+                    vessels: vec![get_mmsi(123)],
+                })
+                .unwrap(),
+            )
+            .dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new("application", "msgpack"))
+        );
+        let res: Option<common::VesselList> =
+            wire::decode(&response.into_bytes().unwrap()).unwrap();
+        let res = res.unwrap();
+        assert_eq!(res.vessels[0].mmsi, get_mmsi(123));
+        assert_eq!(res.vessels[0].coordinates, get_vessel(123).coordinates);
+
+        // compressed if accepted & worth it.
+        port.insert((1..100).map(get_vessel).collect());
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .header(http::Header::new("Accept-Encoding", "br"))
+            .body(
+                json::serde_json::to_string(&common::MMSIList {
+                    vessels: (1..100).map(get_mmsi).collect(),
+                })
+                .unwrap(),
+            )
+            .dispatch();
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("br"));
+    }
+
+    #[test]
+    fn test_import_vessels_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+            },
+            true,
+        );
+
+        // replace an existing vessel & add a new one.
+        let mut vessel = get_vessel(123);
+        vessel.name = "foo".to_string();
+        let collection = common::geojson::vessels_to_feature_collection(&[vessel, get_vessel(456)]);
+        let response = client
+            .post("/vessels/import")
+            .header(http::ContentType::new("application", "geo+json"))
+            .body(json::serde_json::to_string(&collection).unwrap())
+            .dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(
+            response.into_json::<common::MMSIList>().unwrap().vessels,
+            vec![get_mmsi(123), get_mmsi(456)]
+        );
+
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .body("{\"vessels\": [123, 456]}")
+            .dispatch();
+        let res = response.into_json::<common::VesselList>().unwrap();
+        assert_eq!(res.vessels.len(), 2);
+        assert_eq!(res.vessels[0].name, "foo");
+        assert_eq!(res.vessels[1].coordinates.len(), 2);
+    }
+
+    #[test]
+    fn test_import_csv_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+            },
+            true,
+        );
+        let response = client
+            .post("/vessels/import/csv")
+            .header(http::ContentType::CSV)
+            .body(CSV)
+            .dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        let report = response.into_json::<import::ImportReport>().unwrap();
+        assert_eq!(report.vessels, vec![get_mmsi(123)]);
+        assert_eq!(report.accepted, 1);
+        assert_eq!(report.rejected_count, 1);
+        assert_eq!(report.rejected[0].line, 3);
+
+        // merged into the existing track.
+        let response = client
+            .post("/vessels")
+            .header(http::ContentType::JSON)
+            .body("{\"vessels\": [123]}")
+            .dispatch();
+        let res = response.into_json::<common::VesselList>().unwrap();
+        assert_eq!(res.vessels[0].name, "BOATY");
+        assert_eq!(res.vessels[0].coordinates.len(), 3);
+        assert_eq!(
+            res.vessels[0].timestamps[2],
+            "2022-01-01T00:01:00Z".to_string()
+        );
+
+        // custom column mapping.
+        let response = client
+            .post("/vessels/import/csv?layout=dma&columns.lat=lat&columns.lon=lon")
+            .header(http::ContentType::CSV)
+            .body("MMSI,# Timestamp,lat,lon\n789,01/01/2022 00:00:00,55.7,12.6\n")
+            .dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        let report = response.into_json::<import::ImportReport>().unwrap();
+        assert_eq!(report.vessels, vec![get_mmsi(789)]);
+        let response = client.get("/vessels").dispatch();
+        assert_eq!(
+            response
+                .into_json::<common::MMSIList>()
+                .unwrap()
+                .vessels
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_export_columnar_for_failure() {
+        let client = get_client(common::Port::default(), true);
+        let response = client
+            .get("/vessels/export.arrows?bbox=4,52,5,51")
+            .dispatch();
+        assert_eq!(response.status(), http::Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string(),
+            Some("invalid bbox: 4,52,5,51 - expected min_lon,min_lat,max_lon,max_lat".into())
+        );
+        let status = client
+            .get("/vessels/export.parquet?from=today")
+            .dispatch()
+            .status();
+        assert_eq!(status, http::Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn test_export_columnar_for_sanity() {
+        // large enough for several batches & chunks.
+        let mut vessel = get_vessel(123);
+        vessel.coordinates = (0..100_000)
+            .map(|i| common::Coordinate {
+                lat: 51.0 + i as f64 * 1e-6,
+                lon: 4.0,
+            })
+            .collect();
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([
+                    (get_mmsi(123), vessel),
+                    (get_mmsi(456), get_vessel(456)),
+                ]),
+            },
+            true,
+        );
+        let response = client.get("/vessels/export.arrows").dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new(
+                "application",
+                "vnd.apache.arrow.stream"
+            ))
+        );
+        assert_eq!(
+            response.headers().get_one("Content-Disposition"),
+            Some("attachment; filename=\"vessels.arrows\"")
+        );
+        let body = response.into_bytes().unwrap();
+        let reader = arrow_ipc::reader::StreamReader::try_new(body.as_slice(), None).unwrap();
+        let rows: Vec<usize> = reader.map(|batch| batch.unwrap().num_rows()).collect();
+        assert!(rows.len() > 1);
+        assert_eq!(rows.iter().sum::<usize>(), 100_002);
+
+        let response = client
+            .get("/vessels/export.parquet?bbox=3.5,51.05,4.5,52")
+            .dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new("application", "vnd.apache.parquet"))
+        );
+        let body = bytes::Bytes::from(response.into_bytes().unwrap());
+        let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(body)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(
+            batches.iter().map(|batch| batch.num_rows()).sum::<usize>(),
+            50_000 + 1
+        );
+        assert_eq!(batches[0].schema(), columnar::schema());
+    }
+
+    #[test]
+    fn test_export_for_sanity() {
+        let client = get_client(
+            common::Port {
+                vessels: collections::HashMap::from([
+                    (get_mmsi(456), get_vessel(456)),
+                    (get_mmsi(123), get_vessel(123)),
+                ]),
+            },
+            true,
+        );
+        let response = client.get("/vessels/export.gpx").dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new("application", "gpx+xml"))
+        );
+        assert_eq!(
+            response.headers().get_one("Content-Disposition"),
+            Some("attachment; filename=\"vessels.gpx\"")
+        );
+        let res = response.into_string().unwrap();
+        assert_eq!(res.matches("<trk>").count(), 2);
+        assert!(res.find("MMSI 000000123").unwrap() < res.find("MMSI 000000456").unwrap());
+
+        // unknown vessels are skipped.
+        let response = client
+            .get("/vessels/export.kml?mmsi=456&mmsi=789")
+            .dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(http::ContentType::new(
+                "application",
+                "vnd.google-earth.kml+xml"
+            ))
+        );
+        let res = response.into_string().unwrap();
+        assert_eq!(res.matches("<Placemark").count(), 1);
+        assert!(res.contains("<Placemark id=\"000000456\">"));
+    }
+
+    #[test]
+    fn test_run_import_for_sanity() {
+        let dir = env::temp_dir();
+        let csv_file = dir.join("dataport_test_run_import_sanity.csv");
+        let data_file = dir.join("dataport_test_run_import_sanity.json");
+        fs::write(
+            &csv_file,
+            "MMSI,# Timestamp,Latitude,Longitude\n123,01/01/2022 00:01:00,51.05,4.05\n",
+        )
+        .unwrap();
+        let port = common::Port {
+            vessels: collections::HashMap::from([(get_mmsi(123), get_vessel(123))]),
+        };
+        fs::write(&data_file, json::serde_json::to_string(&port).unwrap()).unwrap();
+
+        let args = [
+            "--layout".to_string(),
+            "dma".to_string(),
+            "--data".to_string(),
+            data_file.to_str().unwrap().to_string(),
+            csv_file.to_str().unwrap().to_string(),
+        ];
+        let report = run_import(&args).unwrap();
+        assert_eq!(report.accepted, 1);
+        let port = get_port_data(data_file.to_str().unwrap()).unwrap();
+        fs::remove_file(&csv_file).unwrap();
+        fs::remove_file(&data_file).unwrap();
+        assert_eq!(port.vessels[&get_mmsi(123)].coordinates.len(), 3);
+        assert_eq!(port.vessels[&get_mmsi(123)].name, "boaty mcboatface");
+    }
+
     #[test]
     fn test_rocket_for_sanity() {
         let client = blocking::Client::tracked(rocket()).expect("a valid test client.");
         let response = client.get("/").dispatch();
         assert_eq!(response.status(), http::Status::Ok);
         assert_eq!(response.into_string(), Some("Nothing to see here.".into()));
+
+        // preflight for the JSON POST.
+        let response = client
+            .options("/vessels")
+            .header(http::Header::new("Origin", "http://localhost:8080"))
+            .header(http::Header::new("Access-Control-Request-Method", "POST"))
+            .header(http::Header::new(
+                "Access-Control-Request-Headers",
+                "Content-Type",
+            ))
+            .dispatch();
+        assert_eq!(response.status(), http::Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("*")
+        );
+    }
+
+    #[test]
+    fn test_openapi_for_sanity() {
+        // set UPDATE_OPENAPI to regenerate the committed specification.
+        let spec = json::serde_json::to_string_pretty(&ApiDoc::openapi()).expect("a valid spec.");
+        if env::var("UPDATE_OPENAPI").is_ok() {
+            fs::write("openapi.json", format!("{}\n", spec)).expect("a writable openapi.json.");
+        }
+        // read at runtime - a regenerated specification is compared as written.
+        let committed = fs::read_to_string("openapi.json").expect("a readable openapi.json.");
+        let committed: json::Value =
+            json::serde_json::from_str(&committed).expect("a valid committed spec.");
+        let served: json::Value = json::serde_json::from_str(&spec).unwrap();
+        assert_eq!(
+            served, committed,
+            "The OpenAPI specification drifted - rerun the tests with UPDATE_OPENAPI=1."
+        );
+
+        let client = blocking::Client::tracked(rocket()).expect("a valid test client.");
+        let response = client.get("/openapi.json").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        assert_eq!(response.into_json::<json::Value>(), Some(committed));
+    }
+
+    #[test]
+    fn test_load_port_data_for_sanity() {
+        let vessel = common::Vessel {
+            name: "boaty mcboatface".to_string(),
+            ship_type: common::ais::ShipType::Tug,
+            coordinates: vec![
+                common::Coordinate {
+                    lat: 51.0,
+                    lon: 4.0,
+                },
+                common::Coordinate {
+                    lat: 51.1,
+                    lon: 4.1,
+                },
+            ],
+            ..common::Vessel::new(get_mmsi(123))
+        };
+        let path = env::temp_dir().join("dataport_test_load_port_data.json");
+        let port = common::Port {
+            vessels: collections::HashMap::from([(get_mmsi(123), vessel)]),
+        };
+        fs::write(&path, json::serde_json::to_string(&port).unwrap()).unwrap();
+
+        let (metrics, port_metrics) = get_metrics().unwrap();
+        let health = common::health::Health::new(false);
+        let data = PortData::default();
+        load_port_data(path.to_str().unwrap(), &data, &health, &port_metrics).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(health.is_ready());
+        assert_eq!(data.read().vessels.len(), 1);
+        let res = metrics.render();
+        assert!(res.contains("dataport_vessels 1"));
+        assert!(res.contains("dataport_track_points 2"));
+    }
+
+    #[test]
+    fn test_get_metrics_for_sanity() {
+        let client = blocking::Client::tracked(rocket()).expect("a valid test client.");
+        client.get("/").dispatch();
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+        let body = response.into_string().unwrap();
+        assert!(body.contains("dataport_vessels 0"));
+        assert!(body
+            .contains("dataport_http_requests_total{method=\"GET\",route=\"/\",status=\"200\"} 1"));
+    }
+
+    #[test]
+    fn test_health_for_sanity() {
+        let client = blocking::Client::tracked(rocket()).expect("a valid test client.");
+        let response = client.get("/healthz").dispatch();
+        assert_eq!(response.status(), http::Status::Ok);
+
+        // data is loaded in the background - becomes ready eventually.
+        let mut status = http::Status::ServiceUnavailable;
+        for _ in 0..100 {
+            status = client.get("/readyz").dispatch().status();
+            if status == http::Status::Ok {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(10));
+        }
+        assert_eq!(status, http::Status::Ok);
     }
 }
@@ -0,0 +1,161 @@
+//! Clustering of the vessel markers at low zoom levels - markers close to each other on screen are
+//! replaced by a single marker showing their number.
+
+use std::{collections, f64::consts};
+
+use common::Coordinate;
+
+/// Zoom level from which all markers are shown.
+pub const MAX_ZOOM: f64 = 13.0;
+
+/// Size of the grid cells markers are clustered in - in pixels.
+const CELL_PX: f64 = 60.0;
+
+/// Size of the map tiles in pixels.
+const TILE_PX: f64 = 256.0;
+
+/// Latitude limit of the Web Mercator projection.
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+/// Markers shown as one - `members` are the indices of the positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub center: Coordinate,
+    pub members: Vec<usize>,
+}
+
+/// Projects a coordinate to pixels of the map at a zoom level - Web Mercator as used by leaflet.
+pub fn project(coordinate: &Coordinate, zoom: f64) -> (f64, f64) {
+    let scale = TILE_PX * 2_f64.powf(zoom);
+    let lat = coordinate
+        .lat
+        .clamp(-MAX_LATITUDE, MAX_LATITUDE)
+        .to_radians();
+    let x = (coordinate.lon + 180.0) / 360.0 * scale;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / consts::PI) / 2.0 * scale;
+    (x, y)
+}
+
+/// Returns the coordinate at pixels of the map at a zoom level - the inverse of `project`.
+pub fn unproject(x: f64, y: f64, zoom: f64) -> Coordinate {
+    let scale = TILE_PX * 2_f64.powf(zoom);
+    let lon = x / scale * 360.0 - 180.0;
+    let lat = (consts::PI * (1.0 - 2.0 * y / scale))
+        .sinh()
+        .atan()
+        .to_degrees();
+    Coordinate { lat, lon }
+}
+
+/// Groups the positions by the grid cell they are in at the zoom level - the center of a cluster
+/// is the mean of its positions.
+pub fn cluster(positions: &[Coordinate], zoom: f64) -> Vec<Cluster> {
+    let mut cells: collections::HashMap<(i64, i64), usize> = collections::HashMap::new();
+    let mut res: Vec<Cluster> = vec![];
+    for (i, pos) in positions.iter().enumerate() {
+        let (x, y) = project(pos, zoom);
+        let cell = ((x / CELL_PX).floor() as i64, (y / CELL_PX).floor() as i64);
+        let j = *cells.entry(cell).or_insert_with(|| {
+            res.push(Cluster {
+                center: Coordinate { lat: 0.0, lon: 0.0 },
+                members: vec![],
+            });
+            res.len() - 1
+        });
+        res[j].center.lat += pos.lat;
+        res[j].center.lon += pos.lon;
+        res[j].members.push(i);
+    }
+    for item in res.iter_mut() {
+        item.center.lat /= item.members.len() as f64;
+        item.center.lon /= item.members.len() as f64;
+    }
+    res
+}
+
+/// Returns the size of a cluster marker in pixels - growing with the number of vessels.
+pub fn size(count: usize) -> u32 {
+    24 + 6 * (count.max(1) as f64).log10().floor() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_coordinate(lat: f64, lon: f64) -> Coordinate {
+        Coordinate { lat, lon }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_cluster_for_success() {
+        let positions = [
+            get_coordinate(51.90, 4.10),
+            get_coordinate(51.91, 4.11),
+            get_coordinate(53.0, 6.0),
+        ];
+        let res = cluster(&positions, 8.0);
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].members, vec![0, 1]);
+        assert!((res[0].center.lat - 51.905).abs() < 1e-9);
+        assert!((res[0].center.lon - 4.105).abs() < 1e-9);
+        assert_eq!(res[1].members, vec![2]);
+        assert_eq!(res[1].center, positions[2]);
+    }
+
+    #[test]
+    fn test_project_for_success() {
+        assert_eq!(project(&get_coordinate(0.0, 0.0), 0.0), (128.0, 128.0));
+        let (x, y) = project(&get_coordinate(0.0, 180.0), 1.0);
+        assert_eq!((x, y), (512.0, 256.0));
+    }
+
+    #[test]
+    fn test_unproject_for_success() {
+        assert_eq!(unproject(128.0, 128.0, 0.0), get_coordinate(0.0, 0.0));
+        let res = unproject(0.0, 0.0, 0.0);
+        assert!((res.lat - MAX_LATITUDE).abs() < 1e-6 && res.lon == -180.0);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_cluster_for_failure() {
+        assert!(cluster(&[], 8.0).is_empty());
+    }
+
+    #[test]
+    fn test_project_for_failure() {
+        // poles are clamped to the limits of the projection.
+        let (_, y) = project(&get_coordinate(90.0, 0.0), 0.0);
+        assert!(y.is_finite() && y.abs() < 1e-6);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_cluster_for_sanity() {
+        // clusters split up when zooming in.
+        let positions = [get_coordinate(51.90, 4.10), get_coordinate(51.91, 4.11)];
+        assert_eq!(cluster(&positions, 8.0).len(), 1);
+        assert_eq!(cluster(&positions, MAX_ZOOM).len(), 2);
+    }
+
+    #[test]
+    fn test_unproject_for_sanity() {
+        let coordinate = get_coordinate(51.9, 4.1);
+        let (x, y) = project(&coordinate, 10.0);
+        let res = unproject(x, y, 10.0);
+        assert!((res.lat - coordinate.lat).abs() < 1e-9);
+        assert!((res.lon - coordinate.lon).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_size_for_sanity() {
+        assert_eq!(size(0), 24);
+        assert_eq!(size(9), 24);
+        assert_eq!(size(10), 30);
+        assert_eq!(size(1500), 42);
+    }
+}
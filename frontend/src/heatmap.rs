@@ -0,0 +1,116 @@
+//! Density of the vessel positions - counted in grid cells on screen & drawn as colored cells, so
+//! busy areas stand out at any zoom level.
+
+use std::collections;
+
+use common::Coordinate;
+
+use crate::{cluster, viewport};
+
+/// Size of the grid cells in pixels.
+const CELL_PX: f64 = 16.0;
+
+/// Colors from low to high density.
+const COLORS: [&str; 5] = ["#2c7bb6", "#abd9e9", "#ffffbf", "#fdae61", "#d7191c"];
+
+/// Grid cell with the number of positions within it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub bounds: viewport::Bounds,
+    pub count: usize,
+}
+
+/// Counts the positions per grid cell at the zoom level - ordered by grid cell.
+pub fn density<'a>(positions: impl Iterator<Item = &'a Coordinate>, zoom: f64) -> Vec<Cell> {
+    let mut cells: collections::BTreeMap<(i64, i64), usize> = collections::BTreeMap::new();
+    for pos in positions {
+        let (x, y) = cluster::project(pos, zoom);
+        *cells
+            .entry(((x / CELL_PX).floor() as i64, (y / CELL_PX).floor() as i64))
+            .or_default() += 1;
+    }
+    cells
+        .into_iter()
+        .map(|((x, y), count)| {
+            let north_west = cluster::unproject(x as f64 * CELL_PX, y as f64 * CELL_PX, zoom);
+            let south_east =
+                cluster::unproject((x + 1) as f64 * CELL_PX, (y + 1) as f64 * CELL_PX, zoom);
+            Cell {
+                bounds: viewport::Bounds {
+                    south: south_east.lat,
+                    west: north_west.lon,
+                    north: north_west.lat,
+                    east: south_east.lon,
+                },
+                count,
+            }
+        })
+        .collect()
+}
+
+/// Returns the color for a count relative to the maximum count - on a logarithmic scale as a few
+/// berths hold most positions.
+pub fn color(count: usize, max: usize) -> &'static str {
+    let fraction = if max > 1 {
+        (count.max(1) as f64).ln() / (max as f64).ln()
+    } else {
+        1.0
+    };
+    COLORS[((fraction * COLORS.len() as f64) as usize).min(COLORS.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_coordinate(lat: f64, lon: f64) -> Coordinate {
+        Coordinate { lat, lon }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_density_for_success() {
+        let positions = [
+            get_coordinate(51.9, 4.1),
+            get_coordinate(51.9, 4.1),
+            get_coordinate(53.0, 6.0),
+        ];
+        let res = density(positions.iter(), 10.0);
+        assert_eq!(res.len(), 2);
+        let busy = res.iter().find(|item| item.count == 2).unwrap();
+        assert!(busy.bounds.contains(&positions[0]));
+        assert!(busy.bounds.south < busy.bounds.north && busy.bounds.west < busy.bounds.east);
+        assert_eq!(res.iter().map(|item| item.count).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_color_for_success() {
+        assert_eq!(color(1, 1000), COLORS[0]);
+        assert_eq!(color(1000, 1000), COLORS[4]);
+        assert_eq!(color(40, 1000), COLORS[2]);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_density_for_failure() {
+        assert!(density([].iter(), 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_color_for_failure() {
+        assert_eq!(color(0, 0), COLORS[4]);
+        assert_eq!(color(0, 10), COLORS[0]);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_density_for_sanity() {
+        // cells merge when zooming out.
+        let positions = [get_coordinate(51.9, 4.1), get_coordinate(51.91, 4.11)];
+        assert_eq!(density(positions.iter(), 14.0).len(), 2);
+        assert_eq!(density(positions.iter(), 8.0).len(), 1);
+    }
+}
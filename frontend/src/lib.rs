@@ -1,7 +1,24 @@
 extern crate console_error_panic_hook;
 
-use std::panic;
+mod cluster;
+mod heatmap;
+mod marker;
+mod measure;
+mod playback;
+mod popup;
+mod query;
+mod request;
+mod search;
+mod simplify;
+mod style;
+mod tools;
+mod tracks;
+mod viewport;
+mod zones;
 
+use std::{cell, panic, rc};
+
+use gloo_utils::format::JsValueSerdeExt;
 use wasm_bindgen::{prelude, JsCast};
 
 /// Chunk size for getting set of vessels.
@@ -14,193 +31,1907 @@ const TILES_URL: &str =
 /// Endpoint to use to reach datapoint - temporary.
 const DATAPORT_ENDPOINT: &str = "http://localhost:8000/vessels";
 
+/// Endpoint to use to reach the path function - temporary.
+const PATH_FUNCTION_ENDPOINT: &str = "http://localhost:8001/simplify/batch";
+
+/// Interval of the playback in milliseconds.
+const TICK_MS: f64 = 100.0;
+
+/// Number of steps of the playback slider.
+const SLIDER_STEPS: f64 = 1000.0;
+
+/// Delay in milliseconds after the map stopped moving before the vessels in view are loaded.
+const DEBOUNCE_MS: i32 = 300;
+
+/// Ratio of the viewport size vessels are also loaded (& kept) around it.
+const VIEWPORT_PADDING: f64 = 0.2;
+
+/// Zoom levels to zoom in when clicking on a cluster.
+const CLUSTER_ZOOM_STEP: f64 = 2.0;
+
+/// Maximum number of vessels listed in the search results.
+const RESULTS_LIMIT: usize = 50;
+
+/// Maximum number of suggestions while typing a name or MMSI.
+const SUGGESTIONS_LIMIT: usize = 10;
+
+/// Maximum zoom level when zooming to a selected vessel.
+const SELECTED_MAX_ZOOM: f64 = 14.0;
+
+/// Color of the outline of the track of the selected vessel.
+const HIGHLIGHT_COLOR: &str = "#ffeb3b";
+
+/// Maximum number of errors shown in the banner.
+const MAX_ERRORS: u32 = 3;
+
+/// Default location of the zones of the port - served next to the frontend.
+const ZONES_URL: &str = "zones.geojson";
+
+/// Pane of the zones - below the tracks.
+const ZONES_PANE: &str = "zones";
+
+/// Pane of the heatmap - above the zones, below the tracks.
+const HEATMAP_PANE: &str = "heatmap";
+
+/// Fill opacity of the zones.
+const ZONE_OPACITY: f64 = 0.15;
+
+/// Fill opacity of the heatmap cells.
+const HEATMAP_OPACITY: f64 = 0.5;
+
+/// Color of the drawings of the measurement tools.
+const TOOL_COLOR: &str = "#d81b60";
+
+/// Clicks within this distance (pixels) of a vessel marker pick the vessel.
+const SNAP_PX: f64 = 12.0;
+
+/// Options for the map.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MapOptions {
+    /// Draws the tracks on a canvas instead of as SVG elements - much faster for many tracks.
+    prefer_canvas: bool,
+}
+
 /// Options for the Polyline.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct PolylineOptions {
     color: String,
     weight: usize,
+    opacity: f64,
+}
+
+/// Options for fitting the map to bounds.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FitOptions {
+    max_zoom: f64,
+}
+
+/// Options for controls.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ControlOptions {
+    position: String,
+}
+
+/// Bindings to the parts of leaflet not covered by the leaflet crate.
+#[prelude::wasm_bindgen]
+extern "C" {
+    /// Creates a control - its content is returned by the `onAdd` callback.
+    #[wasm_bindgen(js_namespace = L, js_name = control)]
+    fn new_control(options: &prelude::JsValue) -> leaflet::Control;
+
+    /// Creates an icon from HTML - used for the SVG markers.
+    #[wasm_bindgen(js_namespace = L, js_name = divIcon)]
+    fn new_div_icon(options: &prelude::JsValue) -> leaflet::Icon;
+
+    /// Stops clicks on the element from reaching the map.
+    #[wasm_bindgen(js_namespace = ["L", "DomEvent"], js_name = disableClickPropagation)]
+    fn disable_click_propagation(element: &web_sys::HtmlElement);
+
+    /// Stops scrolling within the element from zooming the map.
+    #[wasm_bindgen(js_namespace = ["L", "DomEvent"], js_name = disableScrollPropagation)]
+    fn disable_scroll_propagation(element: &web_sys::HtmlElement);
+
+    /// Creates a control switching the base layers & toggling the overlays.
+    #[wasm_bindgen(js_namespace = ["L", "control"], js_name = layers)]
+    fn new_layers_control(
+        base_layers: &prelude::JsValue,
+        overlays: &prelude::JsValue,
+        options: &prelude::JsValue,
+    ) -> leaflet::Control;
+
+    /// Map with the methods missing from the leaflet bindings.
+    type MapExtra;
+
+    /// Creates a pane of the map - the layers within are ordered by its z-index.
+    #[wasm_bindgen(method, js_name = createPane)]
+    fn create_pane(this: &MapExtra, name: &str) -> web_sys::HtmlElement;
+
+    /// Returns true if the layer is shown on the map.
+    #[wasm_bindgen(method, js_name = hasLayer)]
+    fn has_layer(this: &MapExtra, layer: &leaflet::Layer) -> bool;
+}
+
+/// Options for areas - zones & heatmap cells.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AreaOptions {
+    pane: String,
+    stroke: bool,
+    color: String,
+    weight: usize,
+    fill_color: String,
+    fill_opacity: f64,
+    interactive: bool,
+}
+
+/// Options for the points of the measurement tools.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PointOptions {
+    radius: f64,
+    color: String,
+    interactive: bool,
+}
+
+/// Options for the vessel markers - clicks reach the map, so the tools can pick vessels.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkerOptions {
+    bubbling_mouse_events: bool,
+}
+
+/// Options for the layers control.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayersOptions {
+    position: String,
 }
 
 /// Options for the icon.
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct IconOptions {
-    icon_url: String,
-    icon_size: (usize, usize),
-    icon_anchor: (i8, i8),
-    popup_anchor: (i8, i8),
+    html: String,
+    class_name: String,
+    icon_size: (u32, u32),
+    icon_anchor: (u32, u32),
+    popup_anchor: (i32, i32),
 }
 
-/// do a HTTP request to a specified endpoint.
-async fn do_request<T: serde::Serialize>(
+/// do a HTTP request to a specified endpoint - the request id allows tracing it through the services.
+///
+/// Responses are requested in the compact MessagePack format; the browser takes care of the
+/// (gzip/brotli) compression. The request is cancelled once the (optional) signal is aborted.
+async fn do_request<T: serde::Serialize, R: serde::de::DeserializeOwned>(
     url: &str,
     verb: &str,
     body: T,
-) -> Result<prelude::JsValue, prelude::JsValue> {
-    let mut opts = web_sys::RequestInit::new();
-    opts.method(verb);
-    opts.mode(web_sys::RequestMode::Cors);
-
-    // if we do a POST we add whatever is in the body.
-    if verb == "POST" {
-        let json = serde_json::to_string(&body).unwrap();
-        let json: prelude::JsValue = prelude::JsValue::from_serde(&json).unwrap();
-        opts.body(Some(&json));
-    }
-
-    let request = web_sys::Request::new_with_str_and_init(url, &opts)?;
-    // if we have a body it it json data.
-    if verb == "POST" {
-        request
-            .headers()
-            .set("Content-Type", "application/json")
-            .unwrap();
+    request_id: &str,
+    signal: Option<&web_sys::AbortSignal>,
+) -> Result<R, prelude::JsValue> {
+    let spec = request::new(url, verb, &body, request_id)
+        .map_err(|err| prelude::JsValue::from_str(&err.to_string()))?;
+    let opts = web_sys::RequestInit::new();
+    opts.set_method(&spec.method);
+    opts.set_mode(web_sys::RequestMode::Cors);
+    opts.set_signal(signal);
+    if let Some(body) = &spec.body {
+        opts.set_body(&prelude::JsValue::from_str(body));
+    }
+    let request = web_sys::Request::new_with_str_and_init(&spec.url, &opts)?;
+    for (name, value) in &spec.headers {
+        request.headers().set(name, value)?;
     }
 
-    let window = web_sys::window().unwrap();
+    let window = web_sys::window().ok_or("no window.")?;
     let resp_value =
         wasm_bindgen_futures::JsFuture::from(window.fetch_with_request(&request)).await?;
 
     // `resp_value` is a `Response` object.
-    assert!(resp_value.is_instance_of::<web_sys::Response>());
-    let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+    let resp: web_sys::Response = resp_value.dyn_into()?;
+    if !resp.ok() {
+        return Err(prelude::JsValue::from_str(&request::status_error(
+            verb,
+            url,
+            resp.status(),
+        )));
+    }
 
     // Convert this other `Promise` into a rust `Future`.
-    let json = wasm_bindgen_futures::JsFuture::from(resp.json()?).await?;
+    let buffer = wasm_bindgen_futures::JsFuture::from(resp.array_buffer()?).await?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+    common::wire::decode(&bytes).map_err(|err| prelude::JsValue::from_str(&err.to_string()))
+}
 
-    Ok(json)
+/// Get the query string of the page.
+fn get_search() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default()
 }
 
-/// Get a potential list of MMSIs from the query string.
-fn get_query_vessels() -> Vec<i32> {
-    let mut res: Vec<i32> = vec![];
-    let window = web_sys::window().expect("the actual window.");
-    let tmp = window.location().search().unwrap();
-    let search_str = tmp.trim_start_matches('?');
-    let queries: Vec<&str> = search_str.split('&').collect();
-    for part in queries {
-        if part.is_empty() {
-            continue;
+/// Get a potential list of MMSIs from the query string - invalid MMSIs are skipped.
+fn get_query_vessels() -> Vec<common::mmsi::Mmsi> {
+    let (res, errors) = query::vessels(&get_search());
+    for err in errors {
+        web_sys::console::warn_1(&err.into());
+    }
+    res
+}
+
+/// Get the style of the tracks from the query string - e.g. `?style=speed`.
+fn get_query_style() -> style::TrackStyle {
+    query::style(&get_search())
+}
+
+/// Get all vessels with position reports within the bounds.
+async fn get_vessels(
+    bounds: &viewport::Bounds,
+    request_id: &str,
+    signal: &web_sys::AbortSignal,
+) -> Result<common::MMSIList, prelude::JsValue> {
+    // FIXME: need to get this from were the browser loaded this form.
+    let endpoint = request::vessels_url(DATAPORT_ENDPOINT, bounds);
+    do_request(&endpoint, "GET", None::<usize>, request_id, Some(signal)).await
+}
+
+/// Get the details of the vessels - in chunks, each added to the tracks once loaded.
+async fn load_vessels(
+    tracks: &cell::RefCell<Tracks>,
+    vessels: &[common::mmsi::Mmsi],
+    request_id: &str,
+    signal: Option<&web_sys::AbortSignal>,
+) -> Result<(), prelude::JsValue> {
+    // FIXME: figure out async runtime for wasm.
+    for vessel_chunk in vessels.chunks(CHUNK_SIZE) {
+        let res: Option<common::VesselList> = do_request(
+            DATAPORT_ENDPOINT,
+            "POST",
+            common::MMSIList {
+                vessels: Vec::from(vessel_chunk),
+            },
+            request_id,
+            signal,
+        )
+        .await?;
+        if let Some(res) = res {
+            tracks.borrow_mut().add(res.vessels);
         }
-        let kv_list: Vec<&str> = part.split('=').collect();
-        if kv_list.first().copied().unwrap() != "mmsi" {
-            continue;
+    }
+    Ok(())
+}
+
+/// Loads the vessels within the viewport of the map - a load is cancelled once the map moved on.
+struct Loader {
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+    request_id: String,
+    /// Simplifies the tracks once loaded.
+    simplifier: rc::Rc<Simplifier>,
+    /// Controller of the running load.
+    controller: cell::RefCell<Option<web_sys::AbortController>>,
+    /// Handle of the pending (debounced) load.
+    timeout: cell::Cell<Option<i32>>,
+}
+
+impl Loader {
+    fn new(simplifier: rc::Rc<Simplifier>) -> Self {
+        Loader {
+            tracks: simplifier.tracks.clone(),
+            request_id: simplifier.request_id.clone(),
+            simplifier,
+            controller: cell::RefCell::new(None),
+            timeout: cell::Cell::new(None),
         }
-        let mssi_list: Vec<&str> = kv_list.last().copied().unwrap().split(',').collect();
-        for mmsi in mssi_list {
-            res.push(mmsi.parse::<i32>().unwrap());
+    }
+
+    /// Schedules loading the vessels within the bounds - replaces the pending load.
+    fn schedule(self: &rc::Rc<Self>, bounds: viewport::Bounds) -> Result<(), prelude::JsValue> {
+        let window = web_sys::window().expect("the actual window.");
+        if let Some(handle) = self.timeout.take() {
+            window.clear_timeout_with_handle(handle);
+        }
+        let loader = self.clone();
+        let callback = prelude::Closure::once_into_js(move || {
+            loader.timeout.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(err) = loader.load(bounds).await {
+                    show_error("Loading the vessels failed", &err);
+                }
+            });
+        });
+        let handle = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.unchecked_ref(),
+            DEBOUNCE_MS,
+        )?;
+        self.timeout.set(Some(handle));
+        Ok(())
+    }
+
+    /// Drops the vessels outside the bounds & loads the new ones within.
+    async fn load(&self, bounds: viewport::Bounds) -> Result<(), prelude::JsValue> {
+        // the vessels of the running load are no longer relevant.
+        if let Some(controller) = self.controller.take() {
+            controller.abort();
+        }
+        let controller = web_sys::AbortController::new()?;
+        let signal = controller.signal();
+        self.controller.replace(Some(controller));
+
+        self.tracks.borrow_mut().retain(&bounds);
+        let res = async {
+            let list = get_vessels(&bounds, &self.request_id, &signal).await?;
+            let vessels = self.tracks.borrow().store.missing(list.vessels);
+            load_vessels(&self.tracks, &vessels, &self.request_id, Some(&signal)).await
+        }
+        .await;
+        match res {
+            // superseded by a newer load.
+            Err(_) if signal.aborted() => Ok(()),
+            Ok(()) => {
+                self.simplifier.schedule();
+                Ok(())
+            }
+            res => res,
         }
     }
-    res
 }
 
-/// Get all vessels in the area.
-async fn get_vessels() -> common::MMSIList {
-    // FIXME: need to get this from were the browser loaded this form.
-    let endpoint = DATAPORT_ENDPOINT;
-    let json: prelude::JsValue = do_request(endpoint, "GET", None::<usize>)
-        .await
-        .expect("A map of vessels.");
-    let vessels: common::MMSIList = json.into_serde().unwrap();
-    vessels
+/// Returns the padded viewport of the map.
+fn get_bounds(map: &leaflet::Map) -> viewport::Bounds {
+    let bounds = map.getBounds();
+    let (south_west, north_east) = (bounds.getSouthWest(), bounds.getNorthEast());
+    viewport::Bounds::new(
+        south_west.lat(),
+        south_west.lng(),
+        north_east.lat(),
+        north_east.lng(),
+    )
+    .pad(VIEWPORT_PADDING)
+}
+
+/// Loads the vessels within the viewport - again whenever the map is moved or zoomed.
+fn watch_viewport(map: &leaflet::Map, loader: rc::Rc<Loader>) -> Result<(), prelude::JsValue> {
+    let on_move = prelude::Closure::<dyn Fn()>::new({
+        let (map, loader) = (map.clone(), loader.clone());
+        move || {
+            if let Err(err) = loader.schedule(get_bounds(&map)) {
+                show_error("Loading the vessels failed", &err);
+            }
+        }
+    });
+    map.on("moveend", on_move.as_ref());
+    on_move.forget();
+    loader.schedule(get_bounds(map))
 }
 
 /// add tiles to the leaflet map.
-fn add_tiles(map: &leaflet::Map) {
-    leaflet::TileLayer::new(TILES_URL, &prelude::JsValue::NULL).addTo(map);
-}
-
-/// Add an GPS trace.
-fn add_traces(map: &leaflet::Map, traces: Vec<common::Vessel>) {
-    for vessel in traces {
-        let mut pos = vec![];
-        for item in vessel.coordinates {
-            pos.push(leaflet::LatLng::new(item.1, item.0));
-        }
-        let trace = leaflet::Polyline::new_with_options(
-            pos.iter().map(prelude::JsValue::from).collect(),
-            &prelude::JsValue::from_serde(&PolylineOptions {
-                color: "#ff7900".into(),
-                weight: 1,
-            })
-            .expect("simple line options."),
+fn add_tiles(map: &leaflet::Map) -> leaflet::TileLayer {
+    let tiles = leaflet::TileLayer::new(TILES_URL, &prelude::JsValue::NULL);
+    tiles.addTo(map);
+    tiles
+}
+
+/// Returns a polyline through the coordinates.
+fn new_polyline(
+    coordinates: &[common::Coordinate],
+    color: &str,
+    weight: usize,
+    opacity: f64,
+) -> leaflet::Polyline {
+    let pos = coordinates
+        .iter()
+        .map(|item| prelude::JsValue::from(leaflet::LatLng::new(item.lat, item.lon)))
+        .collect();
+    leaflet::Polyline::new_with_options(
+        pos,
+        &prelude::JsValue::from_serde(&PolylineOptions {
+            color: color.into(),
+            weight,
+            opacity,
+        })
+        .expect("simple line options."),
+    )
+}
+
+/// Returns a marker for the vessel at a position - its symbol reflects the given position report.
+fn new_marker(vessel: &common::Vessel, index: usize, pos: &common::Coordinate) -> leaflet::Marker {
+    let marker = leaflet::Marker::new_with_options(
+        &leaflet::LatLng::new(pos.lat, pos.lon),
+        &prelude::JsValue::from_serde(&MarkerOptions {
+            bubbling_mouse_events: true,
+        })
+        .expect("simple marker options."),
+    );
+    let size = marker::size(vessel.ship_type.category());
+    marker.setIcon(&new_div_icon(
+        &prelude::JsValue::from_serde(&IconOptions {
+            html: marker::svg(vessel, index),
+            class_name: "vessel".into(),
+            icon_size: (size, size),
+            icon_anchor: (size / 2, size / 2),
+            popup_anchor: (0, -(size as i32) / 2),
+        })
+        .expect("simple marker options."),
+    ));
+    marker
+}
+
+/// Returns a marker for a cluster of vessels - showing their number.
+fn new_cluster_marker(cluster: &cluster::Cluster) -> leaflet::Marker {
+    let pos = &cluster.center;
+    let marker = leaflet::Marker::new(&leaflet::LatLng::new(pos.lat, pos.lon));
+    let count = cluster.members.len();
+    let size = cluster::size(count);
+    marker.setIcon(&new_div_icon(
+        &prelude::JsValue::from_serde(&IconOptions {
+            html: format!("<div>{}</div>", count),
+            class_name: "cluster".into(),
+            icon_size: (size, size),
+            icon_anchor: (size / 2, size / 2),
+            popup_anchor: (0, -(size as i32) / 2),
+        })
+        .expect("simple marker options."),
+    ));
+    marker
+}
+
+/// Vessel tracks shown on the map - redrawn when the style or zoom level changes or during the
+/// playback.
+struct Tracks {
+    layer: leaflet::LayerGroup,
+    markers: leaflet::LayerGroup,
+    style: style::TrackStyle,
+    store: tracks::Store,
+    /// Zooms in on a clicked cluster.
+    on_cluster: prelude::Closure<dyn Fn(leaflet::MouseEvent)>,
+    /// Called once vessels were added or removed.
+    on_change: Vec<rc::Rc<dyn Fn()>>,
+}
+
+impl Tracks {
+    fn new(map: &leaflet::Map, style: style::TrackStyle) -> Self {
+        let layer = leaflet::LayerGroup::new();
+        layer.addTo(map);
+        let markers = leaflet::LayerGroup::new();
+        markers.addTo(map);
+        let on_cluster = prelude::Closure::<dyn Fn(leaflet::MouseEvent)>::new({
+            let map = map.clone();
+            move |event: leaflet::MouseEvent| {
+                map.setView(&event.latlng(), map.getZoom() + CLUSTER_ZOOM_STEP)
+            }
+        });
+        Tracks {
+            layer,
+            markers,
+            style,
+            store: tracks::Store::new(map.getZoom()),
+            on_cluster,
+            on_change: vec![],
+        }
+    }
+
+    /// Adds & draws the tracks & markers of the vessels - vessels already shown are skipped.
+    fn add(&mut self, vessels: Vec<common::Vessel>) {
+        let newest = self.store.newest;
+        let added = self.store.add(vessels, js_sys::Date::parse);
+        // all ages change with a newer reference.
+        if self.style == style::TrackStyle::Age && self.store.newest != newest {
+            self.redraw();
+        } else {
+            added
+                .into_iter()
+                .filter_map(|mmsi| self.store.get(mmsi))
+                .for_each(|track| self.draw(track));
+            self.draw_markers();
+        }
+        self.changed();
+    }
+
+    /// Calls the change callbacks - once the tracks are no longer borrowed.
+    fn changed(&self) {
+        for on_change in self.on_change.iter().cloned() {
+            wasm_bindgen_futures::spawn_local(async move { on_change() });
+        }
+    }
+
+    /// Shows only the vessels selected by the filter.
+    fn set_filter(&mut self, filter: search::Filter) {
+        if self.store.filter != filter {
+            self.store.filter = filter;
+            self.redraw();
+        }
+    }
+
+    /// Highlights a vessel.
+    fn select(&mut self, mmsi: Option<common::mmsi::Mmsi>) {
+        self.store.selected = mmsi;
+        self.redraw();
+    }
+
+    /// Changes the style & redraws all tracks.
+    fn set_style(&mut self, style: style::TrackStyle) {
+        self.style = style;
+        self.redraw();
+    }
+
+    /// Removes the vessels without position reports within the bounds.
+    fn retain(&mut self, bounds: &viewport::Bounds) {
+        if self.store.retain(bounds) {
+            self.redraw();
+            self.changed();
+        }
+    }
+
+    /// Shows the vessels at a time during the playback - or the full tracks if None.
+    fn set_playback(&mut self, playback: Option<(f64, f64)>) {
+        self.store.playback = playback;
+        self.redraw();
+    }
+
+    /// Changes the zoom level - tracks simplified for another level are shown in full until they
+    /// are simplified again.
+    fn set_zoom(&mut self, zoom: f64) {
+        self.store.zoom = zoom;
+        self.redraw();
+    }
+
+    /// Sets the simplified paths of the vessels for a zoom level & redraws all tracks.
+    fn set_simplified(
+        &mut self,
+        level: u32,
+        paths: Vec<(common::mmsi::Mmsi, Option<Vec<common::Coordinate>>)>,
+    ) {
+        self.store.set_simplified(level, paths);
+        self.redraw();
+    }
+
+    fn redraw(&self) {
+        self.layer.clearLayers();
+        self.store.iter().for_each(|track| self.draw(track));
+        self.draw_markers();
+    }
+
+    fn draw(&self, track: &tracks::Track) {
+        if !self.store.filter.matches(&track.vessel) {
+            return;
+        }
+        match self.store.playback {
+            Some((time, trail)) => self.draw_trail(track, time, trail),
+            None => self.draw_track(track),
+        }
+    }
+
+    /// Draws the track of a single vessel - as a polyline per segment; simplified if available
+    /// for the current zoom level.
+    fn draw_track(&self, track: &tracks::Track) {
+        let simplified = self.store.simplified(track);
+        let (vessel, times) = simplified.as_ref().map_or(
+            (&track.vessel, track.times.as_slice()),
+            |(vessel, times)| (vessel, times.as_slice()),
         );
-        trace.addTo(map);
-
-        // popup marker.
-        let marker = leaflet::Marker::new(pos.last().unwrap());
-        marker.setIcon(&leaflet::Icon::new(
-            &prelude::JsValue::from_serde(&IconOptions {
-                icon_url: "marker.png".into(),
-                icon_size: (16, 16),
-                icon_anchor: (8, 16),
-                popup_anchor: (0, -8),
+        let weight = style::weight(vessel.ship_type.category());
+        if self.store.selected == Some(vessel.mmsi) {
+            new_polyline(&vessel.coordinates, HIGHLIGHT_COLOR, weight + 6, 0.8)
+                .addTo_LayerGroup(&self.layer);
+        }
+        for segment in style::segments(vessel, self.style, times, self.store.newest) {
+            new_polyline(
+                &vessel.coordinates[segment.from..=segment.to],
+                segment.color,
+                weight,
+                segment.opacity,
+            )
+            .addTo_LayerGroup(&self.layer);
+        }
+    }
+
+    /// Draws the trailing track of a single vessel.
+    fn draw_trail(&self, track: &tracks::Track, time: f64, trail: f64) {
+        let category = track.vessel.ship_type.category();
+        let coordinates = playback::trail(&track.vessel.coordinates, &track.times, time, trail);
+        if coordinates.is_empty() {
+            return;
+        }
+        new_polyline(
+            &coordinates,
+            style::category_color(category),
+            style::weight(category),
+            1.0,
+        )
+        .addTo_LayerGroup(&self.layer);
+    }
+
+    /// Draws the markers of the vessels - clustered at low zoom levels; markers have a popup unless
+    /// playing back.
+    fn draw_markers(&self) {
+        self.markers.clearLayers();
+        let positions = self.store.positions();
+        let zoom = self.store.zoom;
+        let clusters = if zoom < cluster::MAX_ZOOM {
+            let coordinates: Vec<common::Coordinate> =
+                positions.iter().map(|(_, _, pos)| *pos).collect();
+            cluster::cluster(&coordinates, zoom)
+        } else {
+            (0..positions.len())
+                .map(|i| cluster::Cluster {
+                    center: positions[i].2,
+                    members: vec![i],
+                })
+                .collect()
+        };
+        for item in clusters {
+            if let [member] = item.members[..] {
+                let (track, index, pos) = &positions[member];
+                let marker = new_marker(&track.vessel, *index, pos);
+                if self.store.playback.is_none() {
+                    leaflet::Layer::bindPopup(
+                        &marker,
+                        &prelude::JsValue::from_str(&popup::html(&track.vessel)),
+                        &prelude::JsValue::NULL,
+                    );
+                }
+                marker.addTo_LayerGroup(&self.markers);
+            } else {
+                let marker = new_cluster_marker(&item);
+                marker.on("click", self.on_cluster.as_ref());
+                marker.addTo_LayerGroup(&self.markers);
+            }
+        }
+    }
+}
+
+/// Path simplifications for the current zoom level - a run is cancelled when the zoom level
+/// changes again.
+struct Simplifier {
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+    request_id: String,
+    /// Controller of the running simplification.
+    controller: cell::RefCell<Option<web_sys::AbortController>>,
+}
+
+impl Simplifier {
+    fn new(tracks: rc::Rc<cell::RefCell<Tracks>>, request_id: String) -> Self {
+        Simplifier {
+            tracks,
+            request_id,
+            controller: cell::RefCell::new(None),
+        }
+    }
+
+    /// Starts simplifying the tracks not simplified for the current zoom level yet.
+    fn schedule(self: &rc::Rc<Self>) {
+        let simplifier = self.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(err) = simplifier.run().await {
+                show_error("Simplifying the tracks failed", &err);
+            }
+        });
+    }
+
+    async fn run(&self) -> Result<(), prelude::JsValue> {
+        if let Some(controller) = self.controller.take() {
+            controller.abort();
+        }
+        let (level, (mmsis, tracks)) = {
+            let tracks = &self.tracks.borrow().store;
+            (tracks.level(), tracks.unsimplified())
+        };
+        if level >= simplify::MAX_ZOOM || tracks.is_empty() {
+            return Ok(());
+        }
+        let controller = web_sys::AbortController::new()?;
+        let signal = controller.signal();
+        self.controller.replace(Some(controller));
+
+        // one request per batch of tracks.
+        for batch in simplify::batches(&tracks) {
+            let res: Result<common::SimplifyBatchOut, _> = do_request(
+                PATH_FUNCTION_ENDPOINT,
+                "POST",
+                simplify::batch_in(level, &tracks[batch.clone()]),
+                &self.request_id,
+                Some(&signal),
+            )
+            .await;
+            // superseded by a newer zoom level.
+            if signal.aborted() {
+                return Ok(());
+            }
+            // tracks which could not be simplified are shown in full.
+            let paths = simplify::batch_out(&mmsis[batch], res.ok());
+            self.tracks.borrow_mut().set_simplified(level, paths);
+        }
+        Ok(())
+    }
+}
+
+/// Updates the tracks once the zoom level changed.
+fn watch_zoom(map: &leaflet::Map, simplifier: rc::Rc<Simplifier>) {
+    let on_zoom = prelude::Closure::<dyn Fn()>::new({
+        let map = map.clone();
+        move || {
+            simplifier.tracks.borrow_mut().set_zoom(map.getZoom());
+            simplifier.schedule();
+        }
+    });
+    map.on("zoomend", on_zoom.as_ref());
+    on_zoom.forget();
+}
+
+/// Get a document as text - e.g. the GeoJSON of the zones.
+async fn fetch_text(url: &str) -> Result<String, prelude::JsValue> {
+    let window = web_sys::window().ok_or("no window.")?;
+    let resp: web_sys::Response = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !resp.ok() {
+        return Err(prelude::JsValue::from_str(&request::status_error(
+            "GET",
+            url,
+            resp.status(),
+        )));
+    }
+    let text = wasm_bindgen_futures::JsFuture::from(resp.text()?).await?;
+    text.as_string().ok_or_else(|| "no text.".into())
+}
+
+/// Returns the options of an area in a pane - areas do not react to the mouse.
+fn area_options(pane: &str, color: &str, fill_opacity: f64) -> prelude::JsValue {
+    prelude::JsValue::from_serde(&AreaOptions {
+        pane: pane.into(),
+        stroke: pane != HEATMAP_PANE,
+        color: color.into(),
+        weight: 1,
+        fill_color: color.into(),
+        fill_opacity,
+        interactive: false,
+    })
+    .expect("simple area options.")
+}
+
+/// Returns a polygon from its rings - the first one being the outer ring.
+fn new_polygon(rings: &[Vec<common::Coordinate>], color: &str) -> leaflet::Polygon {
+    let rings = rings
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .map(|item| prelude::JsValue::from(leaflet::LatLng::new(item.lat, item.lon)))
+                .collect::<js_sys::Array>()
+                .into()
+        })
+        .collect();
+    leaflet::Polygon::new_with_options(rings, &area_options(ZONES_PANE, color, ZONE_OPACITY))
+}
+
+/// Returns a rectangle covering the bounds.
+fn new_rectangle(bounds: &viewport::Bounds, color: &str) -> leaflet::Rectangle {
+    leaflet::Rectangle::new_with_options(
+        &leaflet::LatLngBounds::new(
+            &leaflet::LatLng::new(bounds.south, bounds.west),
+            &leaflet::LatLng::new(bounds.north, bounds.east),
+        ),
+        &area_options(HEATMAP_PANE, color, HEATMAP_OPACITY),
+    )
+}
+
+/// Get the location of the zones from the query string - e.g. `?zones=/data/zones.geojson`.
+fn get_query_zones() -> String {
+    query::zones(&get_search()).unwrap_or_else(|| ZONES_URL.to_string())
+}
+
+/// Loads & draws the zones of the port - clicking within a zone shows its name & kind unless a
+/// vessel was clicked or a tool is used.
+async fn add_zones(
+    map: &leaflet::Map,
+    layer: &leaflet::LayerGroup,
+    tools: rc::Rc<Tools>,
+    url: &str,
+) -> Result<(), prelude::JsValue> {
+    let text = fetch_text(url).await?;
+    let zones = zones::parse(&text).map_err(|err| prelude::JsValue::from_str(&err.to_string()))?;
+    for zone in &zones {
+        for rings in &zone.polygons {
+            new_polygon(rings, zone.kind.color()).addTo_LayerGroup(layer);
+        }
+    }
+    let on_click = prelude::Closure::<dyn Fn(leaflet::MouseEvent)>::new({
+        let (map, layer) = (map.clone(), layer.clone());
+        move |event: leaflet::MouseEvent| {
+            let clicked_map =
+                prelude::JsValue::from(event.sourceTarget()) == prelude::JsValue::from(&map);
+            if !clicked_map || tools.active() || !map.unchecked_ref::<MapExtra>().has_layer(&layer)
+            {
+                return;
+            }
+            let latlng = event.latlng();
+            let found = zones::find(
+                &zones,
+                &common::Coordinate {
+                    lat: latlng.lat(),
+                    lon: latlng.lng(),
+                },
+            );
+            if found.is_empty() {
+                return;
+            }
+            // names are not to be trusted.
+            let html = found
+                .iter()
+                .map(|item| {
+                    let name = if item.name.trim().is_empty() {
+                        "n/a"
+                    } else {
+                        &item.name
+                    };
+                    format!(
+                        "<strong>{}</strong> ({})",
+                        common::xml::escape(name),
+                        item.kind
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("<br />");
+            let popup = leaflet::popup(&prelude::JsValue::NULL, None);
+            popup.setLatLng(&latlng);
+            popup.setContent(&prelude::JsValue::from_str(&html));
+            popup.openOn(&map);
+        }
+    });
+    map.on("click", on_click.as_ref());
+    on_click.forget();
+    Ok(())
+}
+
+/// Density of the positions of all vessels loaded - only drawn while shown.
+struct Heatmap {
+    map: leaflet::Map,
+    layer: leaflet::LayerGroup,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+}
+
+impl Heatmap {
+    fn draw(&self) {
+        self.layer.clearLayers();
+        if !self.map.unchecked_ref::<MapExtra>().has_layer(&self.layer) {
+            return;
+        }
+        let tracks = self.tracks.borrow();
+        let cells = heatmap::density(
+            tracks
+                .store
+                .iter()
+                .flat_map(|track| track.vessel.coordinates.iter()),
+            self.map.getZoom(),
+        );
+        let max = cells
+            .iter()
+            .map(|item| item.count)
+            .max()
+            .unwrap_or_default();
+        for item in cells {
+            new_rectangle(&item.bounds, heatmap::color(item.count, max))
+                .addTo_LayerGroup(&self.layer);
+        }
+    }
+}
+
+/// Adds the heatmap - redrawn when shown, zooming or once vessels were loaded or removed.
+fn add_heatmap(map: &leaflet::Map, tracks: rc::Rc<cell::RefCell<Tracks>>) -> leaflet::LayerGroup {
+    let heatmap = rc::Rc::new(Heatmap {
+        map: map.clone(),
+        layer: leaflet::LayerGroup::new(),
+        tracks: tracks.clone(),
+    });
+    let on_redraw = prelude::Closure::<dyn Fn()>::new({
+        let heatmap = heatmap.clone();
+        move || heatmap.draw()
+    });
+    map.on("zoomend", on_redraw.as_ref());
+    map.on("overlayadd", on_redraw.as_ref());
+    on_redraw.forget();
+    tracks.borrow_mut().on_change.push({
+        let heatmap = heatmap.clone();
+        rc::Rc::new(move || heatmap.draw())
+    });
+    heatmap.layer.clone()
+}
+
+/// Adds the control toggling the layers.
+fn add_layers(
+    map: &leaflet::Map,
+    tiles: &leaflet::TileLayer,
+    overlays: &[(&str, &leaflet::Layer)],
+) -> Result<(), prelude::JsValue> {
+    let base_layers = js_sys::Object::new();
+    js_sys::Reflect::set(&base_layers, &"OpenStreetMap".into(), tiles)?;
+    let items = js_sys::Object::new();
+    for (name, layer) in overlays {
+        js_sys::Reflect::set(&items, &(*name).into(), layer)?;
+    }
+    new_layers_control(
+        &base_layers,
+        &items,
+        &prelude::JsValue::from_serde(&LayersOptions {
+            position: "topleft".into(),
+        })
+        .expect("simple layers options."),
+    )
+    .addTo(map);
+    Ok(())
+}
+
+/// Measurement tools - the picked points & vessels with the drawings & results of the tool.
+struct Tools {
+    map: leaflet::Map,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+    layer: leaflet::LayerGroup,
+    output: web_sys::Element,
+    tool: cell::Cell<tools::Tool>,
+    picks: cell::RefCell<Vec<tools::Pick>>,
+}
+
+impl Tools {
+    /// Returns true if clicks on the map are used by a tool.
+    fn active(&self) -> bool {
+        self.tool.get() != tools::Tool::Off
+    }
+
+    fn set_tool(&self, tool: tools::Tool) {
+        self.tool.set(tool);
+        self.clear();
+    }
+
+    /// Removes the picks & drawings.
+    fn clear(&self) {
+        self.picks.borrow_mut().clear();
+        self.layer.clearLayers();
+        self.show(&[self.tool.get().hint().to_string()]);
+    }
+
+    /// Shows the results - as text, as vessel names are not to be trusted.
+    fn show(&self, lines: &[String]) {
+        self.output.set_inner_html("");
+        let Some(document) = self.output.owner_document() else {
+            return;
+        };
+        for line in lines.iter().filter(|item| !item.is_empty()) {
+            if let Ok(item) = document.create_element("div") {
+                item.set_text_content(Some(line));
+                self.output.append_child(&item).ok();
+            }
+        }
+    }
+
+    /// Applies the tool to the clicked position.
+    fn click(&self, clicked: common::Coordinate) {
+        let pick = tools::pick(&self.tracks.borrow().store, clicked, SNAP_PX);
+        if !tools::apply(self.tool.get(), &mut self.picks.borrow_mut(), pick) {
+            return;
+        }
+        self.layer.clearLayers();
+        match self.tool.get() {
+            tools::Tool::Off => {}
+            tools::Tool::Measure => self.draw_measure(),
+            tools::Tool::Rings => self.draw_rings(),
+            tools::Tool::Cpa => self.draw_cpa(),
+        }
+    }
+
+    /// Draws a point of a tool.
+    fn draw_point(&self, pos: &common::Coordinate) {
+        leaflet::CircleMarker::new_with_options(
+            &leaflet::LatLng::new(pos.lat, pos.lon),
+            &prelude::JsValue::from_serde(&PointOptions {
+                radius: 4.0,
+                color: TOOL_COLOR.into(),
+                interactive: false,
             })
-            .expect("simple marker options."),
+            .expect("simple point options."),
+        )
+        .addTo_LayerGroup(&self.layer);
+    }
+
+    /// Draws a line of a tool.
+    fn draw_line(&self, coordinates: &[common::Coordinate]) {
+        new_polyline(coordinates, TOOL_COLOR, 2, 1.0).addTo_LayerGroup(&self.layer);
+    }
+
+    /// Draws the legs between the picks - with their distance & bearing.
+    fn draw_measure(&self) {
+        let picks = self.picks.borrow();
+        let coordinates: Vec<common::Coordinate> = picks.iter().map(|item| item.position).collect();
+        for item in &coordinates {
+            self.draw_point(item);
+        }
+        self.draw_line(&coordinates);
+        self.show(&tools::legs(&picks));
+    }
+
+    /// Draws the range rings around the pick.
+    fn draw_rings(&self) {
+        let picks = self.picks.borrow();
+        let Some(pick) = picks.first() else {
+            return;
+        };
+        self.draw_point(&pick.position);
+        for radius in measure::RING_RADII {
+            self.draw_line(&measure::ring(
+                &pick.position,
+                radius * measure::NAUTICAL_MILE,
+            ));
+        }
+        self.show(&[tools::rings(pick)]);
+    }
+
+    /// Draws the tracks of both vessels up to their closest point of approach.
+    fn draw_cpa(&self) {
+        let picks = self.picks.borrow();
+        let [a, b] = &picks[..] else {
+            if let Some(pick) = picks.first() {
+                self.draw_point(&pick.position);
+                self.show(&[format!("{} - click the second vessel.", pick.label(0))]);
+            }
+            return;
+        };
+        let motions: Vec<Option<measure::Motion>> = {
+            let tracks = &self.tracks.borrow().store;
+            [a, b]
+                .iter()
+                .map(|pick| tracks.motion(pick.vessel.as_ref()?.0))
+                .collect()
+        };
+        let [Some(motion_a), Some(motion_b)] = motions[..] else {
+            self.show(&["Course, speed or time of a vessel not available.".to_string()]);
+            return;
+        };
+        let cpa = measure::cpa(&motion_a, &motion_b);
+        self.draw_line(&[motion_a.position, cpa.positions.0]);
+        self.draw_line(&[motion_b.position, cpa.positions.1]);
+        self.draw_line(&[cpa.positions.0, cpa.positions.1]);
+        for item in [cpa.positions.0, cpa.positions.1] {
+            self.draw_point(&item);
+        }
+        self.show(&tools::cpa((a, &motion_a), (b, &motion_b), &cpa));
+    }
+}
+
+/// Adds the measurement tools - the selected tool is applied when clicking on the map.
+fn add_tools(
+    map: &leaflet::Map,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+) -> Result<rc::Rc<Tools>, prelude::JsValue> {
+    let window = web_sys::window().expect("the actual window.");
+    let document = window.document().expect("a document.");
+    let container: web_sys::HtmlElement = document.create_element("div")?.dyn_into()?;
+    container.set_class_name("tools");
+    let select: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    for (_, value, label) in tools::Tool::ALL {
+        add_option(&document, &select, value, label)?;
+    }
+    let clear: web_sys::HtmlButtonElement = document.create_element("button")?.dyn_into()?;
+    clear.set_inner_html("&#10005;");
+    clear.set_title("Clear");
+    let output = document.create_element("div")?;
+    container.append_child(&select)?;
+    container.append_child(&clear)?;
+    container.append_child(&output)?;
+    disable_click_propagation(&container);
+
+    let layer = leaflet::LayerGroup::new();
+    layer.addTo(map);
+    let tools = rc::Rc::new(Tools {
+        map: map.clone(),
+        tracks,
+        layer,
+        output,
+        tool: cell::Cell::new(tools::Tool::Off),
+        picks: cell::RefCell::new(vec![]),
+    });
+
+    let on_tool = prelude::Closure::<dyn Fn()>::new({
+        let (tools, select) = (tools.clone(), select.clone());
+        move || {
+            if let Some(tool) = tools::Tool::from_value(&select.value()) {
+                tools.set_tool(tool);
+            }
+        }
+    });
+    select.add_event_listener_with_callback("change", on_tool.as_ref().unchecked_ref())?;
+    on_tool.forget();
+
+    let on_clear = prelude::Closure::<dyn Fn()>::new({
+        let tools = tools.clone();
+        move || tools.clear()
+    });
+    clear.add_event_listener_with_callback("click", on_clear.as_ref().unchecked_ref())?;
+    on_clear.forget();
+
+    let on_click = prelude::Closure::<dyn Fn(leaflet::MouseEvent)>::new({
+        let tools = tools.clone();
+        move |event: leaflet::MouseEvent| {
+            let latlng = event.latlng();
+            tools.click(common::Coordinate {
+                lat: latlng.lat(),
+                lon: latlng.lng(),
+            });
+        }
+    });
+    tools.map.on("click", on_click.as_ref());
+    on_click.forget();
+
+    let control = new_control(
+        &prelude::JsValue::from_serde(&ControlOptions {
+            position: "topleft".into(),
+        })
+        .expect("simple control options."),
+    );
+    let on_add =
+        prelude::Closure::<dyn Fn() -> prelude::JsValue>::new(move || container.clone().into());
+    js_sys::Reflect::set(&control, &"onAdd".into(), on_add.as_ref())?;
+    on_add.forget();
+    control.addTo(map);
+    Ok(tools)
+}
+
+/// Returns the HTML of the legend entries - only uses our own labels.
+fn legend_html(style: style::TrackStyle) -> String {
+    let mut res = String::new();
+    for (label, color, opacity) in style::legend(style) {
+        res.push_str(&format!(
+            "<div><i style=\"background: {}; opacity: {}\"></i>{}</div>",
+            color, opacity, label
         ));
-        let text: String = format!(
-            "<strong><u>{}</u></strong> &raquo; <em><a href=\"?mmsi={}\" target=\"_blank\">show</a></em><br />\
-            <strong>MMSI</strong>: <a href=\"https://www.marinetraffic.com/en/ais/details/ships/mmsi:{}\" target=\"_blank\">{}</a><br />\
-            <strong>Speed</strong>: {}<br />\
-            <strong>Heading</strong>:{}\
-            <br /><strong>Type</strong>:{}\
-            <br /><strong>Timestamp</strong>:{}, <br />\
-            <strong>Status</strong>:{}, <br />\
-            <strong>Destination</strong>:{}",
-            vessel.name,
-            vessel.mmsi,
-            vessel.mmsi,
-            vessel.mmsi,
-            vessel.speeds.last().copied().unwrap(),
-            vessel.headings.last().copied().unwrap(),
-            vessel.ship_type, vessel.timestamps.last().cloned().unwrap(),
-            vessel.statuses.last().cloned().unwrap(),
-            vessel.destinations.last().cloned().unwrap(),
-        );
-        leaflet::Layer::bindPopup(
-            &marker,
-            &prelude::JsValue::from_str(&*text),
-            &prelude::JsValue::NULL,
-        );
-        marker.addTo(map);
+    }
+    res.push_str("<div><small>Line weight: vessel size (by type)</small></div>");
+    res
+}
+
+/// Adds the legend with a selection of the track style.
+fn add_legend(
+    map: &leaflet::Map,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+) -> Result<(), prelude::JsValue> {
+    let document = web_sys::window()
+        .expect("the actual window.")
+        .document()
+        .expect("a document.");
+    let container: web_sys::HtmlElement = document.create_element("div")?.dyn_into()?;
+    container.set_class_name("legend");
+    let select: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    for item in style::TrackStyle::ALL {
+        let option = document.create_element("option")?;
+        option.set_attribute("value", item.key())?;
+        option.set_text_content(Some(item.label()));
+        select.append_child(&option)?;
+    }
+    let current = tracks.borrow().style;
+    select.set_value(current.key());
+    let entries = document.create_element("div")?;
+    entries.set_inner_html(&legend_html(current));
+    container.append_child(&select)?;
+    container.append_child(&entries)?;
+    disable_click_propagation(&container);
+
+    let on_change = prelude::Closure::<dyn Fn()>::new({
+        let select = select.clone();
+        move || {
+            if let Ok(style) = select.value().parse() {
+                tracks.borrow_mut().set_style(style);
+                entries.set_inner_html(&legend_html(style));
+            }
+        }
+    });
+    select.add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())?;
+    on_change.forget();
+
+    let control = new_control(
+        &prelude::JsValue::from_serde(&ControlOptions {
+            position: "bottomright".into(),
+        })
+        .expect("simple control options."),
+    );
+    let on_add =
+        prelude::Closure::<dyn Fn() -> prelude::JsValue>::new(move || container.clone().into());
+    js_sys::Reflect::set(&control, &"onAdd".into(), on_add.as_ref())?;
+    on_add.forget();
+    control.addTo(map);
+    Ok(())
+}
+
+/// Adds the playback control - a time slider with play/pause, speed & trail length selection.
+///
+/// Using the control switches the map to show the vessels at the playback time with their
+/// trailing tracks; the stop button shows the full tracks again.
+fn add_playback(
+    map: &leaflet::Map,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+) -> Result<(), prelude::JsValue> {
+    let window = web_sys::window().expect("the actual window.");
+    let document = window.document().expect("a document.");
+    let container: web_sys::HtmlElement = document.create_element("div")?.dyn_into()?;
+    container.set_class_name("playback");
+    let play: web_sys::HtmlButtonElement = document.create_element("button")?.dyn_into()?;
+    play.set_inner_html("&#9654;");
+    play.set_title("Play/pause");
+    let slider: web_sys::HtmlInputElement = document.create_element("input")?.dyn_into()?;
+    slider.set_type("range");
+    slider.set_min("0");
+    slider.set_max(&SLIDER_STEPS.to_string());
+    slider.set_value(&SLIDER_STEPS.to_string());
+    let label = document.create_element("span")?;
+    label.set_text_content(Some("full tracks"));
+    let speed: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    for item in playback::SPEEDS {
+        let option = document.create_element("option")?;
+        option.set_attribute("value", &item.to_string())?;
+        option.set_text_content(Some(&format!("{}x", item)));
+        speed.append_child(&option)?;
+    }
+    speed.set_value(&playback::DEFAULT_SPEED.to_string());
+    let trail: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    for item in playback::TRAILS {
+        let option = document.create_element("option")?;
+        option.set_attribute("value", &item.to_string())?;
+        option.set_text_content(Some(&format!("{} min", item)));
+        trail.append_child(&option)?;
+    }
+    trail.set_value(&playback::DEFAULT_TRAIL.to_string());
+    let stop: web_sys::HtmlButtonElement = document.create_element("button")?.dyn_into()?;
+    stop.set_inner_html("&#10005;");
+    stop.set_title("Show full tracks");
+    container.append_child(&play)?;
+    container.append_child(&slider)?;
+    container.append_child(&label)?;
+    container.append_child(&speed)?;
+    container.append_child(&trail)?;
+    container.append_child(&stop)?;
+    disable_click_propagation(&container);
+
+    let clock = rc::Rc::new(cell::RefCell::new(playback::Clock::new(0.0, 0.0)));
+    let trail_ms = rc::Rc::new(cell::Cell::new(playback::DEFAULT_TRAIL as f64 * 60_000.0));
+
+    // applies an action to the clock & shows the vessels at its time - the time range grows as
+    // more vessels are loaded.
+    let update = rc::Rc::new({
+        let (clock, trail_ms, tracks) = (clock.clone(), trail_ms.clone(), tracks.clone());
+        let (play, slider, label) = (play.clone(), slider.clone(), label.clone());
+        move |action: &dyn Fn(&mut playback::Clock)| {
+            let Some((start, end)) = tracks.borrow().store.range() else {
+                return;
+            };
+            let mut clock = clock.borrow_mut();
+            clock.set_range(start, end);
+            action(&mut clock);
+            slider.set_value(&(clock.fraction() * SLIDER_STEPS).round().to_string());
+            label.set_text_content(Some(&playback::format_time(clock.time)));
+            play.set_inner_html(if clock.playing {
+                "&#10074;&#10074;"
+            } else {
+                "&#9654;"
+            });
+            tracks
+                .borrow_mut()
+                .set_playback(Some((clock.time, trail_ms.get())));
+        }
+    });
+
+    let on_play = prelude::Closure::<dyn Fn()>::new({
+        let update = update.clone();
+        move || {
+            update(&|clock| {
+                if clock.playing {
+                    clock.pause()
+                } else {
+                    clock.play()
+                }
+            })
+        }
+    });
+    play.add_event_listener_with_callback("click", on_play.as_ref().unchecked_ref())?;
+    on_play.forget();
+
+    let on_seek = prelude::Closure::<dyn Fn()>::new({
+        let (update, slider) = (update.clone(), slider.clone());
+        move || {
+            let fraction = slider.value_as_number() / SLIDER_STEPS;
+            update(&|clock| {
+                clock.pause();
+                clock.seek(fraction);
+            })
+        }
+    });
+    slider.add_event_listener_with_callback("input", on_seek.as_ref().unchecked_ref())?;
+    on_seek.forget();
+
+    let on_speed = prelude::Closure::<dyn Fn()>::new({
+        let (clock, speed) = (clock.clone(), speed.clone());
+        move || {
+            if let Ok(value) = speed.value().parse::<f64>() {
+                clock.borrow_mut().speed = value;
+            }
+        }
+    });
+    speed.add_event_listener_with_callback("change", on_speed.as_ref().unchecked_ref())?;
+    on_speed.forget();
+
+    let on_trail = prelude::Closure::<dyn Fn()>::new({
+        let (update, tracks, trail) = (update.clone(), tracks.clone(), trail.clone());
+        move || {
+            if let Ok(value) = trail.value().parse::<f64>() {
+                trail_ms.set(value * 60_000.0);
+                // only redraw if playing back.
+                if tracks.borrow().store.playback.is_some() {
+                    update(&|_| {});
+                }
+            }
+        }
+    });
+    trail.add_event_listener_with_callback("change", on_trail.as_ref().unchecked_ref())?;
+    on_trail.forget();
+
+    let on_stop = prelude::Closure::<dyn Fn()>::new({
+        let (clock, play, slider, label) = (clock.clone(), play.clone(), slider.clone(), label);
+        move || {
+            clock.borrow_mut().pause();
+            play.set_inner_html("&#9654;");
+            slider.set_value(&SLIDER_STEPS.to_string());
+            label.set_text_content(Some("full tracks"));
+            tracks.borrow_mut().set_playback(None);
+        }
+    });
+    stop.add_event_listener_with_callback("click", on_stop.as_ref().unchecked_ref())?;
+    on_stop.forget();
+
+    let on_tick = prelude::Closure::<dyn Fn()>::new(move || {
+        if clock.borrow().playing {
+            update(&|clock| clock.tick(TICK_MS));
+        }
+    });
+    window.set_interval_with_callback_and_timeout_and_arguments_0(
+        on_tick.as_ref().unchecked_ref(),
+        TICK_MS as i32,
+    )?;
+    on_tick.forget();
+
+    let control = new_control(
+        &prelude::JsValue::from_serde(&ControlOptions {
+            position: "bottomleft".into(),
+        })
+        .expect("simple control options."),
+    );
+    let on_add =
+        prelude::Closure::<dyn Fn() -> prelude::JsValue>::new(move || container.clone().into());
+    js_sys::Reflect::set(&control, &"onAdd".into(), on_add.as_ref())?;
+    on_add.forget();
+    control.addTo(map);
+    Ok(())
+}
+
+/// Appends an option to a select or datalist element.
+fn add_option(
+    document: &web_sys::Document,
+    parent: &web_sys::Element,
+    value: &str,
+    text: &str,
+) -> Result<(), prelude::JsValue> {
+    let option = document.create_element("option")?;
+    option.set_attribute("value", value)?;
+    option.set_text_content(Some(text));
+    parent.append_child(&option)?;
+    Ok(())
+}
+
+/// Creates an input element.
+fn new_input(
+    document: &web_sys::Document,
+    kind: &str,
+    placeholder: &str,
+) -> Result<web_sys::HtmlInputElement, prelude::JsValue> {
+    let input: web_sys::HtmlInputElement = document.create_element("input")?.dyn_into()?;
+    input.set_type(kind);
+    input.set_placeholder(placeholder);
+    Ok(input)
+}
+
+/// Adds the search & filter panel - the results list highlights & zooms to the clicked vessel.
+fn add_search(
+    map: &leaflet::Map,
+    tracks: rc::Rc<cell::RefCell<Tracks>>,
+) -> Result<(), prelude::JsValue> {
+    let window = web_sys::window().expect("the actual window.");
+    let document = window.document().expect("a document.");
+    let container: web_sys::HtmlElement = document.create_element("div")?.dyn_into()?;
+    container.set_class_name("panel");
+    let text = new_input(&document, "search", "Name or MMSI")?;
+    text.set_attribute("list", "suggestions")?;
+    let suggestions = document.create_element("datalist")?;
+    suggestions.set_id("suggestions");
+    let category: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    add_option(&document, &category, "", "All types")?;
+    for (i, item) in common::ais::ShipCategory::ALL.iter().enumerate() {
+        add_option(&document, &category, &i.to_string(), &item.to_string())?;
+    }
+    let status: web_sys::HtmlSelectElement = document.create_element("select")?.dyn_into()?;
+    add_option(&document, &status, "", "All statuses")?;
+    for item in (0..=15).filter_map(common::ais::NavStatus::from_code) {
+        add_option(
+            &document,
+            &status,
+            &item.code().to_string(),
+            &item.to_string(),
+        )?;
+    }
+    let min_speed = new_input(&document, "number", "Min kn")?;
+    let max_speed = new_input(&document, "number", "Max kn")?;
+    for item in [&min_speed, &max_speed] {
+        item.set_min("0");
+        item.set_step("0.1");
+    }
+    let destination = new_input(&document, "search", "Destination")?;
+    let count = document.create_element("div")?;
+    let results = document.create_element("ul")?;
+    for item in [
+        text.as_ref(),
+        suggestions.as_ref(),
+        category.as_ref(),
+        status.as_ref(),
+        min_speed.as_ref(),
+        max_speed.as_ref(),
+        destination.as_ref(),
+        &count,
+        &results,
+    ] {
+        container.append_child(item)?;
+    }
+    disable_click_propagation(&container);
+    disable_scroll_propagation(&container);
+
+    let inputs: [web_sys::EventTarget; 6] = [
+        text.clone().into(),
+        category.clone().into(),
+        status.clone().into(),
+        min_speed.clone().into(),
+        max_speed.clone().into(),
+        destination.clone().into(),
+    ];
+
+    // applies the filter & lists the vessels matching it.
+    let update: rc::Rc<dyn Fn()> = rc::Rc::new({
+        let tracks = tracks.clone();
+        let (count, results) = (count.clone(), results.clone());
+        move || {
+            let speed = |input: &web_sys::HtmlInputElement| input.value().parse::<f64>().ok();
+            let filter = search::Filter {
+                text: text.value(),
+                category: category
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| common::ais::ShipCategory::ALL.get(i).copied()),
+                status: status.value().parse::<common::ais::NavStatus>().ok(),
+                min_speed: speed(&min_speed),
+                max_speed: speed(&max_speed),
+                destination: destination.value(),
+            };
+            let mut tracks = tracks.borrow_mut();
+            let vessels: Vec<&common::Vessel> =
+                tracks.store.iter().map(|track| &track.vessel).collect();
+            let found = search::search(vessels.iter().copied(), &filter, RESULTS_LIMIT);
+            let matching = vessels.iter().filter(|item| filter.matches(item)).count();
+            count.set_text_content(Some(&search::summary(matching, vessels.len(), &filter)));
+            // names are set as text - they are not to be trusted.
+            suggestions.set_inner_html("");
+            results.set_inner_html("");
+            for (n, i) in found.into_iter().enumerate() {
+                let vessel = vessels[i];
+                let mmsi = vessel.mmsi.to_string();
+                if n < SUGGESTIONS_LIMIT {
+                    let _ = add_option(&document, &suggestions, &vessel.name, &mmsi);
+                }
+                let Ok(item) = document.create_element("li") else {
+                    continue;
+                };
+                let _ = item.set_attribute("data-mmsi", &mmsi);
+                if tracks.store.selected == Some(vessel.mmsi) {
+                    item.set_class_name("selected");
+                }
+                item.set_text_content(Some(&format!(
+                    "{} ({})",
+                    vessel.name,
+                    vessel.ship_type.category()
+                )));
+                let _ = results.append_child(&item);
+            }
+            tracks.set_filter(filter);
+        }
+    });
+    for item in inputs {
+        let on_input = prelude::Closure::<dyn Fn()>::new({
+            let update = update.clone();
+            move || update()
+        });
+        item.add_event_listener_with_callback("input", on_input.as_ref().unchecked_ref())?;
+        on_input.forget();
+    }
+
+    let on_select = prelude::Closure::<dyn Fn(web_sys::Event)>::new({
+        let (map, tracks, update) = (map.clone(), tracks.clone(), update.clone());
+        move |event: web_sys::Event| {
+            let Some(mmsi) = event
+                .target()
+                .and_then(|item| item.dyn_into::<web_sys::Element>().ok())
+                .and_then(|item| item.closest("li").ok().flatten())
+                .and_then(|item| item.get_attribute("data-mmsi"))
+                .and_then(|item| item.parse::<common::mmsi::Mmsi>().ok())
+            else {
+                return;
+            };
+            let bounds = {
+                let mut tracks = tracks.borrow_mut();
+                tracks.select(Some(mmsi));
+                tracks
+                    .store
+                    .get(mmsi)
+                    .and_then(|track| viewport::Bounds::around(&track.vessel.coordinates))
+            };
+            if let Some(bounds) = bounds {
+                map.fitBoundsWithOptions(
+                    &leaflet::LatLngBounds::new(
+                        &leaflet::LatLng::new(bounds.south, bounds.west),
+                        &leaflet::LatLng::new(bounds.north, bounds.east),
+                    ),
+                    &prelude::JsValue::from_serde(&FitOptions {
+                        max_zoom: SELECTED_MAX_ZOOM,
+                    })
+                    .expect("simple fit options."),
+                );
+            }
+            update();
+        }
+    });
+    results.add_event_listener_with_callback("click", on_select.as_ref().unchecked_ref())?;
+    on_select.forget();
+
+    // the list follows the vessels loaded.
+    tracks.borrow_mut().on_change.push(update.clone());
+    update();
+
+    let control = new_control(
+        &prelude::JsValue::from_serde(&ControlOptions {
+            position: "topright".into(),
+        })
+        .expect("simple control options."),
+    );
+    let on_add =
+        prelude::Closure::<dyn Fn() -> prelude::JsValue>::new(move || container.clone().into());
+    js_sys::Reflect::set(&control, &"onAdd".into(), on_add.as_ref())?;
+    on_add.forget();
+    control.addTo(map);
+    Ok(())
+}
+
+/// Returns a readable message for an error - e.g. of a failed fetch.
+fn error_message(err: &prelude::JsValue) -> String {
+    if let Some(message) = err.as_string() {
+        message
+    } else if let Some(err) = err.dyn_ref::<js_sys::Error>() {
+        String::from(err.message())
+    } else {
+        format!("{:?}", err)
+    }
+}
+
+/// Shows an error in the banner on top of the map - only the latest few are kept; click the
+/// banner to dismiss it.
+fn show_error(context: &str, err: &prelude::JsValue) {
+    web_sys::console::error_1(err);
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let banner = match document.get_element_by_id("error") {
+        Some(banner) => banner,
+        None => {
+            let Ok(banner) = document.create_element("div") else {
+                return;
+            };
+            banner.set_id("error");
+            banner.set_class_name("error");
+            banner.set_attribute("title", "Click to dismiss").ok();
+            let on_click = prelude::Closure::<dyn Fn()>::new({
+                let banner = banner.clone();
+                move || {
+                    banner.remove();
+                }
+            });
+            banner
+                .add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())
+                .ok();
+            on_click.forget();
+            if let Some(body) = document.body() {
+                body.append_child(&banner).ok();
+            }
+            banner
+        }
+    };
+    let Ok(line) = document.create_element("div") else {
+        return;
+    };
+    line.set_text_content(Some(&format!("{}: {}", context, error_message(err))));
+    banner.append_child(&line).ok();
+    while banner.child_element_count() > MAX_ERRORS {
+        if let Some(first) = banner.first_element_child() {
+            first.remove();
+        }
     }
 }
 
-/// Called by the javascript part.
-#[prelude::wasm_bindgen(start)]
+/// Called by the javascript part - not when testing, as there is no map.
+#[cfg_attr(not(test), prelude::wasm_bindgen(start))]
 pub async fn main() -> Result<(), prelude::JsValue> {
     panic::set_hook(Box::new(console_error_panic_hook::hook));
 
     // initial map - focus on Rotterdam.
-    let map = leaflet::Map::new("map", &prelude::JsValue::NULL);
+    let map = leaflet::Map::new(
+        "map",
+        &prelude::JsValue::from_serde(&MapOptions {
+            prefer_canvas: true,
+        })
+        .expect("simple map options."),
+    );
     map.setView(&leaflet::LatLng::new(51.9496, 4.1453), 10.0);
-    add_tiles(&map);
+    let tiles = add_tiles(&map);
+    map.unchecked_ref::<MapExtra>().create_pane(ZONES_PANE);
+    map.unchecked_ref::<MapExtra>().create_pane(HEATMAP_PANE);
+    let zones = leaflet::LayerGroup::new();
+    zones.addTo(&map);
+    let tracks = rc::Rc::new(cell::RefCell::new(Tracks::new(&map, get_query_style())));
+    add_legend(&map, tracks.clone())?;
+    add_playback(&map, tracks.clone())?;
+    add_search(&map, tracks.clone())?;
+    let heatmap = add_heatmap(&map, tracks.clone());
+    let tools = add_tools(&map, tracks.clone())?;
+    {
+        let tracks = tracks.borrow();
+        add_layers(
+            &map,
+            &tiles,
+            &[
+                ("Zones", &zones),
+                ("Heatmap", &heatmap),
+                ("Tracks", &tracks.layer),
+                ("Vessels", &tracks.markers),
+            ],
+        )?;
+    }
+    wasm_bindgen_futures::spawn_local({
+        let map = map.clone();
+        async move {
+            if let Err(err) = add_zones(&map, &zones, tools, &get_query_zones()).await {
+                show_error("Loading the zones failed", &err);
+            }
+        }
+    });
 
-    // either there are ships given through the query part of the URI, otherwise we show all.
-    let mut vessels = common::MMSIList {
-        vessels: get_query_vessels(),
-    };
-    if vessels.vessels.is_empty() {
-        vessels = get_vessels().await;
+    // all requests for loading this map share the same identifier.
+    let request_id = web_sys::window()
+        .expect("the actual window.")
+        .crypto()?
+        .random_uuid();
+    let simplifier = rc::Rc::new(Simplifier::new(tracks.clone(), request_id.clone()));
+    watch_zoom(&map, simplifier.clone());
+
+    // either there are ships given through the query part of the URI, otherwise we show the ones
+    // in view.
+    let vessels = get_query_vessels();
+    if vessels.is_empty() {
+        watch_viewport(&map, rc::Rc::new(Loader::new(simplifier)))?;
+    } else {
+        match load_vessels(&tracks, &vessels, &request_id, None).await {
+            Ok(()) => simplifier.schedule(),
+            Err(err) => show_error("Loading the vessels failed", &err),
+        }
     }
 
-    // FIXME: figure out async runtime for wasm.
-    for vessel_chunk in vessels.vessels.chunks(CHUNK_SIZE) {
-        let tmp = do_request(
+    // and ready to go.
+    Ok(())
+}
+
+// Browser tests - run headless against a mocked dataport, e.g. using:
+// `wasm-pack test --headless --firefox frontend`.
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    thread_local! {
+        /// Requests seen by the mocked dataport - method, URL & request id.
+        static REQUESTS: cell::RefCell<Vec<(String, String, String)>> =
+            const { cell::RefCell::new(vec![]) };
+    }
+
+    const ZONES: &str = r#"{"type": "FeatureCollection", "features": [{"type": "Feature",
+        "properties": {"name": "Waalhaven", "kind": "berth"}, "geometry": {"type": "Polygon",
+        "coordinates": [[[4.415, 51.88], [4.45, 51.88], [4.45, 51.893], [4.415, 51.88]]]}}]}"#;
+
+    fn get_vessel() -> common::Vessel {
+        common::Vessel {
+            name: "boaty".to_string(),
+            coordinates: vec![common::Coordinate {
+                lat: 51.9,
+                lon: 4.1,
+            }],
+            timestamps: vec!["2021-01-01T00:00:00Z".to_string()],
+            speeds: vec![10.0],
+            headings: vec![90.0],
+            statuses: vec![Default::default()],
+            destinations: vec!["NL RTM".to_string()],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        }
+    }
+
+    /// Returns a MessagePack response.
+    fn msgpack_response<T: serde::Serialize>(value: &T) -> web_sys::Response {
+        let mut bytes = common::wire::encode(value).unwrap();
+        web_sys::Response::new_with_opt_u8_array(Some(&mut bytes)).unwrap()
+    }
+
+    /// Replaces `fetch` by a mocked dataport - serving a single vessel, the zones & failing for
+    /// anything else.
+    fn mock_dataport() {
+        let fetch = prelude::Closure::<dyn Fn(prelude::JsValue) -> js_sys::Promise>::new(
+            |input: prelude::JsValue| {
+                let (method, url, request_id) = match input.dyn_ref::<web_sys::Request>() {
+                    Some(request) => (
+                        request.method(),
+                        request.url(),
+                        request
+                            .headers()
+                            .get(common::REQUEST_ID_HEADER)
+                            .ok()
+                            .flatten()
+                            .unwrap_or_default(),
+                    ),
+                    None => ("GET".to_string(), input.as_string().unwrap(), String::new()),
+                };
+                REQUESTS.with(|item| {
+                    item.borrow_mut()
+                        .push((method.clone(), url.clone(), request_id))
+                });
+                let resp = if url.contains("/vessels?bbox=") {
+                    msgpack_response(&common::MMSIList {
+                        vessels: vec![get_vessel().mmsi],
+                    })
+                } else if url.ends_with("/vessels") && method == "POST" {
+                    msgpack_response(&common::VesselList {
+                        vessels: vec![get_vessel()],
+                    })
+                } else if url.ends_with("zones.geojson") {
+                    web_sys::Response::new_with_opt_str(Some(ZONES)).unwrap()
+                } else {
+                    let init = web_sys::ResponseInit::new();
+                    init.set_status(500);
+                    web_sys::Response::new_with_opt_str_and_init(Some("boom"), &init).unwrap()
+                };
+                js_sys::Promise::resolve(&prelude::JsValue::from(resp))
+            },
+        );
+        let window = web_sys::window().unwrap();
+        js_sys::Reflect::set(&window, &"fetch".into(), fetch.as_ref()).unwrap();
+        fetch.forget();
+    }
+
+    // Tests for success.
+
+    #[wasm_bindgen_test]
+    async fn test_get_vessels_for_success() {
+        mock_dataport();
+        let bounds = viewport::Bounds::new(51.8, 3.9, 52.1, 4.6);
+        let controller = web_sys::AbortController::new().unwrap();
+        let res = get_vessels(&bounds, "abc", &controller.signal())
+            .await
+            .unwrap();
+        assert_eq!(res.vessels, vec![get_vessel().mmsi]);
+        let (method, url, request_id) =
+            REQUESTS.with(|item| item.borrow().last().cloned()).unwrap();
+        assert_eq!(method, "GET");
+        assert!(url.ends_with("/vessels?bbox=3.9,51.8,4.6,52.1"), "{}", url);
+        assert_eq!(request_id, "abc");
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_do_request_for_success() {
+        mock_dataport();
+        let res: common::VesselList = do_request(
             DATAPORT_ENDPOINT,
             "POST",
             common::MMSIList {
-                vessels: Vec::from(vessel_chunk),
+                vessels: vec![get_vessel().mmsi],
             },
-        );
-        let tracks: common::VesselList = tmp.await.unwrap().into_serde().unwrap();
-        add_traces(&map, tracks.vessels);
+            "abc",
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.vessels.len(), 1);
+        assert_eq!(res.vessels[0].name, "boaty");
     }
 
-    // and ready to go.
-    Ok(())
-}
+    #[wasm_bindgen_test]
+    async fn test_fetch_text_for_success() {
+        mock_dataport();
+        let res = zones::parse(&fetch_text("zones.geojson").await.unwrap()).unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].kind, zones::ZoneKind::Berth);
+    }
 
-mod tests {
-    // TODO: implement this!
+    // Tests for failure.
+
+    #[wasm_bindgen_test]
+    async fn test_do_request_for_failure() {
+        mock_dataport();
+        let res: Result<common::VesselList, _> = do_request(
+            "http://localhost:8000/broken",
+            "GET",
+            None::<usize>,
+            "abc",
+            None,
+        )
+        .await;
+        let message = error_message(&res.err().unwrap());
+        assert!(message.contains("failed with status 500"), "{}", message);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_fetch_text_for_failure() {
+        mock_dataport();
+        assert!(fetch_text("missing.geojson").await.is_err());
+    }
+
+    // Tests for sanity.
+
+    #[wasm_bindgen_test]
+    fn test_show_error_for_sanity() {
+        for i in 0..MAX_ERRORS + 2 {
+            show_error(
+                "Loading failed",
+                &prelude::JsValue::from_str(&i.to_string()),
+            );
+        }
+        let banner = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .get_element_by_id("error")
+            .unwrap();
+        // only the latest errors are kept - as text.
+        assert_eq!(banner.child_element_count(), MAX_ERRORS);
+        assert_eq!(
+            banner.last_element_child().unwrap().text_content(),
+            Some(format!("Loading failed: {}", MAX_ERRORS + 1))
+        );
+        banner.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_popup_html_for_sanity() {
+        // the popup is rendered as HTML without any markup from the AIS data.
+        let mut vessel = get_vessel();
+        vessel.name = "<img src=x onerror=alert(1)>".to_string();
+        let element = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("div")
+            .unwrap();
+        element.set_inner_html(&popup::html(&vessel));
+        assert!(element.query_selector("img").unwrap().is_none());
+        assert!(element
+            .text_content()
+            .unwrap()
+            .contains("<img src=x onerror=alert(1)>"));
+    }
 }
@@ -0,0 +1,173 @@
+//! Vessel markers - SVG symbols rotated to the course of the vessel & sized by ship type.
+//!
+//! Vessels under way are drawn as arrows, moored & anchored vessels get a distinct symbol as their
+//! heading is meaningless; vessels without a known course are drawn as diamonds.
+
+use common::ais::{NavStatus, ShipCategory};
+
+use crate::style;
+
+/// Color of the outline of the symbols.
+const STROKE_COLOR: &str = "#263238";
+
+/// Heading reported by AIS transponders if not available.
+pub const HEADING_NOT_AVAILABLE: f64 = 511.0;
+
+/// Symbol drawn for a vessel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symbol {
+    /// Arrow pointing to the course - in degrees clockwise from north.
+    Arrow(f64),
+    /// Course is not known.
+    Diamond,
+    Moored,
+    Anchored,
+}
+
+impl Symbol {
+    /// Returns the symbol for the state of the vessel at the given position report.
+    pub fn new(vessel: &common::Vessel, index: usize) -> Self {
+        match vessel.statuses.get(index) {
+            Some(NavStatus::Moored) => Symbol::Moored,
+            Some(NavStatus::AtAnchor) => Symbol::Anchored,
+            _ => course(vessel, index).map_or(Symbol::Diamond, Symbol::Arrow),
+        }
+    }
+}
+
+/// Returns the course of the vessel at the given position report - the heading, falling back to
+/// the course over ground between the last two distinct positions up to it.
+pub fn course(vessel: &common::Vessel, index: usize) -> Option<f64> {
+    heading(vessel, index).or_else(|| track_course(vessel, index))
+}
+
+/// Returns the heading reported with the given position report - if available.
+pub fn heading(vessel: &common::Vessel, index: usize) -> Option<f64> {
+    vessel
+        .headings
+        .get(index)
+        .copied()
+        .filter(|item| (0.0..360.0).contains(item) && *item != HEADING_NOT_AVAILABLE)
+}
+
+/// Returns the course over ground between the last two distinct positions up to the given
+/// position report.
+pub fn track_course(vessel: &common::Vessel, index: usize) -> Option<f64> {
+    let coordinates = vessel.coordinates.get(..=index)?;
+    let last = coordinates.last()?;
+    let prev = coordinates
+        .iter()
+        .rev()
+        .find(|item| item.lat != last.lat || item.lon != last.lon)?;
+    Some(common::geodesy::final_bearing(prev, last))
+}
+
+/// Returns the size of the marker in pixels - larger for the bigger ship types.
+pub fn size(category: ShipCategory) -> u32 {
+    match style::weight(category) {
+        3 => 24,
+        2 => 18,
+        _ => 14,
+    }
+}
+
+/// Returns the SVG document of the marker for the vessel at the given position report.
+pub fn svg(vessel: &common::Vessel, index: usize) -> String {
+    let category = vessel.ship_type.category();
+    let size = size(category);
+    let shape = match Symbol::new(vessel, index) {
+        Symbol::Arrow(course) => format!(
+            "<polygon points=\"10,1 17,18 10,14 3,18\" transform=\"rotate({:.0} 10 10)\"/>",
+            course
+        ),
+        Symbol::Diamond => "<polygon points=\"10,2 18,10 10,18 2,10\"/>".to_string(),
+        Symbol::Moored => "<rect x=\"4\" y=\"4\" width=\"12\" height=\"12\" rx=\"2\"/>".to_string(),
+        Symbol::Anchored => {
+            "<circle cx=\"10\" cy=\"10\" r=\"7\"/><path d=\"M10 5v10M6 10h8\"/>".to_string()
+        }
+    };
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" \
+        viewBox=\"0 0 20 20\"><g fill=\"{}\" stroke=\"{}\" stroke-width=\"1.5\">{}</g></svg>",
+        style::category_color(category),
+        STROKE_COLOR,
+        shape
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ais::{Hazard, ShipType};
+
+    fn get_vessel() -> common::Vessel {
+        common::Vessel {
+            name: "boaty".to_string(),
+            ship_type: ShipType::Cargo(Hazard::Unspecified),
+            coordinates: vec![
+                common::Coordinate {
+                    lat: 51.9,
+                    lon: 4.0,
+                },
+                common::Coordinate {
+                    lat: 51.9,
+                    lon: 4.1,
+                },
+            ],
+            headings: vec![10.0, 45.0],
+            statuses: vec![NavStatus::UnderWayUsingEngine; 2],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_svg_for_success() {
+        let res = svg(&get_vessel(), 1);
+        assert!(res.starts_with("<svg "));
+        assert!(res.contains("width=\"24\""));
+        assert!(res.contains("fill=\"#4caf50\""));
+        assert!(res.contains("rotate(45 10 10)"));
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_course_for_failure() {
+        // heading not available --> course over ground.
+        let mut vessel = get_vessel();
+        vessel.headings = vec![511.0];
+        let res = course(&vessel, 1).unwrap();
+        assert!((res - 90.0).abs() < 0.1);
+        // single position.
+        assert_eq!(course(&vessel, 0), None);
+        // not moving.
+        vessel.coordinates[1] = vessel.coordinates[0];
+        assert_eq!(course(&vessel, 1), None);
+        assert_eq!(Symbol::new(&vessel, 1), Symbol::Diamond);
+        vessel.coordinates = vec![];
+        assert_eq!(course(&vessel, 1), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_symbol_for_sanity() {
+        let mut vessel = get_vessel();
+        assert_eq!(Symbol::new(&vessel, 0), Symbol::Arrow(10.0));
+        assert_eq!(Symbol::new(&vessel, 1), Symbol::Arrow(45.0));
+        vessel.statuses[1] = NavStatus::Moored;
+        assert_eq!(Symbol::new(&vessel, 1), Symbol::Moored);
+        assert!(svg(&vessel, 1).contains("<rect "));
+        vessel.statuses[1] = NavStatus::AtAnchor;
+        assert_eq!(Symbol::new(&vessel, 1), Symbol::Anchored);
+        assert!(svg(&vessel, 1).contains("<circle "));
+    }
+
+    #[test]
+    fn test_size_for_sanity() {
+        assert!(size(ShipCategory::Tanker) > size(ShipCategory::Tug));
+        assert!(size(ShipCategory::Tug) > size(ShipCategory::Pleasure));
+    }
+}
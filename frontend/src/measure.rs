@@ -0,0 +1,359 @@
+//! Measurement tools - distance & bearing between points, range rings around a vessel & the
+//! closest point of approach (CPA) of two vessels; all using the geodesic math of `common`.
+
+use std::fmt;
+
+use common::{geodesy, Coordinate};
+
+use crate::{cluster, marker, style};
+
+/// Meters per nautical mile.
+pub const NAUTICAL_MILE: f64 = 1852.0;
+
+/// Meters per second per knot.
+const KNOT: f64 = NAUTICAL_MILE / 3600.0;
+
+/// Radii of the range rings in nautical miles.
+pub const RING_RADII: [f64; 3] = [1.0, 2.0, 5.0];
+
+/// Number of points of a range ring.
+const RING_POINTS: usize = 72;
+
+/// Distance (meters) & initial bearing (degrees) from one point to another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Leg {
+    pub distance: f64,
+    pub bearing: f64,
+}
+
+/// Returns the leg between two points.
+pub fn leg(src: &Coordinate, trg: &Coordinate) -> Leg {
+    Leg {
+        distance: common::distance(src.lat, src.lon, trg.lat, trg.lon),
+        bearing: geodesy::initial_bearing(src, trg),
+    }
+}
+
+/// Formats a distance in nautical miles & kilometers.
+pub fn format_distance(distance: f64) -> String {
+    format!(
+        "{:.2} NM ({:.2} km)",
+        distance / NAUTICAL_MILE,
+        distance / 1000.0
+    )
+}
+
+/// Returns the points of a ring around the center - radius in meters; the first point is repeated
+/// at the end.
+pub fn ring(center: &Coordinate, radius: f64) -> Vec<Coordinate> {
+    (0..=RING_POINTS)
+        .map(|i| geodesy::destination(center, i as f64 * 360.0 / RING_POINTS as f64, radius))
+        .collect()
+}
+
+/// Where the course of a motion is taken from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CourseSource {
+    /// Bearing between the latest distinct positions of the track.
+    Track,
+    /// Heading of the latest report - used if the vessel did not move yet.
+    Heading,
+}
+
+impl fmt::Display for CourseSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CourseSource::Track => write!(f, "track"),
+            CourseSource::Heading => write!(f, "heading"),
+        }
+    }
+}
+
+/// Latest position report of a vessel - course in degrees, speed in knots & time in milliseconds
+/// since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub position: Coordinate,
+    pub course: f64,
+    pub source: CourseSource,
+    pub speed: f64,
+    pub time: f64,
+}
+
+impl Motion {
+    /// Returns the position at the time - assuming course & speed stay the same.
+    pub fn at(&self, time: f64) -> Coordinate {
+        let distance = self.speed * KNOT * (time - self.time) / 1000.0;
+        if distance == 0.0 {
+            self.position
+        } else {
+            geodesy::destination(&self.position, self.course, distance)
+        }
+    }
+}
+
+/// Returns the motion of the vessel at its latest position report - None if the course, speed or
+/// time are not available.
+///
+/// The course is the bearing between the latest positions as the heading is where the bow points
+/// to, not where the vessel is going; the heading is only used if the track has no bearing.
+pub fn motion(vessel: &common::Vessel, times: &[f64]) -> Option<Motion> {
+    let index = vessel.coordinates.len().checked_sub(1)?;
+    let (course, source) = marker::track_course(vessel, index)
+        .map(|course| (course, CourseSource::Track))
+        .or_else(|| marker::heading(vessel, index).map(|course| (course, CourseSource::Heading)))?;
+    Some(Motion {
+        position: vessel.coordinates[index],
+        course,
+        source,
+        speed: vessel
+            .speeds
+            .get(index)
+            .copied()
+            .filter(|item| (0.0..style::SPEED_NOT_AVAILABLE).contains(item))?,
+        time: times.get(index).copied().filter(|item| item.is_finite())?,
+    })
+}
+
+/// Closest point of approach - time in milliseconds since the epoch, distance in meters & the
+/// positions of both vessels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cpa {
+    pub time: f64,
+    pub distance: f64,
+    pub positions: (Coordinate, Coordinate),
+}
+
+/// Returns the closest point of approach of two vessels - from the latest of their reports on; if
+/// they are moving apart it is the latest report.
+///
+/// The relative motion is calculated on a plane tangent at the first vessel - fine for the ranges
+/// at which a CPA is of interest.
+pub fn cpa(a: &Motion, b: &Motion) -> Cpa {
+    let time = a.time.max(b.time);
+    let (pos_a, pos_b) = (a.at(time), b.at(time));
+    let Leg { distance, bearing } = leg(&pos_a, &pos_b);
+    let (x, y) = (
+        distance * bearing.to_radians().sin(),
+        distance * bearing.to_radians().cos(),
+    );
+    let velocity = |motion: &Motion| {
+        let course = motion.course.to_radians();
+        (
+            motion.speed * KNOT * course.sin(),
+            motion.speed * KNOT * course.cos(),
+        )
+    };
+    let ((ax, ay), (bx, by)) = (velocity(a), velocity(b));
+    let (dx, dy) = (bx - ax, by - ay);
+    let speed = dx * dx + dy * dy;
+    let seconds = if speed > 0.0 {
+        (-(x * dx + y * dy) / speed).max(0.0)
+    } else {
+        0.0
+    };
+    let time = time + seconds * 1000.0;
+    let positions = (a.at(time), b.at(time));
+    Cpa {
+        time,
+        distance: leg(&positions.0, &positions.1).distance,
+        positions,
+    }
+}
+
+/// Returns the index of the position nearest to the target - if within the given pixels on the
+/// map at the zoom level.
+pub fn nearest(
+    positions: &[Coordinate],
+    target: &Coordinate,
+    zoom: f64,
+    max_px: f64,
+) -> Option<usize> {
+    let (tx, ty) = cluster::project(target, zoom);
+    positions
+        .iter()
+        .map(|item| {
+            let (x, y) = cluster::project(item, zoom);
+            (x - tx).hypot(y - ty)
+        })
+        .enumerate()
+        .filter(|(_, px)| *px <= max_px)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: f64 = 60_000.0;
+
+    fn get_coordinate(lat: f64, lon: f64) -> Coordinate {
+        Coordinate { lat, lon }
+    }
+
+    fn get_motion(lat: f64, lon: f64, course: f64, speed: f64) -> Motion {
+        Motion {
+            position: get_coordinate(lat, lon),
+            course,
+            source: CourseSource::Track,
+            speed,
+            time: 0.0,
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_leg_for_success() {
+        // one minute of latitude is a nautical mile.
+        let res = leg(
+            &get_coordinate(52.0, 4.0),
+            &get_coordinate(52.0 + 1.0 / 60.0, 4.0),
+        );
+        assert!((res.distance - NAUTICAL_MILE).abs() < 5.0, "{:?}", res);
+        assert!(res.bearing.abs() < 1e-6);
+        let res = leg(&get_coordinate(0.0, 0.0), &get_coordinate(0.0, 1.0));
+        assert!((res.bearing - 90.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cpa_for_success() {
+        // head on at 10 knots each, 2 NM apart - meet after 6 minutes.
+        let a = get_motion(52.0, 4.0, 90.0, 10.0);
+        let b = Motion {
+            position: geodesy::destination(&a.position, 90.0, 2.0 * NAUTICAL_MILE),
+            course: 270.0,
+            ..a
+        };
+        let res = cpa(&a, &b);
+        assert!((res.time - 6.0 * MINUTE).abs() < 1000.0, "{:?}", res);
+        assert!(res.distance < 10.0, "{:?}", res);
+        // overtaking - passing 1 NM abeam after 12 minutes.
+        let b = Motion {
+            position: geodesy::destination(
+                &geodesy::destination(&a.position, 270.0, 2.0 * NAUTICAL_MILE),
+                0.0,
+                NAUTICAL_MILE,
+            ),
+            course: 90.0,
+            speed: 20.0,
+            ..a
+        };
+        let res = cpa(&a, &b);
+        assert!((res.time - 12.0 * MINUTE).abs() < 1000.0, "{:?}", res);
+        assert!((res.distance - NAUTICAL_MILE).abs() < 10.0, "{:?}", res);
+    }
+
+    #[test]
+    fn test_nearest_for_success() {
+        let positions = [get_coordinate(51.9, 4.1), get_coordinate(51.9001, 4.1001)];
+        let target = get_coordinate(51.90009, 4.10009);
+        assert_eq!(nearest(&positions, &target, 15.0, 12.0), Some(1));
+    }
+
+    #[test]
+    fn test_motion_for_success() {
+        let vessel = common::Vessel {
+            name: "boaty".to_string(),
+            coordinates: vec![get_coordinate(51.9, 4.1), get_coordinate(51.9, 4.2)],
+            speeds: vec![8.0, 10.5],
+            headings: vec![511.0, 511.0],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        };
+        let res = motion(&vessel, &[0.0, MINUTE]).unwrap();
+        assert_eq!(res.position, vessel.coordinates[1]);
+        assert!((res.course - 90.0).abs() < 0.1, "{:?}", res);
+        assert_eq!(res.source, CourseSource::Track);
+        assert_eq!((res.speed, res.time), (10.5, MINUTE));
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_cpa_for_failure() {
+        // moving apart - the CPA is now.
+        let a = get_motion(52.0, 4.0, 270.0, 10.0);
+        let b = get_motion(52.0, 4.1, 90.0, 10.0);
+        let res = cpa(&a, &b);
+        assert_eq!(res.time, 0.0);
+        assert_eq!(res.positions, (a.position, b.position));
+        // same course & speed - never closer.
+        let b = Motion { course: 270.0, ..b };
+        assert_eq!(cpa(&a, &b).time, 0.0);
+    }
+
+    #[test]
+    fn test_motion_for_failure() {
+        let mut vessel = common::Vessel {
+            name: "boaty".to_string(),
+            coordinates: vec![get_coordinate(51.9, 4.1)],
+            speeds: vec![style::SPEED_NOT_AVAILABLE],
+            headings: vec![90.0],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        };
+        // speed & time not available.
+        assert_eq!(motion(&vessel, &[0.0]), None);
+        vessel.speeds = vec![10.0];
+        // no track yet --> heading.
+        let res = motion(&vessel, &[0.0]).unwrap();
+        assert_eq!((res.course, res.source), (90.0, CourseSource::Heading));
+        assert_eq!(motion(&vessel, &[f64::NAN]), None);
+        vessel.coordinates.clear();
+        assert_eq!(motion(&vessel, &[0.0]), None);
+    }
+
+    #[test]
+    fn test_nearest_for_failure() {
+        let positions = [get_coordinate(51.9, 4.1)];
+        assert_eq!(
+            nearest(&positions, &get_coordinate(52.0, 4.1), 15.0, 12.0),
+            None
+        );
+        assert_eq!(nearest(&[], &get_coordinate(52.0, 4.1), 15.0, 12.0), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_motion_for_sanity() {
+        // the track is preferred over the heading - e.g. drifting with the current.
+        let vessel = common::Vessel {
+            coordinates: vec![get_coordinate(51.9, 4.1), get_coordinate(51.9, 4.2)],
+            speeds: vec![2.0, 2.0],
+            headings: vec![0.0, 0.0],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        };
+        let res = motion(&vessel, &[0.0, MINUTE]).unwrap();
+        assert!((res.course - 90.0).abs() < 0.1, "{:?}", res);
+        assert_eq!(res.source, CourseSource::Track);
+    }
+
+    #[test]
+    fn test_cpa_for_sanity() {
+        // the older report is moved on to the time of the newer one.
+        let a = get_motion(52.0, 4.0, 0.0, 0.0);
+        let b = Motion {
+            position: geodesy::destination(&a.position, 180.0, 2.0 * NAUTICAL_MILE),
+            course: 0.0,
+            source: CourseSource::Heading,
+            speed: 10.0,
+            time: -6.0 * MINUTE,
+        };
+        let res = cpa(&a, &b);
+        assert!((res.time - 6.0 * MINUTE).abs() < 1.0, "{:?}", res);
+        assert!(res.distance < 10.0, "{:?}", res);
+    }
+
+    #[test]
+    fn test_ring_for_sanity() {
+        let center = get_coordinate(51.9, 4.1);
+        let res = ring(&center, NAUTICAL_MILE);
+        assert_eq!(res.len(), RING_POINTS + 1);
+        assert_eq!(res.first(), res.last());
+        for item in res {
+            assert!((leg(&center, &item).distance - NAUTICAL_MILE).abs() < 1e-6);
+        }
+        assert_eq!(format_distance(NAUTICAL_MILE), "1.00 NM (1.85 km)");
+    }
+}
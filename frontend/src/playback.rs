@@ -0,0 +1,284 @@
+//! Playback of the vessel tracks - positions are interpolated between the position reports & only
+//! the trailing part of each track is shown.
+
+use common::Coordinate;
+
+/// Playback speeds - multiples of real time.
+pub const SPEEDS: [u32; 5] = [1, 10, 60, 300, 1800];
+
+/// Default playback speed.
+pub const DEFAULT_SPEED: u32 = 60;
+
+/// Lengths of the trailing tracks in minutes.
+pub const TRAILS: [u32; 5] = [10, 30, 60, 180, 720];
+
+/// Default length of the trailing tracks in minutes.
+pub const DEFAULT_TRAIL: u32 = 30;
+
+/// Playback clock - all times in milliseconds since the epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    pub start: f64,
+    pub end: f64,
+    pub time: f64,
+    /// Multiple of real time.
+    pub speed: f64,
+    pub playing: bool,
+}
+
+impl Clock {
+    pub fn new(start: f64, end: f64) -> Self {
+        Clock {
+            start,
+            end,
+            time: start,
+            speed: DEFAULT_SPEED as f64,
+            playing: false,
+        }
+    }
+
+    /// Updates the time range - e.g. when more vessels are loaded.
+    pub fn set_range(&mut self, start: f64, end: f64) {
+        self.start = start;
+        self.end = end;
+        self.time = self.time.clamp(start, end);
+    }
+
+    /// Starts playing - from the start again if the end was reached.
+    pub fn play(&mut self) {
+        if self.time >= self.end {
+            self.time = self.start;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Advances the time by the elapsed real time in milliseconds - stops at the end.
+    pub fn tick(&mut self, elapsed: f64) {
+        if !self.playing {
+            return;
+        }
+        self.time = (self.time + elapsed * self.speed).min(self.end);
+        if self.time >= self.end {
+            self.playing = false;
+        }
+    }
+
+    /// Jumps to a fraction (0.0 = start, 1.0 = end) of the time range.
+    pub fn seek(&mut self, fraction: f64) {
+        self.time = self.start + (self.end - self.start) * fraction.clamp(0.0, 1.0);
+    }
+
+    /// Returns the fraction of the time range played.
+    pub fn fraction(&self) -> f64 {
+        if self.end > self.start {
+            (self.time - self.start) / (self.end - self.start)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Returns the first & last valid timestamp of all vessels.
+pub fn range<'a>(times: impl IntoIterator<Item = &'a Vec<f64>>) -> Option<(f64, f64)> {
+    let mut valid = times.into_iter().flatten().filter(|item| item.is_finite());
+    let first = *valid.next()?;
+    Some(valid.fold((first, first), |(start, end), item| {
+        (start.min(*item), end.max(*item))
+    }))
+}
+
+/// Returns a timestamp in milliseconds since the epoch as human readable UTC time - e.g.
+/// `2024-03-01 12:30:00 UTC`.
+pub fn format_time(time: f64) -> String {
+    if !time.is_finite() {
+        return "invalid time".to_string();
+    }
+    let seconds = (time / 1000.0).floor() as i64;
+    let (days, seconds) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // civil date from the days since the epoch - see: <https://howardhinnant.github.io/date_algorithms.html>.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Returns the indices of the position reports with a valid timestamp.
+fn valid(coordinates: &[Coordinate], times: &[f64]) -> Vec<usize> {
+    (0..coordinates.len().min(times.len()))
+        .filter(|i| times[*i].is_finite())
+        .collect()
+}
+
+/// Returns the position at the given time - interpolated between the position reports - with the
+/// index of the last report before it; None if outside the reports of the vessel.
+///
+/// Timestamps are expected to be in order, reports without a valid timestamp are skipped.
+pub fn position(
+    coordinates: &[Coordinate],
+    times: &[f64],
+    time: f64,
+) -> Option<(usize, Coordinate)> {
+    let valid = valid(coordinates, times);
+    let after = valid.partition_point(|i| times[*i] <= time);
+    let prev = *valid.get(after.checked_sub(1)?)?;
+    match valid.get(after) {
+        Some(next) => {
+            let fraction = (time - times[prev]) / (times[*next] - times[prev]);
+            Some((
+                prev,
+                common::geodesy::interpolate(&coordinates[prev], &coordinates[*next], fraction),
+            ))
+        }
+        None if time == times[prev] => Some((prev, coordinates[prev])),
+        None => None,
+    }
+}
+
+/// Returns the trailing track - the reports within the trail (in milliseconds) before the given
+/// time, ending at the interpolated position; empty if outside the reports of the vessel.
+pub fn trail(coordinates: &[Coordinate], times: &[f64], time: f64, trail: f64) -> Vec<Coordinate> {
+    let Some((_, current)) = position(coordinates, times, time) else {
+        return vec![];
+    };
+    let mut res: Vec<Coordinate> = valid(coordinates, times)
+        .into_iter()
+        .filter(|i| times[*i] > time - trail && times[*i] <= time)
+        .map(|i| coordinates[i])
+        .collect();
+    if res.last() != Some(&current) {
+        res.push(current);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: f64 = 60_000.0;
+
+    fn get_track() -> (Vec<Coordinate>, Vec<f64>) {
+        (
+            (0..4)
+                .map(|i| Coordinate {
+                    lat: 0.0,
+                    lon: i as f64 * 0.1,
+                })
+                .collect(),
+            vec![0.0, 10.0 * MINUTE, f64::NAN, 30.0 * MINUTE],
+        )
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_position_for_success() {
+        let (coordinates, times) = get_track();
+        assert_eq!(
+            position(&coordinates, &times, 0.0),
+            Some((0, coordinates[0]))
+        );
+        let (index, res) = position(&coordinates, &times, 5.0 * MINUTE).unwrap();
+        assert_eq!(index, 0);
+        assert!((res.lon - 0.05).abs() < 1e-6);
+        // report without timestamp is skipped.
+        let (index, res) = position(&coordinates, &times, 20.0 * MINUTE).unwrap();
+        assert_eq!(index, 1);
+        assert!((res.lon - 0.2).abs() < 1e-6);
+        assert_eq!(
+            position(&coordinates, &times, 30.0 * MINUTE),
+            Some((3, coordinates[3]))
+        );
+    }
+
+    #[test]
+    fn test_trail_for_success() {
+        let (coordinates, times) = get_track();
+        let res = trail(&coordinates, &times, 15.0 * MINUTE, 10.0 * MINUTE);
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0], coordinates[1]);
+        assert!((res[1].lon - 0.15).abs() < 1e-6);
+        let res = trail(&coordinates, &times, 30.0 * MINUTE, 60.0 * MINUTE);
+        assert_eq!(res, vec![coordinates[0], coordinates[1], coordinates[3]]);
+    }
+
+    #[test]
+    fn test_range_for_success() {
+        let res = range(&[vec![f64::NAN, 5.0, 7.0], vec![], vec![3.0, 4.0]]);
+        assert_eq!(res, Some((3.0, 7.0)));
+    }
+
+    #[test]
+    fn test_format_time_for_success() {
+        assert_eq!(format_time(0.0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_time(1_709_296_200_999.0), "2024-03-01 12:30:00 UTC");
+        assert_eq!(format_time(951_782_400_000.0), "2000-02-29 00:00:00 UTC");
+        // before the epoch.
+        assert_eq!(format_time(-1000.0), "1969-12-31 23:59:59 UTC");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_position_for_failure() {
+        let (coordinates, times) = get_track();
+        assert_eq!(position(&coordinates, &times, -1.0), None);
+        assert_eq!(position(&coordinates, &times, 31.0 * MINUTE), None);
+        assert_eq!(position(&coordinates, &[], 0.0), None);
+        assert!(trail(&coordinates, &times, -1.0, MINUTE).is_empty());
+    }
+
+    #[test]
+    fn test_format_time_for_failure() {
+        assert_eq!(format_time(f64::NAN), "invalid time");
+        assert_eq!(format_time(f64::INFINITY), "invalid time");
+    }
+
+    #[test]
+    fn test_range_for_failure() {
+        assert_eq!(range(&[]), None);
+        assert_eq!(range(&[vec![f64::NAN]]), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_clock_for_sanity() {
+        let mut clock = Clock::new(0.0, 60.0 * MINUTE);
+        clock.tick(1000.0);
+        assert_eq!(clock.time, 0.0);
+        clock.play();
+        clock.tick(1000.0);
+        assert_eq!(clock.time, MINUTE);
+        // stops at the end & restarts from the beginning.
+        clock.tick(3_600_000.0);
+        assert_eq!(clock.time, 60.0 * MINUTE);
+        assert!(!clock.playing);
+        clock.play();
+        assert_eq!(clock.time, 0.0);
+        clock.seek(0.5);
+        assert_eq!(clock.fraction(), 0.5);
+        clock.set_range(40.0 * MINUTE, 50.0 * MINUTE);
+        assert_eq!(clock.time, 40.0 * MINUTE);
+        clock.set_range(0.0, 0.0);
+        assert_eq!(clock.fraction(), 1.0);
+    }
+}
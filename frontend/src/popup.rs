@@ -0,0 +1,123 @@
+//! Popups of the vessels - all AIS data is escaped as it is not to be trusted & fields not (yet)
+//! reported are shown as "n/a".
+
+use common::xml::escape;
+
+use crate::style;
+
+/// Shown for fields not available.
+const NOT_AVAILABLE: &str = "n/a";
+
+/// Returns the escaped text - "n/a" if empty.
+fn text(value: Option<&str>) -> String {
+    match value.map(str::trim) {
+        Some(value) if !value.is_empty() => escape(value).into_owned(),
+        _ => NOT_AVAILABLE.to_string(),
+    }
+}
+
+/// Returns the value if within the range - "n/a" otherwise.
+fn number(value: Option<f64>, range: std::ops::Range<f64>, unit: &str) -> String {
+    match value {
+        Some(value) if range.contains(&value) => format!("{:.1}{}", value, unit),
+        _ => NOT_AVAILABLE.to_string(),
+    }
+}
+
+/// Returns the HTML of the popup of a vessel - with its latest state.
+pub fn html(vessel: &common::Vessel) -> String {
+    let origin = vessel.mmsi.flag_state().map_or_else(
+        || vessel.mmsi.kind().to_string(),
+        |flag| flag.name.to_string(),
+    );
+    format!(
+        "<strong><u>{}</u></strong> &raquo; <em><a href=\"?mmsi={}\" target=\"_blank\">show</a></em><br />\
+        <strong>MMSI</strong>: <a href=\"https://www.marinetraffic.com/en/ais/details/ships/mmsi:{}\" target=\"_blank\">{}</a> ({})<br />\
+        <strong>Speed</strong>: {}<br />\
+        <strong>Heading</strong>: {}<br />\
+        <strong>Type</strong>: {}<br />\
+        <strong>Timestamp</strong>: {}<br />\
+        <strong>Status</strong>: {}<br />\
+        <strong>Destination</strong>: {}",
+        text(Some(&vessel.name)),
+        vessel.mmsi,
+        vessel.mmsi,
+        vessel.mmsi,
+        escape(&origin),
+        number(
+            vessel.speeds.last().copied(),
+            0.0..style::SPEED_NOT_AVAILABLE,
+            " kn"
+        ),
+        number(vessel.headings.last().copied(), 0.0..360.0, "&deg;"),
+        escape(&vessel.ship_type.to_string()),
+        text(vessel.timestamps.last().map(String::as_str)),
+        text(
+            vessel
+                .statuses
+                .last()
+                .map(|item| item.to_string())
+                .as_deref()
+        ),
+        text(vessel.destinations.last().map(String::as_str)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marker;
+
+    fn get_vessel() -> common::Vessel {
+        common::Vessel {
+            name: "<b>Boaty</b>".to_string(),
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_html_for_success() {
+        let mut vessel = get_vessel();
+        vessel.speeds = vec![12.34];
+        vessel.headings = vec![90.0];
+        vessel.destinations = vec!["NL RTM".to_string()];
+        let res = html(&vessel);
+        assert!(res.contains("<strong>Speed</strong>: 12.3 kn<br />"));
+        assert!(res.contains("<strong>Heading</strong>: 90.0&deg;<br />"));
+        assert!(res.contains("<strong>Destination</strong>: NL RTM"));
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_html_for_failure() {
+        // nothing reported - nor valid.
+        let res = html(&get_vessel());
+        assert!(res.contains("<strong>Speed</strong>: n/a<br />"));
+        assert!(res.contains("<strong>Timestamp</strong>: n/a<br />"));
+        assert!(res.contains("<strong>Destination</strong>: n/a"));
+        let mut vessel = get_vessel();
+        vessel.speeds = vec![style::SPEED_NOT_AVAILABLE];
+        vessel.headings = vec![marker::HEADING_NOT_AVAILABLE];
+        vessel.destinations = vec!["  ".to_string()];
+        let res = html(&vessel);
+        assert!(res.contains("<strong>Speed</strong>: n/a<br />"));
+        assert!(res.contains("<strong>Heading</strong>: n/a<br />"));
+        assert!(res.contains("<strong>Destination</strong>: n/a"));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_html_for_sanity() {
+        // no markup from the AIS data.
+        let mut vessel = get_vessel();
+        vessel.destinations = vec!["<img src=\"x\" onerror='alert(1)'>".to_string()];
+        let res = html(&vessel);
+        assert!(!res.contains("<b>") && !res.contains("<img"));
+        assert!(res.contains("&lt;b&gt;Boaty&lt;/b&gt;"));
+        assert!(res.contains("&lt;img src=&quot;x&quot; onerror=&apos;alert(1)&apos;&gt;"));
+    }
+}
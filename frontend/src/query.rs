@@ -0,0 +1,144 @@
+//! Query string of the page - e.g. `?mmsi=244123456,211000001&style=speed`; parsed apart from the
+//! browser APIs.
+
+use crate::style;
+
+/// Decodes a (form) URL encoded text - invalid escapes are kept as is.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => res.push(b' '),
+            b'%' => match text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    res.push(byte);
+                    i += 2;
+                }
+                None => res.push(b'%'),
+            },
+            byte => res.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+/// Returns the values of a key - comma separated values are split.
+pub fn values(search: &str, key: &str) -> Vec<String> {
+    let mut res = vec![];
+    for part in search.trim_start_matches('?').split('&') {
+        let Some((name, values)) = part.split_once('=') else {
+            continue;
+        };
+        if decode(name) == key {
+            res.extend(values.split(',').map(decode));
+        }
+    }
+    res
+}
+
+/// Returns the MMSIs of the vessels - with the errors of the invalid ones.
+pub fn vessels(search: &str) -> (Vec<common::mmsi::Mmsi>, Vec<String>) {
+    let mut res = vec![];
+    let mut errors = vec![];
+    for mmsi in values(search, "mmsi") {
+        match mmsi.parse() {
+            Ok(mmsi) => res.push(mmsi),
+            Err(err) => errors.push(format!("{}: {}", mmsi, err)),
+        }
+    }
+    (res, errors)
+}
+
+/// Returns the style of the tracks - the default one if not given or unknown.
+pub fn style(search: &str) -> style::TrackStyle {
+    values(search, "style")
+        .first()
+        .and_then(|item| item.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Returns the location of the zones - if given.
+pub fn zones(search: &str) -> Option<String> {
+    values(search, "zones")
+        .into_iter()
+        .find(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_values_for_success() {
+        let search = "?mmsi=244123456,211000001&style=speed&mmsi=244000002";
+        assert_eq!(
+            values(search, "mmsi"),
+            vec!["244123456", "211000001", "244000002"]
+        );
+        assert_eq!(values(search, "style"), vec!["speed"]);
+        assert_eq!(
+            values("zones=%2Fdata%2Fzones.geojson", "zones"),
+            vec!["/data/zones.geojson"]
+        );
+    }
+
+    #[test]
+    fn test_vessels_for_success() {
+        let (res, errors) = vessels("?mmsi=244123456,211000001");
+        assert_eq!(
+            res.iter().map(|item| item.value()).collect::<Vec<u32>>(),
+            vec![244123456, 211000001]
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_style_for_success() {
+        assert_eq!(style("?style=speed"), style::TrackStyle::Speed);
+        assert_eq!(style("?style=age"), style::TrackStyle::Age);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_values_for_failure() {
+        assert!(values("", "mmsi").is_empty());
+        assert!(values("?mmsi", "mmsi").is_empty());
+        assert!(values("?vessels=244123456", "mmsi").is_empty());
+        // invalid escapes are kept.
+        assert_eq!(values("?name=100%&x=%zz", "name"), vec!["100%"]);
+    }
+
+    #[test]
+    fn test_vessels_for_failure() {
+        let (res, errors) = vessels("?mmsi=boaty,244123456");
+        assert_eq!(res.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("boaty: "));
+    }
+
+    #[test]
+    fn test_style_for_failure() {
+        assert_eq!(style(""), style::TrackStyle::default());
+        assert_eq!(style("?style=rainbow"), style::TrackStyle::default());
+        assert_eq!(zones("?zones="), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_decode_for_sanity() {
+        assert_eq!(decode("NL+RTM"), "NL RTM");
+        assert_eq!(decode("caf%C3%A9"), "café");
+        assert_eq!(decode("%"), "%");
+        assert_eq!(decode("plain"), "plain");
+    }
+}
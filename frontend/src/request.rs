@@ -0,0 +1,114 @@
+//! Requests to the services - built apart from the browser APIs; the request id allows tracing
+//! them through the services.
+
+use crate::viewport;
+
+/// Request to a service - POST requests carry a JSON body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// Builds a request - responses are requested in the compact MessagePack format.
+pub fn new<T: serde::Serialize>(
+    url: &str,
+    method: &str,
+    body: &T,
+    request_id: &str,
+) -> Result<Request, serde_json::Error> {
+    let (top, sub) = common::wire::MSGPACK_MEDIA_TYPE;
+    let mut headers = vec![
+        (
+            common::REQUEST_ID_HEADER.to_string(),
+            request_id.to_string(),
+        ),
+        ("Accept".to_string(), format!("{}/{}", top, sub)),
+    ];
+    // if we do a POST we add whatever is in the body - as json data.
+    let body = if method == "POST" {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        Some(serde_json::to_string(body)?)
+    } else {
+        None
+    };
+    Ok(Request {
+        url: url.to_string(),
+        method: method.to_string(),
+        headers,
+        body,
+    })
+}
+
+/// Returns the URL of the vessels with position reports within the bounds.
+pub fn vessels_url(endpoint: &str, bounds: &viewport::Bounds) -> String {
+    format!("{}?bbox={}", endpoint, bounds.bbox())
+}
+
+/// Returns the error of a failed request.
+pub fn status_error(method: &str, url: &str, status: u16) -> String {
+    format!("{} {} failed with status {}.", method, url, status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests for success.
+
+    #[test]
+    fn test_new_for_success() {
+        let body = common::MMSIList {
+            vessels: vec![common::mmsi::Mmsi::new(244123456).unwrap()],
+        };
+        let res = new("http://localhost:8000/vessels", "POST", &body, "abc").unwrap();
+        assert_eq!(res.method, "POST");
+        assert_eq!(res.body.as_deref(), Some("{\"vessels\":[244123456]}"));
+        assert!(res
+            .headers
+            .contains(&(common::REQUEST_ID_HEADER.to_string(), "abc".to_string())));
+        assert!(res
+            .headers
+            .contains(&("Accept".to_string(), "application/msgpack".to_string())));
+        assert!(res
+            .headers
+            .contains(&("Content-Type".to_string(), "application/json".to_string())));
+    }
+
+    #[test]
+    fn test_vessels_url_for_success() {
+        let bounds = viewport::Bounds::new(51.8, 3.9, 52.1, 4.6);
+        assert_eq!(
+            vessels_url("http://localhost:8000/vessels", &bounds),
+            "http://localhost:8000/vessels?bbox=3.9,51.8,4.6,52.1"
+        );
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_status_error_for_failure() {
+        assert_eq!(
+            status_error("GET", "zones.geojson", 404),
+            "GET zones.geojson failed with status 404."
+        );
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_new_for_sanity() {
+        // no body for GET requests.
+        let res = new(
+            "http://localhost:8000/vessels",
+            "GET",
+            &None::<usize>,
+            "abc",
+        )
+        .unwrap();
+        assert_eq!(res.body, None);
+        assert_eq!(res.headers.len(), 2);
+    }
+}
@@ -0,0 +1,213 @@
+//! Search & filtering of the vessels - by name or MMSI, ship type & the navigational status, speed
+//! & destination of their latest position report.
+
+use common::ais::{NavStatus, ShipCategory};
+
+use crate::style;
+
+/// Selection of the vessels shown - empty texts & None match all vessels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    /// Part of the name or MMSI - case insensitive.
+    pub text: String,
+    pub category: Option<ShipCategory>,
+    pub status: Option<NavStatus>,
+    /// Minimum speed over ground in knots - inclusive.
+    pub min_speed: Option<f64>,
+    /// Maximum speed over ground in knots - inclusive.
+    pub max_speed: Option<f64>,
+    /// Part of the destination - case insensitive.
+    pub destination: String,
+}
+
+/// Returns true if the text contains the (lower case) part - case insensitive.
+fn contains(text: &str, part: &str) -> bool {
+    part.is_empty() || text.to_lowercase().contains(part)
+}
+
+impl Filter {
+    /// Returns true if the filter selects all vessels.
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+            && self.category.is_none()
+            && self.status.is_none()
+            && self.min_speed.is_none()
+            && self.max_speed.is_none()
+            && self.destination.trim().is_empty()
+    }
+
+    /// Returns true if the vessel is selected - vessels without a (valid) speed are skipped if a
+    /// speed range is given.
+    pub fn matches(&self, vessel: &common::Vessel) -> bool {
+        let text = self.text.trim().to_lowercase();
+        let speed = vessel
+            .speeds
+            .last()
+            .filter(|item| (0.0..style::SPEED_NOT_AVAILABLE).contains(*item));
+        let destination = vessel.destinations.last().map_or("", String::as_str);
+        (contains(&vessel.name, &text) || contains(&vessel.mmsi.to_string(), &text))
+            && self
+                .category
+                .is_none_or(|item| vessel.ship_type.category() == item)
+            && self
+                .status
+                .is_none_or(|item| vessel.statuses.last() == Some(&item))
+            && self
+                .min_speed
+                .is_none_or(|min| speed.is_some_and(|speed| *speed >= min))
+            && self
+                .max_speed
+                .is_none_or(|max| speed.is_some_and(|speed| *speed <= max))
+            && contains(destination, &self.destination.trim().to_lowercase())
+    }
+}
+
+/// Returns the indices of at most `limit` vessels matching the filter - vessels whose name or MMSI
+/// start with the text first, then ordered by name.
+pub fn search<'a>(
+    vessels: impl IntoIterator<Item = &'a common::Vessel>,
+    filter: &Filter,
+    limit: usize,
+) -> Vec<usize> {
+    let text = filter.text.trim().to_lowercase();
+    let mut res: Vec<(bool, String, usize)> = vessels
+        .into_iter()
+        .enumerate()
+        .filter(|(_, vessel)| filter.matches(vessel))
+        .map(|(i, vessel)| {
+            let name = vessel.name.to_lowercase();
+            let prefix = name.starts_with(&text) || vessel.mmsi.to_string().starts_with(&text);
+            (!prefix, name, i)
+        })
+        .collect();
+    res.sort();
+    res.into_iter().take(limit).map(|(_, _, i)| i).collect()
+}
+
+/// Returns the number of vessels matching the filter - of all vessels if filtered.
+pub fn summary(matching: usize, total: usize, filter: &Filter) -> String {
+    if filter.is_empty() {
+        format!("{} vessels", matching)
+    } else {
+        format!("{} of {} vessels", matching, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ais::{Hazard, ShipType};
+
+    fn get_vessel(mmsi: u32, name: &str, speed: f64, status: NavStatus) -> common::Vessel {
+        common::Vessel {
+            name: name.to_string(),
+            ship_type: ShipType::Cargo(Hazard::Unspecified),
+            coordinates: vec![common::Coordinate {
+                lat: 51.9,
+                lon: 4.1,
+            }],
+            speeds: vec![speed],
+            statuses: vec![status],
+            destinations: vec!["NL RTM".to_string()],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(mmsi).unwrap())
+        }
+    }
+
+    fn get_vessels() -> Vec<common::Vessel> {
+        vec![
+            get_vessel(
+                244123456,
+                "Rotterdam Express",
+                12.5,
+                NavStatus::UnderWayUsingEngine,
+            ),
+            get_vessel(211000001, "Boaty", 0.0, NavStatus::Moored),
+            get_vessel(244000002, "Express Two", 102.3, NavStatus::AtAnchor),
+        ]
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_search_for_success() {
+        let vessels = get_vessels();
+        let filter = Filter {
+            text: "express".to_string(),
+            ..Default::default()
+        };
+        // name starting with the text first.
+        assert_eq!(search(&vessels, &filter, 10), vec![2, 0]);
+        assert_eq!(search(&vessels, &filter, 1), vec![2]);
+        // by MMSI.
+        let filter = Filter {
+            text: "2440".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(search(&vessels, &filter, 10), vec![2]);
+    }
+
+    #[test]
+    fn test_matches_for_success() {
+        let vessel = &get_vessels()[0];
+        let filter = Filter {
+            text: "ROTTERDAM".to_string(),
+            category: Some(ShipCategory::Cargo),
+            status: Some(NavStatus::UnderWayUsingEngine),
+            min_speed: Some(10.0),
+            max_speed: Some(12.5),
+            destination: "rtm".to_string(),
+        };
+        assert!(filter.matches(vessel));
+        assert!(Filter::default().matches(vessel));
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_matches_for_failure() {
+        let vessels = get_vessels();
+        let filter = Filter {
+            category: Some(ShipCategory::Tanker),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&vessels[0]));
+        let filter = Filter {
+            destination: "hamburg".to_string(),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&vessels[0]));
+        // speed not available.
+        let filter = Filter {
+            min_speed: Some(0.0),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&vessels[2]));
+        // no reports at all.
+        let mut vessel = vessels[0].clone();
+        vessel.statuses.clear();
+        let filter = Filter {
+            status: Some(NavStatus::UnderWayUsingEngine),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&vessel));
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_search_for_sanity() {
+        let vessels = get_vessels();
+        // everything - ordered by name.
+        assert_eq!(search(&vessels, &Filter::default(), 10), vec![1, 2, 0]);
+        let filter = Filter {
+            status: Some(NavStatus::Moored),
+            max_speed: Some(1.0),
+            ..Default::default()
+        };
+        assert_eq!(search(&vessels, &filter, 10), vec![1]);
+        assert!(!filter.is_empty());
+        assert!(Filter::default().is_empty());
+        assert_eq!(summary(1, 3, &filter), "1 of 3 vessels");
+        assert_eq!(summary(3, 3, &Filter::default()), "3 vessels");
+    }
+}
@@ -0,0 +1,212 @@
+//! Level of detail of the tracks - at lower zoom levels the tracks are simplified by the path
+//! function, dropping positions closer to each other than a few pixels.
+
+use std::ops;
+
+use common::{mmsi::Mmsi, Coordinate};
+
+/// Zoom level from which the full tracks are shown.
+pub const MAX_ZOOM: u32 = 15;
+
+/// Tracks with fewer positions are always shown in full.
+pub const MIN_POSITIONS: usize = 100;
+
+/// Maximum number of positions sent to the path function in one request - keeps the body well
+/// within the size limit of the service.
+const MAX_BATCH_POSITIONS: usize = 20_000;
+
+/// Positions closer to the previous one kept are dropped - in pixels.
+const TOLERANCE_PX: f64 = 2.0;
+
+/// Meters per pixel at the equator at zoom level 0.
+const METERS_PER_PIXEL: f64 = 156_543.034;
+
+/// Returns the radius (meters) for the simplification at a zoom level & latitude.
+pub fn radius(zoom: u32, lat: f64) -> f64 {
+    TOLERANCE_PX * METERS_PER_PIXEL * lat.to_radians().cos() / 2_f64.powi(zoom as i32)
+}
+
+/// Splits the tracks into batches of at most `MAX_BATCH_POSITIONS` positions - larger tracks are
+/// sent on their own.
+pub fn batches(tracks: &[Vec<Coordinate>]) -> Vec<ops::Range<usize>> {
+    let mut res = vec![];
+    let (mut start, mut positions) = (0, 0);
+    for (i, track) in tracks.iter().enumerate() {
+        if i > start && positions + track.len() > MAX_BATCH_POSITIONS {
+            res.push(start..i);
+            (start, positions) = (i, 0);
+        }
+        positions += track.len();
+    }
+    if start < tracks.len() {
+        res.push(start..tracks.len());
+    }
+    res
+}
+
+/// Returns the request simplifying the tracks for a zoom level - only their coordinates are sent.
+pub fn batch_in(zoom: u32, tracks: &[Vec<Coordinate>]) -> common::SimplifyBatchIn {
+    common::SimplifyBatchIn {
+        paths: tracks
+            .iter()
+            .map(|coordinates| common::SimplifyPath {
+                radius: radius(zoom, coordinates.first().map_or(0.0, |item| item.lat)),
+                coordinates: coordinates.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Returns the simplified paths of the vessels of a batch - None for the tracks which could not be
+/// simplified, all of them if the request failed.
+pub fn batch_out(
+    mmsis: &[Mmsi],
+    out: Option<common::SimplifyBatchOut>,
+) -> Vec<(Mmsi, Option<Vec<Coordinate>>)> {
+    let mut paths = out.map(|out| out.paths).unwrap_or_default().into_iter();
+    mmsis
+        .iter()
+        .map(|mmsi| (*mmsi, paths.next().flatten().map(|item| item.path)))
+        .collect()
+}
+
+/// Returns the indices of the positions kept by the simplification - which keeps them in order.
+pub fn indices(coordinates: &[Coordinate], simplified: &[Coordinate]) -> Vec<usize> {
+    let mut kept = simplified.iter().peekable();
+    let mut res = vec![];
+    for (i, item) in coordinates.iter().enumerate() {
+        if kept.peek() == Some(&item) {
+            res.push(i);
+            kept.next();
+        }
+    }
+    res
+}
+
+/// Returns the items at the indices.
+pub fn select<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+    indices
+        .iter()
+        .filter_map(|i| items.get(*i).cloned())
+        .collect()
+}
+
+/// Returns the vessel with only the position reports at the indices.
+pub fn select_vessel(vessel: &common::Vessel, indices: &[usize]) -> common::Vessel {
+    common::Vessel {
+        mmsi: vessel.mmsi,
+        name: vessel.name.clone(),
+        ship_type: vessel.ship_type,
+        coordinates: select(&vessel.coordinates, indices),
+        timestamps: select(&vessel.timestamps, indices),
+        speeds: select(&vessel.speeds, indices),
+        headings: select(&vessel.headings, indices),
+        statuses: select(&vessel.statuses, indices),
+        destinations: select(&vessel.destinations, indices),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_track() -> Vec<Coordinate> {
+        (0..5)
+            .map(|i| Coordinate {
+                lat: 51.9,
+                lon: 4.0 + i as f64 * 0.01,
+            })
+            .collect()
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_indices_for_success() {
+        let track = get_track();
+        let simplified = [track[0], track[2], track[4]];
+        assert_eq!(indices(&track, &simplified), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_batches_for_success() {
+        let tracks = vec![get_track(); 3];
+        assert_eq!(batches(&tracks), vec![0..3]);
+        assert!(batches(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_batch_in_for_success() {
+        let res = batch_in(10, &[get_track()]);
+        assert_eq!(res.paths.len(), 1);
+        assert_eq!(res.paths[0].coordinates, get_track());
+        assert_eq!(res.paths[0].radius, radius(10, 51.9));
+    }
+
+    #[test]
+    fn test_batch_out_for_success() {
+        let mmsis = [Mmsi::new(244123456).unwrap(), Mmsi::new(211000001).unwrap()];
+        let out = common::SimplifyBatchOut {
+            paths: vec![Some(common::SimplifyOut { path: get_track() }), None],
+        };
+        let res = batch_out(&mmsis, Some(out));
+        assert_eq!(res, vec![(mmsis[0], Some(get_track())), (mmsis[1], None)]);
+    }
+
+    #[test]
+    fn test_select_vessel_for_success() {
+        let vessel = common::Vessel {
+            name: "boaty".to_string(),
+            coordinates: get_track(),
+            speeds: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        };
+        let res = select_vessel(&vessel, &[1, 3]);
+        assert_eq!(
+            res.coordinates,
+            vec![vessel.coordinates[1], vessel.coordinates[3]]
+        );
+        assert_eq!(res.speeds, vec![2.0, 4.0]);
+        assert!(res.timestamps.is_empty());
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_indices_for_failure() {
+        // positions not part of the track.
+        let track = get_track();
+        let other = [Coordinate { lat: 0.0, lon: 0.0 }];
+        assert!(indices(&track, &other).is_empty());
+        assert!(indices(&[], &track).is_empty());
+    }
+
+    #[test]
+    fn test_batch_out_for_failure() {
+        // failed request or fewer paths than sent - shown in full.
+        let mmsis = [Mmsi::new(244123456).unwrap()];
+        assert_eq!(batch_out(&mmsis, None), vec![(mmsis[0], None)]);
+        let out = common::SimplifyBatchOut { paths: vec![] };
+        assert_eq!(batch_out(&mmsis, Some(out)), vec![(mmsis[0], None)]);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_batches_for_sanity() {
+        // a track larger than a batch is sent on its own.
+        let large = vec![Coordinate::default(); MAX_BATCH_POSITIONS + 1];
+        let half = vec![Coordinate::default(); MAX_BATCH_POSITIONS / 2];
+        let tracks = vec![half.clone(), large, half.clone(), half.clone(), half];
+        assert_eq!(batches(&tracks), vec![0..1, 1..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn test_radius_for_sanity() {
+        // two pixels at zoom level 10 in Rotterdam are ~188m.
+        let res = radius(10, 51.9);
+        assert!((res - 188.5).abs() < 1.0, "radius: {}", res);
+        assert!((radius(11, 51.9) * 2.0 - res).abs() < 1e-9);
+        assert!(radius(10, 0.0) > res);
+    }
+}
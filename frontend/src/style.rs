@@ -0,0 +1,331 @@
+//! Styling of the vessel tracks - colored by ship category, speed or age of the position reports.
+//!
+//! Tracks are split into segments sharing the same color & opacity, so a speed gradient or fading
+//! track needs only a few polylines instead of one per leg.
+
+use common::ais::ShipCategory;
+
+/// Color used for legs without (valid) data.
+pub const NO_DATA_COLOR: &str = "#9e9e9e";
+
+/// Color of the tracks when fading them by age.
+const AGE_COLOR: &str = "#ff7900";
+
+/// Speed over ground (knots) reported if not available.
+pub const SPEED_NOT_AVAILABLE: f64 = 102.3;
+
+/// Upper bounds (knots, exclusive) of the speed classes with their colors.
+const SPEED_CLASSES: [(f64, &str); 6] = [
+    (1.0, "#2c7bb6"),
+    (5.0, "#00ccbc"),
+    (10.0, "#90eb9d"),
+    (15.0, "#f9d057"),
+    (20.0, "#f29e2e"),
+    (f64::INFINITY, "#d7191c"),
+];
+
+/// Upper bounds (hours, exclusive) of the age classes with their opacities.
+const AGE_CLASSES: [(f64, f64); 6] = [
+    (1.0, 1.0),
+    (3.0, 0.8),
+    (6.0, 0.6),
+    (12.0, 0.45),
+    (24.0, 0.3),
+    (f64::INFINITY, 0.15),
+];
+
+/// Opacity used for legs without a valid timestamp.
+const NO_DATA_OPACITY: f64 = 0.15;
+
+/// How to color the tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrackStyle {
+    /// Single color per track based on the ship category.
+    #[default]
+    Category,
+    /// Gradient along the track based on the speed over ground.
+    Speed,
+    /// Fading along the track based on the age of the position reports.
+    Age,
+}
+
+impl TrackStyle {
+    /// All styles - in the order shown in the legend.
+    pub const ALL: [TrackStyle; 3] = [TrackStyle::Category, TrackStyle::Speed, TrackStyle::Age];
+
+    /// Returns the key used in the query string & the legend.
+    pub fn key(&self) -> &'static str {
+        match self {
+            TrackStyle::Category => "type",
+            TrackStyle::Speed => "speed",
+            TrackStyle::Age => "age",
+        }
+    }
+
+    /// Returns a human readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrackStyle::Category => "Ship type",
+            TrackStyle::Speed => "Speed (kn)",
+            TrackStyle::Age => "Age (h)",
+        }
+    }
+}
+
+impl std::str::FromStr for TrackStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TrackStyle::ALL
+            .into_iter()
+            .find(|item| item.key() == s)
+            .ok_or_else(|| format!("unknown track style: {}", s))
+    }
+}
+
+/// Part of a track drawn with the same color & opacity - `from` & `to` are inclusive indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub from: usize,
+    pub to: usize,
+    pub color: &'static str,
+    pub opacity: f64,
+}
+
+/// Returns the color of a ship category.
+pub fn category_color(category: ShipCategory) -> &'static str {
+    match category {
+        ShipCategory::Unknown => NO_DATA_COLOR,
+        ShipCategory::Cargo => "#4caf50",
+        ShipCategory::Tanker => "#d32f2f",
+        ShipCategory::Passenger => "#1976d2",
+        ShipCategory::Tug => "#00acc1",
+        ShipCategory::Fishing => "#ff9800",
+        ShipCategory::Pleasure => "#9c27b0",
+        ShipCategory::HighSpeed => "#fbc02d",
+        ShipCategory::Special => "#795548",
+        ShipCategory::Other => "#607d8b",
+    }
+}
+
+/// Returns the line weight for a ship category - AIS dimensions are not kept, so the category
+/// serves as proxy for the size of the vessel.
+pub fn weight(category: ShipCategory) -> usize {
+    match category {
+        ShipCategory::Cargo | ShipCategory::Tanker | ShipCategory::Passenger => 3,
+        ShipCategory::Tug
+        | ShipCategory::Fishing
+        | ShipCategory::HighSpeed
+        | ShipCategory::Special => 2,
+        ShipCategory::Pleasure | ShipCategory::Other | ShipCategory::Unknown => 1,
+    }
+}
+
+/// Returns the color for a speed over ground in knots.
+pub fn speed_color(speed: Option<f64>) -> &'static str {
+    match speed {
+        Some(speed) if (0.0..SPEED_NOT_AVAILABLE).contains(&speed) => SPEED_CLASSES
+            .iter()
+            .find(|(bound, _)| speed < *bound)
+            .map(|(_, color)| *color)
+            .unwrap_or(NO_DATA_COLOR),
+        _ => NO_DATA_COLOR,
+    }
+}
+
+/// Returns the opacity for the age of a position report in milliseconds.
+pub fn age_opacity(age: f64) -> f64 {
+    if age.is_nan() || age < 0.0 {
+        return NO_DATA_OPACITY;
+    }
+    let hours = age / 3_600_000.0;
+    AGE_CLASSES
+        .iter()
+        .find(|(bound, _)| hours < *bound)
+        .map(|(_, opacity)| *opacity)
+        .unwrap_or(NO_DATA_OPACITY)
+}
+
+/// Splits the track of a vessel into segments.
+///
+/// `times` are the timestamps of the position reports in milliseconds since the epoch (NaN if
+/// unknown) & `newest` the timestamp the ages are relative to.
+pub fn segments(
+    vessel: &common::Vessel,
+    style: TrackStyle,
+    times: &[f64],
+    newest: f64,
+) -> Vec<Segment> {
+    let len = vessel.coordinates.len();
+    if len < 2 {
+        return vec![];
+    }
+    let category = vessel.ship_type.category();
+    // style of the leg ending at the given position report.
+    let leg = |i: usize| match style {
+        TrackStyle::Category => (category_color(category), 1.0),
+        TrackStyle::Speed => (speed_color(vessel.speeds.get(i).copied()), 1.0),
+        TrackStyle::Age => (
+            AGE_COLOR,
+            age_opacity(newest - times.get(i).copied().unwrap_or(f64::NAN)),
+        ),
+    };
+    let mut res: Vec<Segment> = vec![];
+    for i in 1..len {
+        let (color, opacity) = leg(i);
+        match res.last_mut() {
+            Some(last) if last.color == color && last.opacity == opacity => last.to = i,
+            _ => res.push(Segment {
+                from: i - 1,
+                to: i,
+                color,
+                opacity,
+            }),
+        }
+    }
+    res
+}
+
+/// Returns the entries of the legend - label, color & opacity.
+pub fn legend(style: TrackStyle) -> Vec<(String, &'static str, f64)> {
+    match style {
+        TrackStyle::Category => ShipCategory::ALL
+            .into_iter()
+            .map(|item| (item.to_string(), category_color(item), 1.0))
+            .collect(),
+        TrackStyle::Speed => classes(&SPEED_CLASSES.map(|(bound, color)| (bound, (color, 1.0))))
+            .into_iter()
+            .chain([("n/a".to_string(), NO_DATA_COLOR, 1.0)])
+            .collect(),
+        TrackStyle::Age => {
+            classes(&AGE_CLASSES.map(|(bound, opacity)| (bound, (AGE_COLOR, opacity))))
+        }
+    }
+}
+
+/// Returns legend entries for classes given by their upper bounds - e.g. "1 - 5".
+fn classes(items: &[(f64, (&'static str, f64))]) -> Vec<(String, &'static str, f64)> {
+    let mut lower = 0.0;
+    items
+        .iter()
+        .map(|(bound, (color, opacity))| {
+            let label = if bound.is_finite() {
+                format!("{} - {}", lower, bound)
+            } else {
+                format!("> {}", lower)
+            };
+            lower = *bound;
+            (label, *color, *opacity)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ais::{Hazard, ShipType};
+
+    fn get_vessel(speeds: Vec<f64>) -> common::Vessel {
+        common::Vessel {
+            name: "boaty".to_string(),
+            ship_type: ShipType::Tanker(Hazard::Unspecified),
+            coordinates: (0..speeds.len())
+                .map(|i| common::Coordinate {
+                    lat: 51.9,
+                    lon: 4.0 + i as f64 * 0.01,
+                })
+                .collect(),
+            speeds,
+            ..common::Vessel::new(common::mmsi::Mmsi::new(244123456).unwrap())
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_segments_for_success() {
+        let vessel = get_vessel(vec![0.0, 3.0, 3.5, 12.0]);
+        assert_eq!(
+            segments(&vessel, TrackStyle::Category, &[], 0.0),
+            vec![Segment {
+                from: 0,
+                to: 3,
+                color: "#d32f2f",
+                opacity: 1.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_from_str_for_success() {
+        for style in TrackStyle::ALL {
+            assert_eq!(style.key().parse::<TrackStyle>(), Ok(style));
+        }
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_segments_for_failure() {
+        // nothing to draw.
+        assert!(segments(&get_vessel(vec![]), TrackStyle::Speed, &[], 0.0).is_empty());
+        assert!(segments(&get_vessel(vec![1.0]), TrackStyle::Speed, &[], 0.0).is_empty());
+        // missing data.
+        let mut vessel = get_vessel(vec![1.0, 102.3]);
+        vessel.coordinates.push(vessel.coordinates[0]);
+        let res = segments(&vessel, TrackStyle::Speed, &[], 0.0);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].color, NO_DATA_COLOR);
+        let res = segments(&vessel, TrackStyle::Age, &[0.0, f64::NAN], 0.0);
+        assert_eq!(res[0].opacity, NO_DATA_OPACITY);
+    }
+
+    #[test]
+    fn test_from_str_for_failure() {
+        assert!("color".parse::<TrackStyle>().is_err());
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_segments_for_sanity() {
+        // legs with the same speed class share a segment - including their shared position.
+        let vessel = get_vessel(vec![0.0, 3.0, 3.5, 12.0, 25.0]);
+        let res = segments(&vessel, TrackStyle::Speed, &[], 0.0);
+        assert_eq!(
+            res.iter()
+                .map(|item| (item.from, item.to, item.color))
+                .collect::<Vec<_>>(),
+            vec![(0, 2, "#00ccbc"), (2, 3, "#f9d057"), (3, 4, "#d7191c")]
+        );
+
+        // fading with the age - relative to the newest report.
+        let hour = 3_600_000.0;
+        let times = [0.0, 20.0 * hour, 22.0 * hour, 23.5 * hour, 24.0 * hour];
+        let res = segments(&vessel, TrackStyle::Age, &times, 24.0 * hour);
+        assert_eq!(
+            res.iter()
+                .map(|item| (item.from, item.to, item.opacity))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, 0.6), (1, 2, 0.8), (2, 4, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_weight_for_sanity() {
+        assert!(weight(ShipCategory::Tanker) > weight(ShipCategory::Tug));
+        assert!(weight(ShipCategory::Tug) > weight(ShipCategory::Pleasure));
+    }
+
+    #[test]
+    fn test_legend_for_sanity() {
+        let res = legend(TrackStyle::Speed);
+        assert_eq!(res[0], ("0 - 1".to_string(), "#2c7bb6", 1.0));
+        assert_eq!(res[5], ("> 20".to_string(), "#d7191c", 1.0));
+        assert_eq!(res[6].1, NO_DATA_COLOR);
+        let res = legend(TrackStyle::Age);
+        assert_eq!(res.len(), AGE_CLASSES.len());
+        assert_eq!(res[5], ("> 24".to_string(), AGE_COLOR, 0.15));
+        assert_eq!(legend(TrackStyle::Category)[0].0, "Cargo");
+    }
+}
@@ -0,0 +1,313 @@
+//! Measurement tools - the points & vessels picked on the map & the results shown for them.
+
+use common::{mmsi::Mmsi, Coordinate};
+
+use crate::{measure, playback, tracks};
+
+/// Tool applied when clicking on the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Off,
+    Measure,
+    Rings,
+    Cpa,
+}
+
+impl Tool {
+    /// Tools in the order shown - with the value & label of their option.
+    pub const ALL: [(Tool, &'static str, &'static str); 4] = [
+        (Tool::Off, "off", "No tool"),
+        (Tool::Measure, "measure", "Measure distance"),
+        (Tool::Rings, "rings", "Range rings"),
+        (Tool::Cpa, "cpa", "CPA of two vessels"),
+    ];
+
+    /// Returns the tool of an option value.
+    pub fn from_value(value: &str) -> Option<Tool> {
+        Tool::ALL
+            .iter()
+            .find(|(_, item, _)| *item == value)
+            .map(|(tool, _, _)| *tool)
+    }
+
+    /// Returns the hint shown when the tool is selected.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Tool::Off => "",
+            Tool::Measure => "Click points or vessels to measure.",
+            Tool::Rings => "Click a vessel to draw range rings.",
+            Tool::Cpa => "Click two vessels to show their CPA.",
+        }
+    }
+}
+
+/// Point picked with a tool - a vessel if clicked (close to) its marker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pick {
+    pub position: Coordinate,
+    pub vessel: Option<(Mmsi, String)>,
+}
+
+impl Pick {
+    /// Returns the label of the pick - the vessel name or the number of the point.
+    pub fn label(&self, i: usize) -> String {
+        match &self.vessel {
+            Some((mmsi, name)) if name.trim().is_empty() => mmsi.to_string(),
+            Some((_, name)) => name.clone(),
+            None => format!("Point {}", i + 1),
+        }
+    }
+}
+
+/// Returns the pick at the clicked position - snapped to a vessel marker within the given pixels.
+pub fn pick(tracks: &tracks::Store, clicked: Coordinate, snap_px: f64) -> Pick {
+    let positions = tracks.positions();
+    let coordinates: Vec<Coordinate> = positions.iter().map(|(_, _, pos)| *pos).collect();
+    match measure::nearest(&coordinates, &clicked, tracks.zoom, snap_px) {
+        Some(i) => {
+            let (track, _, pos) = positions[i];
+            Pick {
+                position: pos,
+                vessel: Some((track.vessel.mmsi, track.vessel.name.clone())),
+            }
+        }
+        None => Pick {
+            position: clicked,
+            vessel: None,
+        },
+    }
+}
+
+/// Adds the pick for the tool - returns false if the click is ignored.
+pub fn apply(tool: Tool, picks: &mut Vec<Pick>, pick: Pick) -> bool {
+    match tool {
+        Tool::Off => return false,
+        Tool::Measure => picks.push(pick),
+        Tool::Rings => *picks = vec![pick],
+        Tool::Cpa => {
+            // only vessels - a second click on the same vessel is ignored.
+            let Some((mmsi, _)) = &pick.vessel else {
+                return false;
+            };
+            if picks
+                .iter()
+                .any(|item| item.vessel.as_ref().map(|(m, _)| m) == Some(mmsi))
+            {
+                return false;
+            }
+            if picks.len() == 2 {
+                picks.clear();
+            }
+            picks.push(pick);
+        }
+    }
+    true
+}
+
+/// Returns the distance & bearing of the legs between the picks.
+pub fn legs(picks: &[Pick]) -> Vec<String> {
+    let mut res = vec![];
+    let mut total = 0.0;
+    for (i, item) in picks.windows(2).enumerate() {
+        let leg = measure::leg(&item[0].position, &item[1].position);
+        total += leg.distance;
+        res.push(format!(
+            "{} to {}: {}, {:03.0}°",
+            item[0].label(i),
+            item[1].label(i + 1),
+            measure::format_distance(leg.distance),
+            leg.bearing
+        ));
+    }
+    if res.len() > 1 {
+        res.push(format!("Total: {}", measure::format_distance(total)));
+    }
+    if let [first] = picks {
+        res.push(format!("From {} - click the next point.", first.label(0)));
+    }
+    res
+}
+
+/// Returns the radii of the range rings around the pick.
+pub fn rings(pick: &Pick) -> String {
+    let radii: Vec<String> = measure::RING_RADII
+        .iter()
+        .map(|item| item.to_string())
+        .collect();
+    format!(
+        "Range rings around {}: {} NM",
+        pick.label(0),
+        radii.join(", ")
+    )
+}
+
+/// Returns the closest point of approach of the picked vessels - with the time until it & the
+/// courses used.
+pub fn cpa(
+    (a, motion_a): (&Pick, &measure::Motion),
+    (b, motion_b): (&Pick, &measure::Motion),
+    cpa: &measure::Cpa,
+) -> Vec<String> {
+    let start = motion_a.time.max(motion_b.time);
+    vec![
+        format!(
+            "CPA of {} & {}: {}",
+            a.label(0),
+            b.label(1),
+            measure::format_distance(cpa.distance)
+        ),
+        if cpa.time > start {
+            format!(
+                "TCPA: {:.0} min after the latest report ({})",
+                (cpa.time - start) / 60_000.0,
+                playback::format_time(cpa.time)
+            )
+        } else {
+            "Moving apart - closest at the latest report.".to_string()
+        },
+        format!(
+            "Courses: {:.0}° ({}) & {:.0}° ({}).",
+            motion_a.course, motion_a.source, motion_b.course, motion_b.source
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_pick(lon: f64, vessel: Option<(u32, &str)>) -> Pick {
+        Pick {
+            position: Coordinate { lat: 0.0, lon },
+            vessel: vessel.map(|(mmsi, name)| (Mmsi::new(mmsi).unwrap(), name.to_string())),
+        }
+    }
+
+    fn get_motion(lon: f64, course: f64) -> measure::Motion {
+        measure::Motion {
+            position: Coordinate { lat: 0.0, lon },
+            course,
+            source: measure::CourseSource::Track,
+            speed: 10.0,
+            time: 0.0,
+        }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_apply_for_success() {
+        let mut picks = vec![];
+        assert!(apply(Tool::Measure, &mut picks, get_pick(0.0, None)));
+        assert!(apply(Tool::Measure, &mut picks, get_pick(1.0, None)));
+        assert_eq!(picks.len(), 2);
+        // a single center for the rings.
+        assert!(apply(Tool::Rings, &mut picks, get_pick(2.0, None)));
+        assert_eq!(picks, vec![get_pick(2.0, None)]);
+    }
+
+    #[test]
+    fn test_legs_for_success() {
+        let picks = [
+            get_pick(0.0, None),
+            get_pick(1.0, Some((244123456, "boaty"))),
+            get_pick(2.0, Some((244000002, " "))),
+        ];
+        let res = legs(&picks[..1]);
+        assert_eq!(res, vec!["From Point 1 - click the next point."]);
+        let res = legs(&picks);
+        assert_eq!(res.len(), 3);
+        assert!(res[0].starts_with("Point 1 to boaty: "), "{}", res[0]);
+        assert!(res[0].ends_with(", 090°"), "{}", res[0]);
+        assert!(res[1].starts_with("boaty to 244000002: "), "{}", res[1]);
+        assert!(res[2].starts_with("Total: "), "{}", res[2]);
+        assert!(rings(&picks[1]).starts_with("Range rings around boaty: "));
+    }
+
+    #[test]
+    fn test_cpa_for_success() {
+        let (a, b) = (
+            get_pick(0.0, Some((244123456, "a"))),
+            get_pick(0.1, Some((244000002, "b"))),
+        );
+        let (motion_a, motion_b) = (get_motion(0.0, 90.0), get_motion(0.1, 270.0));
+        let res = cpa(
+            (&a, &motion_a),
+            (&b, &motion_b),
+            &measure::cpa(&motion_a, &motion_b),
+        );
+        assert!(res[0].starts_with("CPA of a & b: "), "{}", res[0]);
+        assert!(res[1].starts_with("TCPA: 18 min after"), "{}", res[1]);
+        assert_eq!(res[2], "Courses: 90° (track) & 270° (track).");
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_apply_for_failure() {
+        let mut picks = vec![];
+        assert!(!apply(Tool::Off, &mut picks, get_pick(0.0, None)));
+        // only vessels for the CPA - each once.
+        assert!(!apply(Tool::Cpa, &mut picks, get_pick(0.0, None)));
+        let vessel = get_pick(0.0, Some((244123456, "boaty")));
+        assert!(apply(Tool::Cpa, &mut picks, vessel.clone()));
+        assert!(!apply(Tool::Cpa, &mut picks, vessel));
+        assert_eq!(picks.len(), 1);
+        assert_eq!(Tool::from_value("compass"), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_apply_for_sanity() {
+        // a third vessel starts a new CPA.
+        let mut picks = vec![];
+        for (mmsi, lon) in [(244123456, 0.0), (244000002, 1.0), (244000003, 2.0)] {
+            assert!(apply(
+                Tool::Cpa,
+                &mut picks,
+                get_pick(lon, Some((mmsi, "")))
+            ));
+        }
+        assert_eq!(picks, vec![get_pick(2.0, Some((244000003, "")))]);
+        for (tool, value, _) in Tool::ALL {
+            assert_eq!(Tool::from_value(value), Some(tool));
+        }
+    }
+
+    #[test]
+    fn test_pick_for_sanity() {
+        let mut store = tracks::Store::new(14.0);
+        let vessel = common::Vessel {
+            name: "boaty".to_string(),
+            coordinates: vec![Coordinate {
+                lat: 51.9,
+                lon: 4.1,
+            }],
+            ..common::Vessel::new(Mmsi::new(244123456).unwrap())
+        };
+        store.add(vec![vessel], |_| f64::NAN);
+        // snapped to the marker close to the click.
+        let res = pick(
+            &store,
+            Coordinate {
+                lat: 51.9,
+                lon: 4.1001,
+            },
+            12.0,
+        );
+        assert_eq!(
+            res.position,
+            Coordinate {
+                lat: 51.9,
+                lon: 4.1
+            }
+        );
+        assert_eq!(res.label(0), "boaty");
+        let clicked = Coordinate {
+            lat: 51.9,
+            lon: 4.2,
+        };
+        assert_eq!(pick(&store, clicked, 12.0).vessel, None);
+    }
+}
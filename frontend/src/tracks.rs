@@ -0,0 +1,335 @@
+//! Vessel tracks loaded - keyed by MMSI with the timestamps of their position reports & their
+//! simplifications; which part is shown depends on the filter, zoom level & playback.
+
+use std::collections;
+
+use common::{mmsi::Mmsi, Coordinate, Vessel};
+
+use crate::{measure, playback, search, simplify, viewport};
+
+/// Track of a vessel.
+#[derive(Clone)]
+pub struct Track {
+    pub vessel: Vessel,
+    /// Timestamps of the position reports in milliseconds since the epoch - NaN if invalid.
+    pub times: Vec<f64>,
+    /// Zoom level & indices of the position reports kept by the simplification for it - None if
+    /// the track could not be simplified.
+    simplified: Option<(u32, Option<Vec<usize>>)>,
+}
+
+/// Tracks of the vessels loaded - ordered by MMSI.
+#[derive(Clone)]
+pub struct Store {
+    tracks: collections::BTreeMap<Mmsi, Track>,
+    /// Newest timestamp of all vessels - the ages of the position reports are relative to it.
+    pub newest: f64,
+    /// Time & length of the trailing tracks (both in milliseconds) during the playback - the full
+    /// tracks are shown otherwise.
+    pub playback: Option<(f64, f64)>,
+    pub zoom: f64,
+    /// Vessels shown.
+    pub filter: search::Filter,
+    /// Vessel highlighted.
+    pub selected: Option<Mmsi>,
+}
+
+impl Store {
+    pub fn new(zoom: f64) -> Self {
+        Store {
+            tracks: collections::BTreeMap::new(),
+            newest: f64::NEG_INFINITY,
+            playback: None,
+            zoom,
+            filter: search::Filter::default(),
+            selected: None,
+        }
+    }
+
+    /// Returns true if the vessel is loaded.
+    pub fn contains(&self, mmsi: Mmsi) -> bool {
+        self.tracks.contains_key(&mmsi)
+    }
+
+    pub fn get(&self, mmsi: Mmsi) -> Option<&Track> {
+        self.tracks.get(&mmsi)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Track> {
+        self.tracks.values()
+    }
+
+    /// Returns the tracks selected by the filter.
+    pub fn shown(&self) -> impl Iterator<Item = &Track> {
+        self.iter()
+            .filter(|track| self.filter.matches(&track.vessel))
+    }
+
+    /// Returns the vessels not loaded yet.
+    pub fn missing(&self, mmsis: Vec<Mmsi>) -> Vec<Mmsi> {
+        mmsis
+            .into_iter()
+            .filter(|item| !self.contains(*item))
+            .collect()
+    }
+
+    /// Adds the vessels with their timestamps parsed - vessels already loaded are skipped; returns
+    /// the vessels added.
+    pub fn add(&mut self, vessels: Vec<Vessel>, parse: impl Fn(&str) -> f64) -> Vec<Mmsi> {
+        let mut res = vec![];
+        for vessel in vessels {
+            let collections::btree_map::Entry::Vacant(entry) = self.tracks.entry(vessel.mmsi)
+            else {
+                continue;
+            };
+            let times: Vec<f64> = vessel.timestamps.iter().map(|item| parse(item)).collect();
+            self.newest = times.iter().copied().fold(self.newest, f64::max);
+            res.push(vessel.mmsi);
+            entry.insert(Track {
+                vessel,
+                times,
+                simplified: None,
+            });
+        }
+        res
+    }
+
+    /// Removes the vessels without position reports within the bounds - returns true if any.
+    pub fn retain(&mut self, bounds: &viewport::Bounds) -> bool {
+        let len = self.tracks.len();
+        self.tracks
+            .retain(|_, track| bounds.intersects(&track.vessel.coordinates));
+        self.tracks.len() != len
+    }
+
+    /// Returns the zoom level the tracks are simplified for.
+    pub fn level(&self) -> u32 {
+        self.zoom.round().max(0.0) as u32
+    }
+
+    /// Returns the tracks not simplified for the current zoom level yet.
+    pub fn unsimplified(&self) -> (Vec<Mmsi>, Vec<Vec<Coordinate>>) {
+        let level = self.level();
+        self.iter()
+            .filter(|track| track.vessel.coordinates.len() >= simplify::MIN_POSITIONS)
+            .filter(|track| track.simplified.as_ref().map(|item| item.0) != Some(level))
+            .map(|track| (track.vessel.mmsi, track.vessel.coordinates.clone()))
+            .unzip()
+    }
+
+    /// Sets the simplified paths of the vessels for a zoom level - vessels no longer loaded are
+    /// skipped.
+    pub fn set_simplified(&mut self, level: u32, paths: Vec<(Mmsi, Option<Vec<Coordinate>>)>) {
+        for (mmsi, path) in paths {
+            let Some(track) = self.tracks.get_mut(&mmsi) else {
+                continue;
+            };
+            let indices = path.map(|path| simplify::indices(&track.vessel.coordinates, &path));
+            track.simplified = Some((level, indices));
+        }
+    }
+
+    /// Returns the track with only the position reports kept by the simplification for the
+    /// current zoom level - None if it is shown in full.
+    pub fn simplified(&self, track: &Track) -> Option<(Vessel, Vec<f64>)> {
+        let level = self.level();
+        match &track.simplified {
+            Some((item, Some(indices))) if *item == level && level < simplify::MAX_ZOOM => Some((
+                simplify::select_vessel(&track.vessel, indices),
+                simplify::select(&track.times, indices),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the positions of the markers - track, position report & position; the latest
+    /// position or the interpolated one during the playback.
+    pub fn positions(&self) -> Vec<(&Track, usize, Coordinate)> {
+        self.shown()
+            .filter_map(|track| {
+                let coordinates = &track.vessel.coordinates;
+                match self.playback {
+                    Some((time, _)) => playback::position(coordinates, &track.times, time)
+                        .map(|(index, pos)| (track, index, pos)),
+                    None => coordinates
+                        .last()
+                        .map(|pos| (track, coordinates.len() - 1, *pos)),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the first & last valid timestamp of all vessels.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        playback::range(self.iter().map(|track| &track.times))
+    }
+
+    /// Returns the motion of a vessel at its latest position report.
+    pub fn motion(&self, mmsi: Mmsi) -> Option<measure::Motion> {
+        let track = self.get(mmsi)?;
+        measure::motion(&track.vessel, &track.times)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ais::{Hazard, ShipType};
+
+    fn get_mmsi(mmsi: u32) -> Mmsi {
+        Mmsi::new(mmsi).unwrap()
+    }
+
+    /// Returns a vessel heading east - a position report every minute.
+    fn get_vessel(mmsi: u32, lat: f64, len: usize) -> Vessel {
+        Vessel {
+            name: format!("vessel {}", mmsi),
+            ship_type: ShipType::Cargo(Hazard::Unspecified),
+            coordinates: (0..len)
+                .map(|i| Coordinate {
+                    lat,
+                    lon: 4.0 + i as f64 * 0.001,
+                })
+                .collect(),
+            timestamps: (0..len).map(|i| (i * 60_000).to_string()).collect(),
+            speeds: vec![10.0; len],
+            ..Vessel::new(get_mmsi(mmsi))
+        }
+    }
+
+    fn parse(time: &str) -> f64 {
+        time.parse().unwrap_or(f64::NAN)
+    }
+
+    fn get_store() -> Store {
+        let mut store = Store::new(10.0);
+        store.add(
+            vec![
+                get_vessel(244000002, 51.9, 3),
+                get_vessel(211000001, 51.9, simplify::MIN_POSITIONS),
+            ],
+            parse,
+        );
+        store
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_add_for_success() {
+        let mut store = get_store();
+        assert_eq!(store.iter().count(), 2);
+        assert!(store.contains(get_mmsi(211000001)));
+        assert_eq!(
+            store.newest,
+            (simplify::MIN_POSITIONS - 1) as f64 * 60_000.0
+        );
+        // ordered by MMSI.
+        let mmsis: Vec<Mmsi> = store.iter().map(|track| track.vessel.mmsi).collect();
+        assert_eq!(mmsis, vec![get_mmsi(211000001), get_mmsi(244000002)]);
+        // vessels already loaded are skipped.
+        let res = store.add(
+            vec![
+                get_vessel(244000002, 52.0, 1),
+                get_vessel(244000003, 51.9, 1),
+            ],
+            parse,
+        );
+        assert_eq!(res, vec![get_mmsi(244000003)]);
+        assert_eq!(store.get(get_mmsi(244000002)).unwrap().times.len(), 3);
+        assert_eq!(
+            store.missing(vec![get_mmsi(244000003), get_mmsi(244000004)]),
+            vec![get_mmsi(244000004)]
+        );
+    }
+
+    #[test]
+    fn test_simplified_for_success() {
+        let mut store = get_store();
+        let (mmsis, tracks) = store.unsimplified();
+        assert_eq!(mmsis, vec![get_mmsi(211000001)]);
+        let path = vec![tracks[0][0], tracks[0][simplify::MIN_POSITIONS - 1]];
+        store.set_simplified(store.level(), vec![(mmsis[0], Some(path.clone()))]);
+        assert!(store.unsimplified().0.is_empty());
+        let (vessel, times) = store
+            .simplified(store.get(mmsis[0]).unwrap())
+            .expect("simplified track.");
+        assert_eq!(vessel.coordinates, path);
+        assert_eq!(times, vec![0.0, store.newest]);
+        // shown in full at another zoom level until simplified again.
+        store.zoom = 12.0;
+        assert!(store.simplified(store.get(mmsis[0]).unwrap()).is_none());
+        assert_eq!(store.unsimplified().0, mmsis);
+    }
+
+    #[test]
+    fn test_positions_for_success() {
+        let mut store = get_store();
+        let res = store.positions();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[1].1, 2);
+        assert_eq!(res[1].2, res[1].0.vessel.coordinates[2]);
+        // interpolated during the playback - vessels without reports at the time are skipped.
+        store.playback = Some((90_000.0, 600_000.0));
+        let res = store.positions();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[1].1, 1);
+        assert!((res[1].2.lon - 4.0015).abs() < 1e-6);
+        store.playback = Some((150_000.0, 600_000.0));
+        assert_eq!(store.positions().len(), 1);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_simplified_for_failure() {
+        let mut store = get_store();
+        let mmsi = get_mmsi(211000001);
+        // could not be simplified - shown in full.
+        store.set_simplified(store.level(), vec![(mmsi, None)]);
+        assert!(store.simplified(store.get(mmsi).unwrap()).is_none());
+        assert!(store.unsimplified().0.is_empty());
+        // vessels not loaded are skipped.
+        store.set_simplified(store.level(), vec![(get_mmsi(244000004), None)]);
+        assert!(!store.contains(get_mmsi(244000004)));
+        // full tracks from the max zoom level on.
+        store.zoom = simplify::MAX_ZOOM as f64;
+        store.set_simplified(store.level(), vec![(mmsi, Some(vec![]))]);
+        assert!(store.simplified(store.get(mmsi).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_motion_for_failure() {
+        let store = get_store();
+        assert!(store.motion(get_mmsi(244000004)).is_none());
+        let mut store = Store::new(10.0);
+        store.add(vec![get_vessel(244000002, 51.9, 3)], |_| f64::NAN);
+        assert!(store.motion(get_mmsi(244000002)).is_none());
+        assert_eq!(store.range(), None);
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_store_for_sanity() {
+        let mut store = get_store();
+        assert!(store.motion(get_mmsi(244000002)).is_some());
+        assert_eq!(store.range(), Some((0.0, store.newest)));
+        // filtered.
+        store.filter = search::Filter {
+            text: "2440".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(store.shown().count(), 1);
+        assert_eq!(store.positions().len(), 1);
+        // only the vessels within the bounds are kept.
+        assert!(!store.retain(&viewport::Bounds::new(51.0, 3.0, 52.0, 5.0)));
+        let mut other = get_vessel(244000003, 53.0, 2);
+        other.timestamps.clear();
+        store.add(vec![other], parse);
+        assert_eq!(store.iter().count(), 3);
+        assert!(store.retain(&viewport::Bounds::new(51.0, 3.0, 52.0, 5.0)));
+        assert_eq!(store.iter().count(), 2);
+        assert!(!store.contains(get_mmsi(244000003)));
+    }
+}
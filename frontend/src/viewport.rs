@@ -0,0 +1,165 @@
+//! Visible area of the map - vessels are only loaded (& kept) if they have position reports within
+//! it, so large ports stay responsive.
+
+use common::Coordinate;
+
+/// Area of the map in degrees - longitudes normalized into [-180, 180]; crosses the antimeridian if
+/// west is larger than east.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+/// Normalizes a longitude into [-180, 180].
+fn wrap(lon: f64) -> f64 {
+    if (-180.0..=180.0).contains(&lon) {
+        lon
+    } else {
+        (lon + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+impl Bounds {
+    /// Creates the bounds from the corners of the map - longitudes exceed [-180, 180] if the world
+    /// wraps.
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> Self {
+        let (west, east) = if east - west >= 360.0 {
+            (-180.0, 180.0)
+        } else {
+            (wrap(west), wrap(east))
+        };
+        Bounds {
+            south: south.max(-90.0),
+            west,
+            north: north.min(90.0),
+            east,
+        }
+    }
+
+    /// Returns the smallest bounds around the coordinates - None if there are none.
+    pub fn around(coordinates: &[Coordinate]) -> Option<Self> {
+        let first = coordinates.first()?;
+        Some(coordinates.iter().fold(
+            Bounds::new(first.lat, first.lon, first.lat, first.lon),
+            |res, item| Bounds {
+                south: res.south.min(item.lat),
+                west: res.west.min(item.lon),
+                north: res.north.max(item.lat),
+                east: res.east.max(item.lon),
+            },
+        ))
+    }
+
+    /// Returns the bounds grown by a ratio of their size on each side - keeps the vessels just
+    /// outside the view while panning.
+    pub fn pad(&self, ratio: f64) -> Self {
+        let width = if self.west <= self.east {
+            self.east - self.west
+        } else {
+            self.east - self.west + 360.0
+        };
+        let height = self.north - self.south;
+        Bounds::new(
+            self.south - height * ratio,
+            self.west - width * ratio,
+            self.north + height * ratio,
+            self.west + width * (1.0 + ratio),
+        )
+    }
+
+    /// Returns the bounds as used by the dataport - min_lon,min_lat,max_lon,max_lat.
+    pub fn bbox(&self) -> String {
+        format!("{},{},{},{}", self.west, self.south, self.east, self.north)
+    }
+
+    /// Returns true if the coordinate is within the bounds.
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        let lon = if self.west <= self.east {
+            (self.west..=self.east).contains(&coordinate.lon)
+        } else {
+            coordinate.lon >= self.west || coordinate.lon <= self.east
+        };
+        lon && (self.south..=self.north).contains(&coordinate.lat)
+    }
+
+    /// Returns true if any of the coordinates is within the bounds.
+    pub fn intersects(&self, coordinates: &[Coordinate]) -> bool {
+        coordinates.iter().any(|item| self.contains(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_coordinate(lat: f64, lon: f64) -> Coordinate {
+        Coordinate { lat, lon }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_bbox_for_success() {
+        let bounds = Bounds::new(51.8, 3.9, 52.1, 4.6);
+        assert_eq!(bounds.bbox(), "3.9,51.8,4.6,52.1");
+    }
+
+    #[test]
+    fn test_intersects_for_success() {
+        let bounds = Bounds::new(51.8, 3.9, 52.1, 4.6);
+        let track = [get_coordinate(51.0, 3.0), get_coordinate(51.9, 4.1)];
+        assert!(bounds.intersects(&track));
+        assert!(!bounds.intersects(&track[..1]));
+        assert!(!bounds.intersects(&[]));
+    }
+
+    #[test]
+    fn test_around_for_success() {
+        let track = [
+            get_coordinate(51.9, 4.1),
+            get_coordinate(51.8, 4.3),
+            get_coordinate(52.0, 4.2),
+        ];
+        assert_eq!(
+            Bounds::around(&track),
+            Some(Bounds::new(51.8, 4.1, 52.0, 4.3))
+        );
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_around_for_failure() {
+        assert_eq!(Bounds::around(&[]), None);
+    }
+
+    #[test]
+    fn test_new_for_failure() {
+        // whole world & beyond.
+        let bounds = Bounds::new(-100.0, -200.0, 100.0, 400.0);
+        assert_eq!(bounds.bbox(), "-180,-90,180,90");
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_new_for_sanity() {
+        // view across the antimeridian.
+        let bounds = Bounds::new(-20.0, 170.0, -10.0, 190.0);
+        assert_eq!((bounds.west, bounds.east), (170.0, -170.0));
+        assert!(bounds.contains(&get_coordinate(-15.0, 179.0)));
+        assert!(bounds.contains(&get_coordinate(-15.0, -175.0)));
+        assert!(!bounds.contains(&get_coordinate(-15.0, 0.0)));
+    }
+
+    #[test]
+    fn test_pad_for_sanity() {
+        let bounds = Bounds::new(50.0, 4.0, 52.0, 6.0).pad(0.5);
+        assert_eq!(bounds, Bounds::new(49.0, 3.0, 53.0, 7.0));
+        let bounds = Bounds::new(-20.0, 170.0, -10.0, 190.0).pad(0.5);
+        assert_eq!((bounds.west, bounds.east), (160.0, -160.0));
+    }
+}
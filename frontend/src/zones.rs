@@ -0,0 +1,237 @@
+//! Port zones - polygons of berths, anchorages & fairways loaded from a GeoJSON document.
+//!
+//! The kind of a zone is taken from its `kind` property, its name from the `name` property;
+//! features without a (multi) polygon geometry are skipped.
+
+use std::{fmt, str::FromStr};
+
+use common::Coordinate;
+
+/// Kind of a zone - determines its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneKind {
+    Berth,
+    Anchorage,
+    Fairway,
+    Other,
+}
+
+impl ZoneKind {
+    /// Returns the color of the zone.
+    pub fn color(&self) -> &'static str {
+        match self {
+            ZoneKind::Berth => "#6a1b9a",
+            ZoneKind::Anchorage => "#00897b",
+            ZoneKind::Fairway => "#1565c0",
+            ZoneKind::Other => "#616161",
+        }
+    }
+}
+
+impl fmt::Display for ZoneKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ZoneKind::Berth => "Berth",
+            ZoneKind::Anchorage => "Anchorage",
+            ZoneKind::Fairway => "Fairway",
+            ZoneKind::Other => "Other",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+impl FromStr for ZoneKind {
+    type Err = std::convert::Infallible;
+
+    /// Parses a kind - unknown kinds become `Other`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "berth" => ZoneKind::Berth,
+            "anchorage" => ZoneKind::Anchorage,
+            "fairway" => ZoneKind::Fairway,
+            _ => ZoneKind::Other,
+        })
+    }
+}
+
+/// Zone of the port - a polygon is a list of rings, the first one being the outer ring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub kind: ZoneKind,
+    pub polygons: Vec<Vec<Vec<Coordinate>>>,
+}
+
+/// Converts the rings of a GeoJSON polygon - positions are longitude, latitude.
+fn to_rings(rings: &[Vec<geojson::Position>]) -> Vec<Vec<Coordinate>> {
+    rings
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .filter(|pos| pos.len() >= 2)
+                .map(|pos| Coordinate {
+                    lat: pos[1],
+                    lon: pos[0],
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses the zones from a GeoJSON document.
+pub fn parse(text: &str) -> Result<Vec<Zone>, Box<geojson::Error>> {
+    let collection = geojson::FeatureCollection::from_str(text).map_err(Box::new)?;
+    let mut res = vec![];
+    for feature in collection.features {
+        let polygons = match feature.geometry.as_ref().map(|item| &item.value) {
+            Some(geojson::Value::Polygon(rings)) => vec![to_rings(rings)],
+            Some(geojson::Value::MultiPolygon(polygons)) => {
+                polygons.iter().map(|rings| to_rings(rings)).collect()
+            }
+            _ => continue,
+        };
+        let property = |name: &str| {
+            feature
+                .property(name)
+                .and_then(|item| item.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        res.push(Zone {
+            name: property("name"),
+            kind: property("kind").parse().unwrap_or(ZoneKind::Other),
+            polygons,
+        });
+    }
+    Ok(res)
+}
+
+/// Returns true if the coordinate is within the ring - using ray casting.
+fn in_ring(ring: &[Coordinate], coordinate: &Coordinate) -> bool {
+    let mut res = false;
+    for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        if (a.lat > coordinate.lat) != (b.lat > coordinate.lat)
+            && coordinate.lon < a.lon + (coordinate.lat - a.lat) / (b.lat - a.lat) * (b.lon - a.lon)
+        {
+            res = !res;
+        }
+    }
+    res
+}
+
+impl Zone {
+    /// Returns true if the coordinate is within the zone - but not within one of its holes.
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        self.polygons.iter().any(|rings| match rings.split_first() {
+            Some((outer, holes)) => {
+                in_ring(outer, coordinate) && !holes.iter().any(|item| in_ring(item, coordinate))
+            }
+            None => false,
+        })
+    }
+}
+
+/// Returns the zones containing the coordinate.
+pub fn find<'a>(zones: &'a [Zone], coordinate: &Coordinate) -> Vec<&'a Zone> {
+    zones
+        .iter()
+        .filter(|item| item.contains(coordinate))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": {"name": "Anchorage 3", "kind": "anchorage"},
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [
+                        [[3.0, 51.0], [4.0, 51.0], [4.0, 52.0], [3.0, 52.0], [3.0, 51.0]],
+                        [[3.4, 51.4], [3.6, 51.4], [3.6, 51.6], [3.4, 51.6], [3.4, 51.4]]
+                    ]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": {"name": "Buoy"},
+                "geometry": {"type": "Point", "coordinates": [4.0, 52.0]}
+            },
+            {
+                "type": "Feature",
+                "properties": {"kind": "Pier"},
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [
+                        [[[5.0, 51.0], [6.0, 51.0], [6.0, 52.0], [5.0, 51.0]]],
+                        [[[7.0, 51.0], [8.0, 51.0], [8.0, 52.0], [7.0, 51.0]]]
+                    ]
+                }
+            }
+        ]
+    }"#;
+
+    fn get_coordinate(lat: f64, lon: f64) -> Coordinate {
+        Coordinate { lat, lon }
+    }
+
+    // Tests for success.
+
+    #[test]
+    fn test_parse_for_success() {
+        let res = parse(ZONES).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].name, "Anchorage 3");
+        assert_eq!(res[0].kind, ZoneKind::Anchorage);
+        assert_eq!(res[0].polygons[0].len(), 2);
+        assert_eq!(res[0].polygons[0][0][1], get_coordinate(51.0, 4.0));
+        assert_eq!(res[1].name, "");
+        assert_eq!(res[1].kind, ZoneKind::Other);
+        assert_eq!(res[1].polygons.len(), 2);
+    }
+
+    #[test]
+    fn test_find_for_success() {
+        let zones = parse(ZONES).unwrap();
+        let res = find(&zones, &get_coordinate(51.2, 3.2));
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].name, "Anchorage 3");
+        assert_eq!(find(&zones, &get_coordinate(51.2, 7.8)).len(), 1);
+    }
+
+    // Tests for failure.
+
+    #[test]
+    fn test_parse_for_failure() {
+        assert!(parse("").is_err());
+        assert!(parse("{\"type\": \"Point\", \"coordinates\": [4.0, 52.0]}").is_err());
+    }
+
+    #[test]
+    fn test_find_for_failure() {
+        let zones = parse(ZONES).unwrap();
+        // outside & within the hole.
+        assert!(find(&zones, &get_coordinate(50.0, 3.5)).is_empty());
+        assert!(find(&zones, &get_coordinate(51.5, 3.5)).is_empty());
+        assert!(find(&[], &get_coordinate(51.2, 3.2)).is_empty());
+    }
+
+    // Tests for sanity.
+
+    #[test]
+    fn test_zone_kind_for_sanity() {
+        assert_eq!("Berth".parse(), Ok(ZoneKind::Berth));
+        assert_eq!(" FAIRWAY ".parse(), Ok(ZoneKind::Fairway));
+        assert_eq!("".parse(), Ok(ZoneKind::Other));
+        assert_ne!(ZoneKind::Berth.color(), ZoneKind::Anchorage.color());
+        // labels parse again.
+        for kind in [ZoneKind::Berth, ZoneKind::Anchorage, ZoneKind::Fairway] {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+}
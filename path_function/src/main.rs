@@ -1,22 +1,124 @@
 #[macro_use]
 extern crate rocket;
 
+use common::wire;
+use rocket::http;
 use rocket::serde::json;
+use utoipa::OpenApi;
 
-#[post("/simplify", format = "application/json", data = "<data>")]
-fn simplify(data: json::Json<common::SimplifyIn>) -> Option<json::Json<common::SimplifyOut>> {
-    if data.vessel.coordinates.len() <= 1 {
+/// OpenAPI specification of this service.
+#[derive(OpenApi)]
+#[openapi(
+    paths(simplify, simplify_batch, openapi),
+    components(schemas(
+        common::SimplifyIn,
+        common::SimplifyOut,
+        common::SimplifyPath,
+        common::SimplifyBatchIn,
+        common::SimplifyBatchOut,
+        common::polyline::EncodedPath,
+        common::Vessel,
+        common::Coordinate
+    ))
+)]
+struct ApiDoc;
+
+/// Metrics about the path simplifications done by this service.
+struct SimplifyMetrics {
+    reduction_ratio: prometheus::Histogram,
+}
+
+#[utoipa::path(
+    post,
+    path = "/simplify",
+    request_body(content(
+        (common::SimplifyIn = "application/json"),
+        (common::SimplifyIn = "application/msgpack")
+    )),
+    params(
+        ("polyline" = Option<u32>, Query,
+            description = "Return the path as `EncodedPath` - a polyline with a precision of 5 or 6.")
+    ),
+    responses(
+        (status = 200, content(
+            (common::SimplifyOut = "application/json"),
+            (common::SimplifyOut = "application/msgpack")
+        )),
+        (status = 404, description = "Path could not be simplified."),
+        (status = 422, body = String, description = "Unsupported polyline precision.")
+    )
+)]
+#[post("/simplify", data = "<data>", rank = 2)]
+fn simplify(
+    data: wire::Negotiated<common::SimplifyIn>,
+    metrics: &rocket::State<SimplifyMetrics>,
+) -> Option<wire::Negotiated<common::SimplifyOut>> {
+    let path = simplify_path(&data.vessel.coordinates, data.radius, metrics)?;
+    Some(wire::Negotiated(common::SimplifyOut { path }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/simplify/batch",
+    request_body(content(
+        (common::SimplifyBatchIn = "application/json"),
+        (common::SimplifyBatchIn = "application/msgpack")
+    )),
+    responses(
+        (status = 200, content(
+            (common::SimplifyBatchOut = "application/json"),
+            (common::SimplifyBatchOut = "application/msgpack")
+        ))
+    )
+)]
+#[post("/simplify/batch", data = "<data>")]
+fn simplify_batch(
+    data: wire::Negotiated<common::SimplifyBatchIn>,
+    metrics: &rocket::State<SimplifyMetrics>,
+) -> wire::Negotiated<common::SimplifyBatchOut> {
+    let paths = data
+        .paths
+        .iter()
+        .map(|item| {
+            simplify_path(&item.coordinates, item.radius, metrics)
+                .map(|path| common::SimplifyOut { path })
+        })
+        .collect();
+    wire::Negotiated(common::SimplifyBatchOut { paths })
+}
+
+/// Simplify a path - returned as encoded polyline.
+#[post("/simplify?<polyline>", data = "<data>", rank = 1)]
+fn simplify_polyline(
+    data: wire::Negotiated<common::SimplifyIn>,
+    polyline: u32,
+    metrics: &rocket::State<SimplifyMetrics>,
+) -> Result<Option<wire::Negotiated<common::polyline::EncodedPath>>, (http::Status, String)> {
+    let Some(path) = simplify_path(&data.vessel.coordinates, data.radius, metrics) else {
+        return Ok(None);
+    };
+    common::polyline::EncodedPath::new(&path, polyline)
+        .map(|path| Some(wire::Negotiated(path)))
+        .map_err(|err| (http::Status::UnprocessableEntity, err.to_string()))
+}
+
+/// Drops coordinates within the radius of the previous one kept - None if less than two remain.
+fn simplify_path(
+    coordinates: &[common::Coordinate],
+    radius: f64,
+    metrics: &SimplifyMetrics,
+) -> Option<Vec<common::Coordinate>> {
+    if coordinates.len() <= 1 {
         return None;
     }
     let mut optimized_path: Vec<common::Coordinate> = vec![];
-    let tmp = data.vessel.coordinates.first().unwrap(); // I can unwrap as I know there is a first elem.
+    let tmp = coordinates.first().unwrap(); // I can unwrap as I know there is a first elem.
     optimized_path.push(*tmp);
 
-    for n in 1..data.vessel.coordinates.len() {
+    for trg_coord in &coordinates[1..] {
         let src_coord = &optimized_path.last().unwrap();
-        let trg_coord = &data.vessel.coordinates[n];
-        let distance = common::distance(src_coord.0, src_coord.1, trg_coord.0, trg_coord.1);
-        if distance > data.radius {
+        let distance = common::distance(src_coord.lat, src_coord.lon, trg_coord.lat, trg_coord.lon);
+        if distance > radius {
             optimized_path.push(*trg_coord)
         }
     }
@@ -24,25 +126,69 @@ fn simplify(data: json::Json<common::SimplifyIn>) -> Option<json::Json<common::S
     if optimized_path.len() < 2 {
         None
     } else {
-        Some(json::Json(common::SimplifyOut {
-            path: optimized_path,
-        }))
+        metrics
+            .reduction_ratio
+            .observe(1.0 - optimized_path.len() as f64 / coordinates.len() as f64);
+        Some(optimized_path)
     }
 }
 
+/// Returns the OpenAPI specification of this service.
+#[utoipa::path(get, path = "/openapi.json", responses((status = 200, body = Object)))]
+#[get("/openapi.json")]
+fn openapi() -> json::Json<utoipa::openapi::OpenApi> {
+    json::Json(ApiDoc::openapi())
+}
+
+/// Returns the service metrics and the metrics for the path simplification.
+fn get_metrics() -> Result<(common::metrics::Metrics, SimplifyMetrics), prometheus::Error> {
+    let metrics = common::metrics::Metrics::new("path_function")?;
+    let reduction_ratio = prometheus::Histogram::with_opts(
+        prometheus::HistogramOpts::new(
+            "path_function_reduction_ratio",
+            "Ratio of coordinates removed by the path simplification.",
+        )
+        .buckets(prometheus::linear_buckets(0.1, 0.1, 10)?),
+    )?;
+    metrics
+        .registry()
+        .register(Box::new(reduction_ratio.clone()))?;
+    Ok((metrics, SimplifyMetrics { reduction_ratio }))
+}
+
 #[catch(default)]
 fn error() -> &'static str {
     "Whoops doopsie."
 }
 
-#[launch]
-fn rocket() -> _ {
-    let figment = rocket::Config::figment()
-        .merge(("port", 8765))
-        .merge(("address", "0.0.0.0"));
-    rocket::custom(figment)
-        .mount("/", routes![simplify])
+/// Builds the rocket engine.
+fn rocket() -> rocket::Rocket<rocket::Build> {
+    let (metrics, simplify_metrics) = get_metrics().expect("Could not setup metrics.");
+    rocket::custom(common::config::figment(8765))
+        .mount(
+            "/",
+            common::trace::instrument(routes![
+                simplify,
+                simplify_polyline,
+                simplify_batch,
+                openapi
+            ]),
+        )
         .register("/", catchers![error])
+        .attach(common::cors::fairing())
+        .attach(common::trace::Tracing)
+        .attach(common::compression::Compression)
+        .attach(metrics)
+        .attach(common::health::Health::new(true))
+        .manage(simplify_metrics)
+}
+
+/// Launches the rocket engine.
+#[rocket::main]
+async fn main() {
+    common::trace::init();
+    // launch errors are reported by rocket itself once dropped.
+    let _ = rocket().launch().await;
 }
 
 #[cfg(test)]
@@ -53,6 +199,7 @@ mod tests {
     use super::rocket;
     use rocket::http::Status;
     use rocket::local::blocking::Client;
+    use std::{env, fs};
 
     #[test]
     fn test_rocket_for_success() {
@@ -63,30 +210,48 @@ mod tests {
             Status::from_code(404).expect("should be 404")
         );
         // assert_eq!(response.into_string(), Some("Hello, world!".into()));
+
+        let response = client
+            .options("/simplify")
+            .header(rocket::http::Header::new("Origin", "http://localhost:8080"))
+            .header(rocket::http::Header::new(
+                "Access-Control-Request-Method",
+                "POST",
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::NoContent);
     }
 
     #[test]
     fn test_simplify_for_success() {
         let item = common::SimplifyIn {
             vessel: common::Vessel {
-                mmsi: 123,
                 name: "Boaty McBoatface".to_string(),
-                ship_type: "dummy".to_string(),
                 coordinates: vec![
-                    common::Coordinate(51.453254021051386, 0.7516262537890542),
-                    common::Coordinate(51.457077325466926, 0.762528238401656),
-                    common::Coordinate(51.461044608406944, 0.7766441233094546),
-                    common::Coordinate(51.46357330397952, 0.789980784315918),
+                    common::Coordinate {
+                        lat: 51.453254021051386,
+                        lon: 0.7516262537890542,
+                    },
+                    common::Coordinate {
+                        lat: 51.457077325466926,
+                        lon: 0.762528238401656,
+                    },
+                    common::Coordinate {
+                        lat: 51.461044608406944,
+                        lon: 0.7766441233094546,
+                    },
+                    common::Coordinate {
+                        lat: 51.46357330397952,
+                        lon: 0.789980784315918,
+                    },
                 ],
-                timestamps: vec![],
-                speeds: vec![],
-                headings: vec![],
-                statuses: vec![],
-                destinations: vec![],
+                ..common::Vessel::new(common::mmsi::Mmsi::new(123).unwrap())
             },
             radius: 100.0,
         };
-        simplify(json::Json(item));
+        let rocket = rocket::build().manage(get_metrics().unwrap().1);
+        let metrics = rocket::State::get(&rocket).expect("Metrics state.");
+        simplify(wire::Negotiated(item), metrics);
     }
 
     #[test]
@@ -94,68 +259,236 @@ mod tests {
         // only one coordinate.
         let item = common::SimplifyIn {
             vessel: common::Vessel {
-                mmsi: 123,
                 name: "Boaty McBoatface".to_string(),
-                ship_type: "dummy".to_string(),
-                coordinates: vec![common::Coordinate(51.453254021051386, 0.7516262537890542)],
-                timestamps: vec![],
-                speeds: vec![],
-                headings: vec![],
-                statuses: vec![],
-                destinations: vec![],
+                coordinates: vec![common::Coordinate {
+                    lat: 51.453254021051386,
+                    lon: 0.7516262537890542,
+                }],
+                ..common::Vessel::new(common::mmsi::Mmsi::new(123).unwrap())
             },
             radius: 100.0,
         };
-        let res = simplify(json::Json(item));
-        assert_eq!(res.is_none(), true, "This shouldn't happen.");
+        let rocket = rocket::build().manage(get_metrics().unwrap().1);
+        let metrics = rocket::State::get(&rocket).expect("Metrics state.");
+        let res = simplify(wire::Negotiated(item), metrics);
+        assert!(res.is_none(), "This shouldn't happen.");
 
         // two equal coordinates.
         let item = common::SimplifyIn {
             vessel: common::Vessel {
-                mmsi: 123,
                 name: "Boaty McBoatface".to_string(),
-                ship_type: "dummy".to_string(),
                 coordinates: vec![
-                    common::Coordinate(51.453254021051386, 0.7516262537890542),
-                    common::Coordinate(51.453254021051386, 0.7516262537890542),
+                    common::Coordinate {
+                        lat: 51.453254021051386,
+                        lon: 0.7516262537890542,
+                    },
+                    common::Coordinate {
+                        lat: 51.453254021051386,
+                        lon: 0.7516262537890542,
+                    },
                 ],
-                timestamps: vec![],
-                speeds: vec![],
-                headings: vec![],
-                statuses: vec![],
-                destinations: vec![],
+                ..common::Vessel::new(common::mmsi::Mmsi::new(123).unwrap())
             },
             radius: 100.0,
         };
-        let res = simplify(json::Json(item));
-        assert_eq!(res.is_none(), true, "This shouldn't happen.")
+        let res = simplify(wire::Negotiated(item), metrics);
+        assert!(res.is_none(), "This shouldn't happen.")
     }
 
     #[test]
+    #[allow(clippy::excessive_precision)]
     fn test_simplify_for_sanity() {
         let item = common::SimplifyIn {
             vessel: common::Vessel {
-                mmsi: 123,
                 name: "Boaty McBoatface".to_string(),
-                ship_type: "dummy".to_string(),
                 coordinates: vec![
-                    common::Coordinate(51.453254021051386, 0.7516262537890542),
-                    common::Coordinate(51.457077325466926, 0.762528238401656),
-                    common::Coordinate(51.461044608406944, 0.7766441233094546),
-                    common::Coordinate(51.461044608405944, 0.7776441233094546),
-                    common::Coordinate(51.46357330397952, 0.789980784315918),
+                    common::Coordinate {
+                        lat: 51.453254021051386,
+                        lon: 0.7516262537890542,
+                    },
+                    common::Coordinate {
+                        lat: 51.457077325466926,
+                        lon: 0.762528238401656,
+                    },
+                    common::Coordinate {
+                        lat: 51.461044608406944,
+                        lon: 0.7766441233094546,
+                    },
+                    common::Coordinate {
+                        lat: 51.461044608405944,
+                        lon: 0.7776441233094546,
+                    },
+                    common::Coordinate {
+                        lat: 51.46357330397952,
+                        lon: 0.789980784315918,
+                    },
                 ],
-                timestamps: vec![],
-                speeds: vec![],
-                headings: vec![],
-                statuses: vec![],
-                destinations: vec![],
+                ..common::Vessel::new(common::mmsi::Mmsi::new(123).unwrap())
             },
             radius: 100.0,
         };
-        let res = simplify(json::Json(item));
+        let (service_metrics, simplify_metrics) = get_metrics().unwrap();
+        let rocket = rocket::build().manage(simplify_metrics);
+        let metrics = rocket::State::get(&rocket).expect("Metrics state.");
+        let res = simplify(wire::Negotiated(item), metrics);
         if let Some(v) = res {
             assert_eq!(v.path.len(), 4); // shortened by one step.
         }
+        let res = service_metrics.render();
+        assert!(res.contains("path_function_reduction_ratio_bucket{le=\"0.1\"} 0"));
+        assert!(res.contains("path_function_reduction_ratio_count 1"));
+    }
+
+    #[test]
+    fn test_rocket_for_sanity() {
+        // MessagePack in both directions - compressed if accepted.
+        let item = common::SimplifyIn {
+            vessel: common::Vessel {
+                name: "Boaty McBoatface".to_string(),
+                coordinates: (0..1000)
+                    .map(|i| common::Coordinate {
+                        lat: 51.0 + i as f64 * 0.01,
+                        lon: 0.75,
+                    })
+                    .collect(),
+                ..common::Vessel::new(common::mmsi::Mmsi::new(123).unwrap())
+            },
+            radius: 100.0,
+        };
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/simplify")
+            .header(rocket::http::ContentType::new("application", "msgpack"))
+            .header(rocket::http::Header::new("Accept", "application/msgpack"))
+            .body(wire::encode(&item).unwrap())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.content_type(),
+            Some(rocket::http::ContentType::new("application", "msgpack"))
+        );
+        let res: common::SimplifyOut = wire::decode(&response.into_bytes().unwrap()).unwrap();
+        // coordinates are transferred with a precision of 1e-7 degrees.
+        assert_eq!(res.path.len(), item.vessel.coordinates.len());
+        for (a, b) in res.path.iter().zip(&item.vessel.coordinates) {
+            assert!((a.lat - b.lat).abs() < 1e-7 && (a.lon - b.lon).abs() < 1e-7);
+        }
+
+        let response = client
+            .post("/simplify")
+            .header(rocket::http::ContentType::JSON)
+            .header(rocket::http::Header::new("Accept-Encoding", "gzip"))
+            .body(json::serde_json::to_string(&item).unwrap())
+            .dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(rocket::http::ContentType::JSON)
+        );
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    }
+
+    #[test]
+    fn test_simplify_polyline_for_sanity() {
+        let body = json::serde_json::json!({
+            "vessel": {
+                "mmsi": 123,
+                "name": "Boaty McBoatface",
+                "ship_type": "dummy",
+                // tracks are also accepted as encoded polyline.
+                "coordinates": {"polyline": "_p~iF~ps|U_ulLnnqC_mqNvxq`@", "precision": 5},
+                "timestamps": [],
+                "speeds": [],
+                "headings": [],
+                "statuses": [],
+                "destinations": []
+            },
+            "radius": 100.0
+        });
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/simplify?polyline=5")
+            .header(http::ContentType::JSON)
+            .body(body.to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let res = response
+            .into_json::<common::polyline::EncodedPath>()
+            .unwrap();
+        assert_eq!(res.polyline, "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+        assert_eq!(res.precision, 5);
+
+        // plain path by default.
+        let response = client
+            .post("/simplify")
+            .header(http::ContentType::JSON)
+            .body(body.to_string())
+            .dispatch();
+        let res = response.into_json::<common::SimplifyOut>().unwrap();
+        assert_eq!(res.path.len(), 3);
+        assert_eq!(res.path[2].lat, 43.252);
+
+        let response = client
+            .post("/simplify?polyline=7")
+            .header(http::ContentType::JSON)
+            .body(body.to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn test_simplify_batch_for_sanity() {
+        let body = json::serde_json::json!({
+            "paths": [
+                {
+                    "coordinates": {"polyline": "_p~iF~ps|U_ulLnnqC_mqNvxq`@", "precision": 5},
+                    "radius": 100.0
+                },
+                // only one coordinate.
+                {"coordinates": [{"lat": 51.45, "lon": 0.75}], "radius": 100.0}
+            ]
+        });
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/simplify/batch")
+            .header(http::ContentType::JSON)
+            .body(body.to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let res = response.into_json::<common::SimplifyBatchOut>().unwrap();
+        assert_eq!(res.paths.len(), 2);
+        assert_eq!(res.paths[0].as_ref().map(|item| item.path.len()), Some(3));
+        assert!(res.paths[1].is_none());
+    }
+
+    #[test]
+    fn test_openapi_for_sanity() {
+        // set UPDATE_OPENAPI to regenerate the committed specification.
+        let spec = json::serde_json::to_string_pretty(&ApiDoc::openapi()).expect("a valid spec.");
+        if env::var("UPDATE_OPENAPI").is_ok() {
+            fs::write("openapi.json", format!("{}\n", spec)).expect("a writable openapi.json.");
+        }
+        // read at runtime - a regenerated specification is compared as written.
+        let committed = fs::read_to_string("openapi.json").expect("a readable openapi.json.");
+        let committed: json::Value =
+            json::serde_json::from_str(&committed).expect("a valid committed spec.");
+        let served: json::Value = json::serde_json::from_str(&spec).unwrap();
+        assert_eq!(
+            served, committed,
+            "The OpenAPI specification drifted - rerun the tests with UPDATE_OPENAPI=1."
+        );
+
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client.get("/openapi.json").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_json::<json::Value>(), Some(committed));
+    }
+
+    #[test]
+    fn test_health_for_sanity() {
+        let client = Client::tracked(rocket()).expect("valid rocket instance");
+        let response = client.get("/healthz").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let response = client.get("/readyz").dispatch();
+        assert_eq!(response.status(), Status::Ok);
     }
 }